@@ -1,6 +1,6 @@
 // ordering, ord? std stuff idk
 sort(T(ord)) :: func(data *[]T) {
-	had_update := true
+	mut had_update := true;
 	
 	while had_update {
 		had_update = false;
@@ -8,7 +8,7 @@ sort(T(ord)) :: func(data *[]T) {
 		for i in 0..(data.len() - 1) {
 			if data[i] < data[i + 1] continue;
 			
-			data.swap(i, i + 1)
+			data.swap(i, i + 1);
 			had_update = true;
 		}
 	}
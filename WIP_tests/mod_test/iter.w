@@ -10,16 +10,16 @@ iter(T):new :: func(data *[]T) iter(T) {
 	}
 }
 
-iter(T):next :: func(self *iter(T)) option(*T) {
-	if self.data.len() >= self.offset option:none
+iter(T):next :: func(self *mut iter(T)) option(*T) {
+	if self.data.len() >= self.offset -> option:none
 	else {
-		tmp := option:some(&self.data[self.offset])
+		tmp := option:some(&self.data[self.offset]);
 		self.offset += 1;
 		tmp
 	}
 }
 
-iter(T):advance :: func(self *iter(T), by usize) {
+iter(T):advance :: func(self *mut iter(T), by usize) {
 	self.offset += by;
 }
 
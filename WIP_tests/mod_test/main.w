@@ -9,11 +9,11 @@ add_t(A, B, C) :: trait {
 }
 
 conv_t(A, B) :: trait {
-	conv(A) B,
+	conv func(A) B,
 }
 
-add_t(A=add_t(A, A, A), B=conv_t(B, A), A) :: impl {
-	add func(a A, b B) C {
-		add_t:add(a, conv_t(_, B):conv(b))
+impl(A(add_t(A, A, A)), B(conv_t(B, A))) :: add_t(A, B, A) (A) {
+	add func(a A, b B) A {
+		add_t:add(a, conv_t:conv(b))
 	}
 }
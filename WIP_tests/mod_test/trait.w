@@ -1,12 +1,12 @@
 disposable(Self) :: trait {
-    dispose func(self *Self),
+    dispose func(self *mut Self),
 }
 
 rc(T) :: @(no_copy) struct {
-    inner *rc_inner(T),
+    inner *mut rc_inner(T),
 }
 
-rc_inner(T) : struct {
+rc_inner(T) :: struct {
     ref_count usize,
     drop_fn func(*T),
 
@@ -21,8 +21,8 @@ rc(T):new :: func(val *T, drop_fn func(*T)) rc(T) {
     }
 }
 
-rc(T):copy :: func(self *rc(T)) option(rc(T)) {
-    if self.inner.ref_count == 0 option:none
+rc(T):copy :: func(self *mut rc(T)) option(rc(T)) {
+    if self.inner.ref_count == 0 -> option:none
     else {
         self.inner.ref_count += 1;
         rc {
@@ -32,17 +32,17 @@ rc(T):copy :: func(self *rc(T)) option(rc(T)) {
 }
 
 rc(T):value :: func(self *rc(T)) option(*T) {
-    if self.inner.ref_count == 0 option:none
-    else option:some(&self.inner.value)
+    if self.inner.ref_count == 0 -> option:none
+    else -> option:some(&self.inner.value)
 }
 
 impl(T) :: disposable (rc(T)) {
-    dispose func(self *rc (T)) {
+    dispose func(self *mut rc(T)) {
         if self.inner.ref_count == 0 return;
 
         self.inner.ref_count -= 1;
         if self.inner.ref_count == 0
-            self.inner.drop_fn(&self.inner.value);
+            -> self.inner.drop_fn(&self.inner.value);
     }
 }
 
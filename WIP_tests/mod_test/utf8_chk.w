@@ -15,16 +15,16 @@ utf8_test :: func(cstr *[]u8) bool {
 	while option:some(char) := it.next() {
 		if char & 0x80 == 0 continue;
 		
-		extra := if char >> 5 == 0b110 1
-			else if char >> 4 == 0b1110 2
-			else if char >> 3 == 0b11110 3
+		extra := if char >> 5 == 0b110 -> 1
+			else if char >> 4 == 0b1110 -> 2
+			else if char >> 3 == 0b11110 -> 3
 			else return false;
 
 		for _ in 0..extra
-			if option:some(nchar) := it.next()
+			if option:some(nchar) := it.next() {
 				if nchar >> 6 == 0b10 continue;
 				return false;
-			else
+			} else
 				return false;
 	}
 
@@ -7,7 +7,7 @@ some_test :: option(str) := test("test");
 
 // const?
 test :: func(s str) option(str) {
-    option::some(s)
+    option:some(s)
 }
 
 main :: func() {
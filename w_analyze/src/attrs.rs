@@ -0,0 +1,77 @@
+use crate::data::err::UnknownAttributeWarning;
+use crate::ErrorCollector;
+use w_parse::item::attr::Attribute;
+use w_parse::item::named::NamedKind;
+use w_parse::item::Item;
+use w_parse::types::ItemTy;
+use w_parse::ParsedModule;
+
+/// `@no_copy` forbids implicit copies of a type,
+/// `@extern("symbol")` binds a definition to an external symbol,
+/// `@int_to_ptr` allows a function to cast integers to pointers
+pub const KNOWN_ATTRIBUTES: &[&str] = &["no_copy", "extern", "int_to_ptr"];
+
+/// Finds the first attribute with the given name
+pub fn find_attr<'a>(attrs: &'a [Attribute], name: &str) -> Option<&'a Attribute> {
+    debug_assert!(KNOWN_ATTRIBUTES.contains(&name), "unknown attribute {name}");
+    attrs.iter().find(|attr| **attr.name().0 == *name)
+}
+
+/// Warns about every attribute in the module the compiler does not know
+pub fn check_attributes(module: &ParsedModule, errs: &ErrorCollector) {
+    for item in module.items.iter() {
+        let def = match item {
+            Item::Definer(def) => def,
+            Item::Method(method) => {
+                check(&method.attrs, errs);
+                continue;
+            }
+            Item::Impl(_) | Item::Import(_) | Item::Modules(_) => continue,
+        };
+
+        check(&def.attrs, errs);
+        if let NamedKind::Type(ty) = &def.kind {
+            check_ty(&ty.ty, errs);
+        }
+    }
+}
+
+fn check_ty(ty: &ItemTy, errs: &ErrorCollector) {
+    match ty {
+        ItemTy::Struct(st) => {
+            for field in &st.fields {
+                check(&field.attrs, errs);
+                check_ty(&field.ty, errs);
+            }
+        }
+        ItemTy::Enum(en) => {
+            for variant in &en.variants {
+                check(&variant.attrs, errs);
+                for ty in variant.fields.iter().flat_map(|tp| &tp.types) {
+                    check_ty(ty, errs);
+                }
+            }
+        }
+        ItemTy::Tuple(tp) => tp.types.iter().for_each(|ty| check_ty(ty, errs)),
+        ItemTy::Applied(app) => app.args.types.iter().for_each(|ty| check_ty(ty, errs)),
+        ItemTy::Func(func) => {
+            func.args.iter().for_each(|ty| check_ty(ty, errs));
+            if let Some(ret_ty) = &func.ret_ty {
+                check_ty(ret_ty, errs);
+            }
+        }
+        ItemTy::Array(arr) => check_ty(&arr.ty, errs),
+        ItemTy::Pointer(ptr) => check_ty(&ptr.ty, errs),
+        ItemTy::Referred(_) | ItemTy::Never(_) => (),
+    }
+}
+
+fn check(attrs: &[Attribute], errs: &ErrorCollector) {
+    for attr in attrs {
+        if !KNOWN_ATTRIBUTES.contains(&&**attr.name().0) {
+            errs.add_error(UnknownAttributeWarning {
+                loc: attr.span.clone(),
+            });
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use crate::operands::ValueClass;
+use w_parse::expr::assign::AssignOp;
+use w_tokenize::Span;
+
+pub struct AssignError {
+    /// The assignment operator
+    pub loc: Span,
+    pub kind: AssignErrorKind,
+}
+
+pub enum AssignErrorKind {
+    /// Only names, fields, elements and dereferenced pointers can be assigned to
+    NotAPlace,
+    /// One side of a compound assignment does not fit the operator
+    Operand { op: AssignOp, found: ValueClass },
+    /// The sides of a compound assignment are different kinds or widths of numbers
+    Mismatch {
+        op: AssignOp,
+        place: ValueClass,
+        value: ValueClass,
+    },
+}
+
+impl AnalyzerError for AssignError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        let (desc, note) = match &self.kind {
+            AssignErrorKind::NotAPlace => (
+                "Can not assign to this expression".to_string(),
+                "Only bindings, fields, elements and dereferenced pointers can be assigned to",
+            ),
+            AssignErrorKind::Operand { op, found } => (
+                format!(
+                    "`{}` can not be used with {}",
+                    op_str(*op),
+                    found.describe()
+                ),
+                match op {
+                    AssignOp::Shl
+                    | AssignOp::Shr
+                    | AssignOp::And
+                    | AssignOp::Or
+                    | AssignOp::Xor => "Bitwise assignments need integers on both sides",
+                    _ => "Arithmetic assignments need numbers on both sides",
+                },
+            ),
+            AssignErrorKind::Mismatch { op, place, value } => (
+                format!(
+                    "`{}` can not combine {} with {}",
+                    op_str(*op),
+                    place.name(),
+                    value.name()
+                ),
+                "Cast the value with `as` to the type of the place",
+            ),
+        };
+
+        f.err()
+            .description(desc)
+            .location(self.loc.clone())
+            .add_note(note)
+            .submit();
+    }
+}
+
+fn op_str(op: AssignOp) -> &'static str {
+    match op {
+        AssignOp::Assign => "=",
+        AssignOp::Add => "+=",
+        AssignOp::Sub => "-=",
+        AssignOp::Mul => "*=",
+        AssignOp::Div => "/=",
+        AssignOp::Mod => "%=",
+        AssignOp::And => "&=",
+        AssignOp::Or => "|=",
+        AssignOp::Xor => "^=",
+        AssignOp::Shl => "<<=",
+        AssignOp::Shr => ">>=",
+    }
+}
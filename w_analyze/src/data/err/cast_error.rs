@@ -0,0 +1,37 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use crate::operands::ValueClass;
+use w_tokenize::Span;
+
+pub struct CastError {
+    /// The `as` of the cast
+    pub loc: Span,
+    pub from: ValueClass,
+    pub to: ValueClass,
+}
+
+impl AnalyzerError for CastError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        let (desc, note) = match (self.from, self.to) {
+            (ValueClass::Integer(_), ValueClass::Pointer) => (
+                "Integers can only be cast to pointers in functions marked `@int_to_ptr`"
+                    .to_string(),
+                "Mark the function with `@int_to_ptr` if the address is known to be valid",
+            ),
+            (from, to) => (
+                format!("Can not cast {} to {}", from.describe(), to.describe()),
+                "Numbers can be cast to numbers and pointers to pointers",
+            ),
+        };
+
+        f.err()
+            .description(desc)
+            .location(self.loc.clone())
+            .add_note(note)
+            .submit();
+    }
+}
@@ -0,0 +1,31 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use w_tokenize::Span;
+
+/// A `return`, `break` or `continue` which would leave a deferred expression
+pub struct DeferError {
+    pub defer: Span,
+    pub jump: Span,
+    pub keyword: &'static str,
+}
+
+impl AnalyzerError for DeferError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        f.err()
+            .description(format!(
+                "`{}` can not leave a deferred expression",
+                self.keyword
+            ))
+            .location(self.jump.clone())
+            .add_elaboration()
+            .description("The expression is deferred here")
+            .location(self.defer.clone())
+            .add_note("Deferred expressions already run while the scope is left")
+            .build_help()
+            .submit();
+    }
+}
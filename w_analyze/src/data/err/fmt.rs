@@ -1,8 +1,14 @@
 use crate::data::err::fmt::builder::StepDesc;
 use crate::data::err::ErrKind;
+use std::fmt::Write;
+use w_rcstr::{expand_tabs, SourceMap};
 use w_tokenize::Span;
 
-pub struct ErrorFormatter {}
+/// Collects the diagnostics submitted by [`AnalyzerError::fmt`](crate::data::err::AnalyzerError::fmt)
+#[derive(Default)]
+pub struct ErrorFormatter {
+    errors: Vec<Error>,
+}
 
 pub struct Error {
     kind: ErrKind,
@@ -10,6 +16,7 @@ pub struct Error {
     description: String,
     location: Span,
 
+    elaborations: Vec<Error>,
     help: Vec<Error>,
     notes: Vec<String>,
 }
@@ -66,7 +73,7 @@ macro_rules! define_builder_steps {
 }
 
 impl ErrorFormatter {
-    pub fn err(&mut self) -> StepDesc {
+    pub fn err(&mut self) -> StepDesc<'_> {
         StepDesc(ErrorBuilder {
             fmt: Some(self),
             kind: ErrKind::Error,
@@ -78,7 +85,7 @@ impl ErrorFormatter {
         })
     }
 
-    pub fn warn(&mut self) -> StepDesc {
+    pub fn warn(&mut self) -> StepDesc<'_> {
         StepDesc(ErrorBuilder {
             fmt: Some(self),
             kind: ErrKind::Warning,
@@ -90,8 +97,60 @@ impl ErrorFormatter {
         })
     }
 
-    pub fn submit(&mut self, _error: Error) {
-        todo!()
+    pub fn submit(&mut self, error: Error) {
+        self.errors.push(error);
+    }
+
+    /// Renders everything submitted so far, resolving file names through `sources`
+    pub fn render(&self, sources: &SourceMap) -> String {
+        let mut out = String::new();
+        for error in &self.errors {
+            let label = match error.kind {
+                ErrKind::Error => "error",
+                ErrKind::Warning => "warning",
+            };
+            render_error(&mut out, sources, error, label);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn render_error(out: &mut String, sources: &SourceMap, error: &Error, label: &str) {
+    let frag = error.location.fragment();
+    let pos = frag.line_col();
+    let line_no = (pos.line + 1).to_string();
+    let gutter = " ".repeat(line_no.len());
+    let marker = frag.marker();
+
+    writeln!(out, "{label}: {}", error.description).unwrap();
+    match sources.locate(frag) {
+        Some(loc) => writeln!(out, "{gutter}--> {loc}").unwrap(),
+        None => writeln!(out, "{gutter}--> {}:{}", pos.line + 1, pos.chars + 1).unwrap(),
+    }
+    writeln!(out, "{gutter} |").unwrap();
+    writeln!(
+        out,
+        "{line_no} | {}",
+        expand_tabs(frag.line(pos.line).unwrap())
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "{gutter} | {}{}",
+        " ".repeat(marker.start),
+        "^".repeat(marker.len())
+    )
+    .unwrap();
+    for note in &error.notes {
+        writeln!(out, "{gutter} = note: {note}").unwrap();
+    }
+
+    for elaboration in &error.elaborations {
+        render_error(out, sources, elaboration, "note");
+    }
+    for help in &error.help {
+        render_error(out, sources, help, "help");
     }
 }
 
@@ -116,6 +175,7 @@ mod builder {
                     description: self.0.description.unwrap(),
                     location: self.0.location.unwrap(),
 
+                    elaborations: self.0.elaborations,
                     help: self.0.help,
                     notes: self.0.notes,
                 }
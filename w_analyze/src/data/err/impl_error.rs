@@ -0,0 +1,65 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use w_parse::Ident;
+
+pub struct ImplError {
+    pub loc: Ident,
+    pub kind: ImplErrorKind,
+}
+
+pub enum ImplErrorKind {
+    UnknownTrait,
+    TraitArgs {
+        expected: usize,
+        found: usize,
+    },
+    /// The method of the trait which is not implemented
+    MissingMethod(Ident),
+    UnexpectedMethod,
+    /// The method of the trait with the expected signature
+    SignatureMismatch(Ident),
+}
+
+impl AnalyzerError for ImplError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        let err = f.err();
+        match &self.kind {
+            ImplErrorKind::UnknownTrait => err
+                .description("Unable to resolve trait")
+                .location(self.loc.0.clone())
+                .add_note("Try defining the trait")
+                .submit(),
+            ImplErrorKind::TraitArgs { expected, found } => err
+                .description(format!(
+                    "The trait takes {expected} parameters but {found} were given"
+                ))
+                .location(self.loc.0.clone())
+                .submit(),
+            ImplErrorKind::MissingMethod(method) => err
+                .description(format!("Missing method `{}`", method.1))
+                .location(self.loc.0.clone())
+                .add_elaboration()
+                .description("The method is required by the trait here")
+                .location(method.0.clone())
+                .build_help()
+                .submit(),
+            ImplErrorKind::UnexpectedMethod => err
+                .description("The method is not part of the trait")
+                .location(self.loc.0.clone())
+                .add_note("Methods which are not part of the trait can be defined on the type")
+                .submit(),
+            ImplErrorKind::SignatureMismatch(method) => err
+                .description("The signature does not match the trait")
+                .location(self.loc.0.clone())
+                .add_elaboration()
+                .description("Expected signature")
+                .location(method.0.clone())
+                .build_help()
+                .submit(),
+        };
+    }
+}
@@ -0,0 +1,47 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use w_tokenize::Span;
+
+pub struct ImportError {
+    pub loc: Span,
+    pub kind: ImportErrorKind,
+}
+
+#[derive(Copy, Clone)]
+pub enum ImportErrorKind {
+    /// `super` used in the root module
+    AboveRoot,
+    /// A path segment which is not a declared module
+    UnknownModule,
+    /// The module has no item with this name
+    Unresolved,
+}
+
+impl AnalyzerError for ImportError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        let (desc, note) = match self.kind {
+            ImportErrorKind::AboveRoot => (
+                "The root module has no parent",
+                "`super` refers to the module which declared the current one",
+            ),
+            ImportErrorKind::UnknownModule => (
+                "Unable to resolve module",
+                "Modules have to be declared with `{ name } :: mod`",
+            ),
+            ImportErrorKind::Unresolved => (
+                "Unable to resolve import",
+                "Only types, traits and modules can be imported",
+            ),
+        };
+
+        f.err()
+            .description(desc)
+            .location(self.loc.clone())
+            .add_note(note)
+            .submit();
+    }
+}
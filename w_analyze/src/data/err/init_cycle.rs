@@ -0,0 +1,41 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use w_parse::Ident;
+
+pub struct InitCycleError {
+    /// Every value or function in the cycle with the use of the next one, starting at a value
+    pub chain: Vec<(Ident, Ident)>,
+}
+
+impl AnalyzerError for InitCycleError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        let (first, _) = &self.chain[0];
+        let names = self
+            .chain
+            .iter()
+            .map(|(name, _)| name.1.as_str())
+            .chain([first.1.as_str()])
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        let mut err = f
+            .err()
+            .description(format!(
+                "The initializer of `{}` depends on itself",
+                first.1
+            ))
+            .location(first.0.clone());
+        for (name, usage) in &self.chain {
+            err = err
+                .add_elaboration()
+                .description(format!("`{}` uses `{}` here", name.1, usage.1))
+                .location(usage.0.clone())
+                .build_help();
+        }
+        err.add_note(format!("The cycle is {names}")).submit();
+    }
+}
@@ -1,17 +1,35 @@
 mod array_fix;
+mod assign_error;
+mod cast_error;
+mod defer_error;
 mod duplicate_import;
-mod fmt;
+pub mod fmt;
+mod impl_error;
+mod import_error;
+mod init_cycle;
 mod multiple_definitions;
+mod mutability;
+mod pattern_error;
 mod recursive_type;
+mod unknown_attribute;
 mod unresolved_type;
 
 use crate::data::err::fmt::ErrorFormatter;
 use std::cell::RefCell;
 
 pub use array_fix::*;
+pub use assign_error::*;
+pub use cast_error::*;
+pub use defer_error::*;
 pub use duplicate_import::*;
+pub use impl_error::*;
+pub use import_error::*;
+pub use init_cycle::*;
 pub use multiple_definitions::*;
+pub use mutability::*;
+pub use pattern_error::*;
 pub use recursive_type::*;
+pub use unknown_attribute::*;
 pub use unresolved_type::*;
 
 #[derive(Default)]
@@ -20,7 +38,7 @@ pub struct ErrorCollector {
     has_errors: RefCell<bool>,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ErrKind {
     Error,
     Warning,
@@ -45,4 +63,20 @@ impl ErrorCollector {
     pub fn has_errors(&self) -> bool {
         *self.has_errors.borrow()
     }
+
+    /// Hands every collected diagnostic to the formatter
+    pub fn format(&self, f: &mut ErrorFormatter) {
+        for err in self.errors.borrow().iter() {
+            err.fmt(f);
+        }
+    }
+
+    /// Number of collected diagnostics of the given kind
+    pub fn count(&self, kind: ErrKind) -> usize {
+        self.errors
+            .borrow()
+            .iter()
+            .filter(|err| err.kind() == kind)
+            .count()
+    }
 }
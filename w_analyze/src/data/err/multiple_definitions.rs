@@ -12,7 +12,11 @@ pub struct MultipleDefinitionsError {
 pub enum DefinitionKind {
     Type,
     Func,
+    Method,
+    Trait,
     Import,
+    Module,
+    Value,
 }
 
 impl AnalyzerError for MultipleDefinitionsError {
@@ -30,10 +34,26 @@ impl AnalyzerError for MultipleDefinitionsError {
                 "The func name is conflicting with another previous definition",
                 "Conflicting func defined here",
             ),
+            DefinitionKind::Method => (
+                "The type already has a method with this name",
+                "Conflicting method defined here",
+            ),
+            DefinitionKind::Trait => (
+                "The trait name is conflicting with another previous definition",
+                "Conflicting trait defined here",
+            ),
             DefinitionKind::Import => (
                 "A import under that name is already defined at a previous location",
                 "Other import location",
             ),
+            DefinitionKind::Value => (
+                "The value name is conflicting with another previous definition",
+                "Conflicting definition here",
+            ),
+            DefinitionKind::Module => (
+                "The module is declared more than once",
+                "First declared here",
+            ),
         };
 
         f.err()
@@ -0,0 +1,53 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use w_parse::Ident;
+use w_tokenize::Span;
+
+pub struct MutabilityError {
+    /// The binding which would have to be mutable
+    pub binding: Ident,
+    pub write: Span,
+    pub kind: MutabilityErrorKind,
+}
+
+#[derive(Copy, Clone)]
+pub enum MutabilityErrorKind {
+    Assign,
+    Borrow,
+    /// Writing through a `*T` instead of a `*mut T`
+    ThroughPointer,
+}
+
+impl AnalyzerError for MutabilityError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        let name = &self.binding.1;
+        let (msg, note) = match self.kind {
+            MutabilityErrorKind::Assign => (
+                format!("Can not assign twice to the immutable binding `{name}`"),
+                format!("Declare the binding as `mut {name}` to allow writes"),
+            ),
+            MutabilityErrorKind::Borrow => (
+                format!("Can not borrow the immutable binding `{name}` as mutable"),
+                format!("Declare the binding as `mut {name}` to allow mutable borrows"),
+            ),
+            MutabilityErrorKind::ThroughPointer => (
+                format!("Can not write through `{name}`, it is not a mutable pointer"),
+                "Only `*mut T` allows writing to what it points to".to_string(),
+            ),
+        };
+
+        f.err()
+            .description(msg)
+            .location(self.write.clone())
+            .add_elaboration()
+            .description("The binding is declared here")
+            .location(self.binding.0.clone())
+            .add_note(note)
+            .build_help()
+            .submit();
+    }
+}
@@ -0,0 +1,88 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use w_parse::Ident;
+use w_tokenize::Span;
+
+pub struct PatternError {
+    pub loc: Span,
+    /// The type the pattern was checked against
+    pub ty: Option<Ident>,
+    pub kind: PatternErrorKind,
+}
+
+pub enum PatternErrorKind {
+    NotAnEnum,
+    UnknownVariant,
+    /// `None` for variants without a field tuple
+    FieldCount {
+        expected: Option<usize>,
+        found: Option<usize>,
+    },
+    NotAStruct,
+    UnknownField,
+    /// Values no arm matches, written as patterns
+    NonExhaustive(Vec<String>),
+    /// A pattern outside of a condition which does not match every value
+    Refutable,
+}
+
+impl AnalyzerError for PatternError {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Error
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        let count = |fields: Option<usize>| match fields {
+            Some(n) => format!("{n} fields"),
+            None => "no field tuple".to_string(),
+        };
+        let (desc, note) = match &self.kind {
+            PatternErrorKind::NotAnEnum => ("Only enums have variants".to_string(), None),
+            PatternErrorKind::UnknownVariant => ("The enum has no such variant".to_string(), None),
+            PatternErrorKind::FieldCount { expected, found } => (
+                format!(
+                    "The variant has {} but the pattern has {}",
+                    count(*expected),
+                    count(*found)
+                ),
+                None,
+            ),
+            PatternErrorKind::NotAStruct => (
+                "Only structs can be matched with field patterns".to_string(),
+                None,
+            ),
+            PatternErrorKind::UnknownField => ("The struct has no such field".to_string(), None),
+            PatternErrorKind::NonExhaustive(missing) => (
+                "The arms do not cover every value".to_string(),
+                Some(format!(
+                    "Not covered: {}",
+                    missing
+                        .iter()
+                        .map(|pat| format!("`{pat}`"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )),
+            ),
+            PatternErrorKind::Refutable => (
+                "The pattern does not match every value".to_string(),
+                Some(
+                    "Use `if pattern := value { ... }` to only run code if it matches".to_string(),
+                ),
+            ),
+        };
+
+        let mut err = f.err().description(desc).location(self.loc.clone());
+        if let Some(note) = note {
+            err = err.add_note(note);
+        }
+        match &self.ty {
+            Some(ty) => err
+                .add_elaboration()
+                .description("The type is defined here")
+                .location(ty.0.clone())
+                .build_help()
+                .submit(),
+            None => err.submit(),
+        };
+    }
+}
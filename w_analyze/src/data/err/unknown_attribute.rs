@@ -0,0 +1,25 @@
+use crate::attrs::KNOWN_ATTRIBUTES;
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{AnalyzerError, ErrKind};
+use w_tokenize::Span;
+
+pub struct UnknownAttributeWarning {
+    pub loc: Span,
+}
+
+impl AnalyzerError for UnknownAttributeWarning {
+    fn kind(&self) -> ErrKind {
+        ErrKind::Warning
+    }
+
+    fn fmt(&self, f: &mut ErrorFormatter) {
+        f.warn()
+            .description("Unknown attribute, it will be ignored")
+            .location(self.loc.clone())
+            .add_note(format!(
+                "Known attributes are: {}",
+                KNOWN_ATTRIBUTES.join(", ")
+            ))
+            .submit();
+    }
+}
@@ -8,35 +8,49 @@ mod typemap;
 pub mod types;
 
 use crate::data::file::FileRef;
-use crate::data::path::{Path, PathBuf};
-use crate::data::types::TypeRef;
+use crate::data::path::PathBuf;
+use crate::data::types::{MethodRef, TraitDef, TypeRef};
 use either::Either;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ops::Deref;
 use typed_arena::Arena;
 
-use w_parse::Ident;
+use w_parse::expr::path::PathRoot;
+use w_parse::{Ident, Symbol};
 
 pub struct Module<'gc> {
     pub types_arena: &'gc Arena<TypeRef<'gc>>,
     pub modules_arena: &'gc Arena<Self>,
 
-    pub types: RefCell<HashMap<Ident, &'gc TypeRef<'gc>>>,
-    pub modules: RefCell<HashMap<Ident, &'gc Self>>,
-
-    // the module is where the item is from and not the item it self
-    // pub imports: RefCell<HashMap<Ident<'a>, &'gc Self>>,
+    pub types: RefCell<HashMap<Symbol, &'gc TypeRef<'gc>>>,
+    pub modules: RefCell<HashMap<Symbol, &'gc Self>>,
+    /// Methods of the types in [`Self::types`], by type and method name
+    pub methods: RefCell<HashMap<Symbol, HashMap<Symbol, MethodRef<'gc>>>>,
+    pub traits: RefCell<HashMap<Symbol, TraitDef<'gc>>>,
+    /// Child modules declared with `{ a } :: mod`
+    pub declared: RefCell<HashMap<Symbol, Ident>>,
+    /// Names of the types and traits the module defines, known before any imports are resolved
+    pub items: RefCell<HashMap<Symbol, Ident>>,
+    pub imports: RefCell<HashMap<Symbol, Import<'gc>>>,
+    /// Constants and statics in the order their initializers run
+    pub values: RefCell<Vec<Ident>>,
     pub previous: Option<&'gc Self>,
 
     pub path: PathBuf,
-    pub name: Option<Ident>,
+    pub name: Option<Symbol>,
 
     pub owner: ModuleOwner<'gc>,
 }
 
 pub type ModuleOwner<'gc> = Option<Either<FileRef, Location<'gc>>>;
 
+/// An imported item, the module is where the item is from and not the item itself
+pub struct Import<'gc> {
+    pub name: Ident,
+    pub from: &'gc Module<'gc>,
+}
+
 #[derive(Clone)]
 pub struct Location<'gc> {
     pub name: Ident,
@@ -55,6 +69,7 @@ impl<'gc> Module<'gc> {
     pub fn new(
         path: PathBuf,
         owner: ModuleOwner<'gc>,
+        previous: Option<&'gc Self>,
         modules: &'gc Arena<Self>,
         types: &'gc Arena<TypeRef<'gc>>,
     ) -> &'gc Self {
@@ -63,9 +78,14 @@ impl<'gc> Module<'gc> {
             modules_arena: modules,
             types: RefCell::new(HashMap::new()),
             modules: RefCell::new(HashMap::new()),
-            // imports: RefCell::new(HashMap::new()),
-            previous: None,
-            name: Some(path.last().expect("path must contain module name").clone()),
+            methods: RefCell::new(HashMap::new()),
+            traits: RefCell::new(HashMap::new()),
+            declared: RefCell::new(HashMap::new()),
+            items: RefCell::new(HashMap::new()),
+            imports: RefCell::new(HashMap::new()),
+            values: RefCell::new(vec![]),
+            previous,
+            name: Some(*path.last().expect("path must contain module name")),
             path,
             owner,
         })
@@ -77,7 +97,12 @@ impl<'gc> Module<'gc> {
             modules_arena: modules,
             types: RefCell::new(HashMap::new()),
             modules: RefCell::new(HashMap::new()),
-            // imports: RefCell::new(HashMap::new()),
+            methods: RefCell::new(HashMap::new()),
+            traits: RefCell::new(HashMap::new()),
+            declared: RefCell::new(HashMap::new()),
+            items: RefCell::new(HashMap::new()),
+            imports: RefCell::new(HashMap::new()),
+            values: RefCell::new(vec![]),
             previous: None,
             path: PathBuf::default(),
             name: None,
@@ -93,46 +118,51 @@ impl<'gc> Module<'gc> {
         self.previous.map_or(self, |p| p.root())
     }
 
-    pub fn access_or_create_type(&'gc self, path: &Path) -> &'gc TypeRef<'gc> {
-        if path.is_empty() {
-            panic!("empty path provided");
+    /// The module a path starts at, `None` if it goes above the root with `super`
+    pub fn path_root(&'gc self, root: Option<&PathRoot>) -> Option<&'gc Module<'gc>> {
+        match root.map(PathRoot::depth) {
+            None => Some(self),
+            Some(None) => Some(self.root()),
+            Some(Some(depth)) => (0..depth).try_fold(self, |md, _| md.previous),
         }
-        let name = path.last().unwrap().clone();
+    }
 
-        let md_path = path.slice(..path.len() - 1);
+    /// The first mention of a type is used as its location until it is defined
+    pub fn access_or_create_type(&'gc self, path: &[Ident]) -> &'gc TypeRef<'gc> {
+        let (name, md_path) = path.split_last().expect("empty path provided");
         let md = self.access_or_create_module(md_path);
 
-        *md.types
-            .borrow_mut()
-            .entry(name.clone())
-            .or_insert_with(|| {
-                &*self.types_arena.alloc(TypeRef {
-                    loc: Location { name, home: md },
-                    definition: RefCell::new(None),
-                })
+        md.types.borrow_mut().entry(name.sym()).or_insert_with(|| {
+            &*self.types_arena.alloc(TypeRef {
+                loc: Location {
+                    name: name.clone(),
+                    home: md,
+                },
+                definition: RefCell::new(None),
             })
+        })
     }
 
-    pub fn access_or_create_module(&'gc self, path: &Path) -> &'gc Module<'gc> {
-        if path.is_empty() {
+    pub fn access_or_create_module(&'gc self, path: &[Ident]) -> &'gc Module<'gc> {
+        let Some((next, rest)) = path.split_first() else {
             return self;
-        }
+        };
 
-        let next = path.first().unwrap();
         let next = *self
             .modules
             .borrow_mut()
-            .entry(next.clone())
+            .entry(next.sym())
             .or_insert_with(|| {
                 Module::new(
-                    self.path.join(next.clone()),
+                    self.path.join(next),
                     None,
+                    Some(self),
                     self.modules_arena,
                     self.types_arena,
                 )
             });
 
-        next.access_or_create_module(path.slice(1..))
+        next.access_or_create_module(rest)
     }
 }
 
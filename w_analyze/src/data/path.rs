@@ -1,18 +1,20 @@
 use std::borrow::Borrow;
+use std::fmt::{Display, Formatter};
 use std::mem::transmute;
 use std::ops::{Bound, Deref, RangeBounds};
 use std::slice::Iter;
-use w_parse::Ident;
+use w_parse::{Ident, Symbol};
 
+/// A path of interned names, spans are left behind in the [`Ident`]s it was built from
 #[repr(transparent)]
 #[derive(Hash, PartialEq, Eq)]
 pub struct Path {
-    path: [Ident],
+    path: [Symbol],
 }
 
 #[derive(Clone, Debug, Default, Hash, Eq, PartialEq)]
 pub struct PathBuf {
-    path: Vec<Ident>,
+    path: Vec<Symbol>,
 }
 
 impl PathBuf {
@@ -22,8 +24,8 @@ impl PathBuf {
 }
 
 impl PathBuf {
-    pub fn append(&mut self, ident: Ident) {
-        self.path.push(ident);
+    pub fn append(&mut self, sym: impl Into<Symbol>) {
+        self.path.push(sym.into());
     }
 
     pub fn append_path(&mut self, path: &Path) {
@@ -32,7 +34,7 @@ impl PathBuf {
 }
 
 impl Path {
-    pub fn join(&self, other: Ident) -> PathBuf {
+    pub fn join(&self, other: impl Into<Symbol>) -> PathBuf {
         let mut path = self.to_owned();
         path.append(other);
         path
@@ -62,27 +64,44 @@ impl Path {
     }
 }
 
-impl ToString for Path {
-    fn to_string(&self) -> String {
-        let mut buf = String::new();
-
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let mut iter = self.path.iter();
 
-        if let Some(ident) = iter.next() {
-            buf.push_str(&*ident.0);
+        if let Some(sym) = iter.next() {
+            f.write_str(sym.as_str())?;
         }
-        for ident in iter {
-            buf.push(':');
-            buf.push_str(&*ident.0);
+        for sym in iter {
+            write!(f, ":{sym}")?;
         }
 
-        buf
+        Ok(())
+    }
+}
+
+impl From<&[Ident]> for PathBuf {
+    fn from(idents: &[Ident]) -> Self {
+        idents.iter().collect()
     }
 }
 
-impl<'a, V: Into<Vec<Ident>>> From<V> for PathBuf {
-    fn from(vec: V) -> Self {
-        Self { path: vec.into() }
+impl From<Vec<Ident>> for PathBuf {
+    fn from(idents: Vec<Ident>) -> Self {
+        idents.iter().collect()
+    }
+}
+
+impl<const N: usize> From<[Ident; N]> for PathBuf {
+    fn from(idents: [Ident; N]) -> Self {
+        idents.iter().collect()
+    }
+}
+
+impl<S: Into<Symbol>> FromIterator<S> for PathBuf {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        Self {
+            path: iter.into_iter().map(Into::into).collect(),
+        }
     }
 }
 
@@ -95,7 +114,7 @@ impl Deref for PathBuf {
 }
 
 impl Deref for Path {
-    type Target = [Ident];
+    type Target = [Symbol];
 
     fn deref(&self) -> &Self::Target {
         &self.path
@@ -118,11 +137,11 @@ impl ToOwned for Path {
     }
 }
 
-impl<'a, 'b> IntoIterator for &'b Path {
-    type Item = &'b Ident;
-    type IntoIter = Iter<'b, Ident>;
+impl<'b> IntoIterator for &'b Path {
+    type Item = &'b Symbol;
+    type IntoIter = Iter<'b, Symbol>;
 
     fn into_iter(self) -> Self::IntoIter {
-        (&self.path).iter()
+        self.path.iter()
     }
 }
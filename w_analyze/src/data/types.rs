@@ -1,9 +1,10 @@
 use std::cell::RefCell;
+use std::ptr;
 
-use w_tokenize::Span;
+use w_tokenize::{IntTy, Span};
 
 use crate::data::Location;
-use crate::PathBuf;
+use w_parse::expr::path::ExprPath;
 use w_parse::Ident;
 
 pub struct TypeRef<'gc> {
@@ -11,14 +12,108 @@ pub struct TypeRef<'gc> {
     pub definition: RefCell<Option<TypeInfo<'gc>>>,
 }
 
+/// A method in the table of its receiver type, see [`Module::methods`](crate::data::Module::methods)
+pub struct MethodRef<'gc> {
+    pub name: Ident,
+    pub func: TypeFunc<'gc>,
+}
+
+pub struct TraitDef<'gc> {
+    pub name: Ident,
+    /// Generic parameters without `Self`, which always stands for the implementing type
+    pub params: Vec<Ident>,
+    pub methods: Vec<(Ident, TypeFunc<'gc>)>,
+}
+
 pub enum TypeInfo<'gc> {
-    Owned { kind: TypeKind<'gc> },
+    Owned {
+        kind: TypeKind<'gc>,
+    },
     Proxy(&'gc TypeRef<'gc>),
+    /// A built in type, its name was not defined by the module
+    Primitive(Primitive),
+}
+
+/// The numbers built into the language, named like the suffixes of number literals
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Primitive {
+    Int(IntTy),
+    Float(FloatTy),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FloatTy {
+    F32,
+    F64,
+}
+
+impl Primitive {
+    pub fn from_name(name: &str) -> Option<Self> {
+        FloatTy::from_suffix(name)
+            .map(Primitive::Float)
+            .or_else(|| IntTy::from_suffix(name).map(Primitive::Int))
+    }
+}
+
+impl FloatTy {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        match suffix {
+            "f32" => Some(FloatTy::F32),
+            "f64" => Some(FloatTy::F64),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            FloatTy::F32 => "f32",
+            FloatTy::F64 => "f64",
+        }
+    }
+}
+
+/// The definition behind a type once imports and aliases are followed
+#[derive(Clone)]
+pub enum Resolved<'gc> {
+    Primitive(Primitive),
+    /// Never an alias, [`TypeKind::Referred`] and [`TypeKind::Applied`] are followed
+    Kind(TypeKind<'gc>),
+}
+
+impl<'gc> TypeRef<'gc> {
+    /// Follows imports and aliases to the definition of the type,
+    /// `None` if it is not defined or the aliases are cyclic.
+    ///
+    /// Names of built in types are those types until the module defines them itself.
+    pub fn resolve(&'gc self) -> Option<Resolved<'gc>> {
+        let mut seen: Vec<&TypeRef> = vec![];
+        let mut tref = self;
+        // cyclic aliases are reported by the recursive reference check
+        while !seen.iter().any(|seen| ptr::eq(*seen, tref)) {
+            seen.push(tref);
+            tref = match &*tref.definition.borrow() {
+                None => {
+                    return Primitive::from_name(tref.loc.name.1.as_str()).map(Resolved::Primitive)
+                }
+                Some(TypeInfo::Primitive(prim)) => return Some(Resolved::Primitive(*prim)),
+                Some(TypeInfo::Proxy(next)) => next,
+                Some(TypeInfo::Owned { kind }) => match kind {
+                    TypeKind::Referred(next, _)
+                    | TypeKind::Applied(TypeApplied { ty: next, .. }) => next,
+                    kind => return Some(Resolved::Kind(kind.clone())),
+                },
+            };
+        }
+        None
+    }
 }
 
 #[derive(Clone)]
 pub enum TypeKind<'gc> {
-    Referred(&'gc TypeRef<'gc>, PathBuf),
+    Referred(&'gc TypeRef<'gc>, ExprPath),
+    /// A generic parameter of the item the type belongs to
+    Param(Ident),
+    Applied(TypeApplied<'gc>),
     Array(TypeArray<'gc>),
     Enum(TypeEnum<'gc>),
     Func(TypeFunc<'gc>),
@@ -28,11 +123,20 @@ pub enum TypeKind<'gc> {
     Tuple(TypeTuple<'gc>),
 }
 
+#[derive(Clone)]
+pub struct TypeApplied<'gc> {
+    pub ty: &'gc TypeRef<'gc>,
+    pub path: ExprPath,
+    pub args: TypeTuple<'gc>,
+}
+
 #[derive(Clone)]
 pub struct TypeArray<'gc> {
     pub def: Span,
     pub ty: Box<TypeKind<'gc>>,
     pub len: Option<u64>,
+    /// `[?]T`, never has a `len`
+    pub dynamic: bool,
 }
 
 #[derive(Clone)]
@@ -51,6 +155,7 @@ pub struct TypeFunc<'gc> {
 #[derive(Clone)]
 pub struct TypePtr<'gc> {
     pub def: Span,
+    pub mutable: bool,
     pub ty: Box<TypeKind<'gc>>,
 }
 
@@ -1,4 +1,6 @@
-use crate::data::types::{TypeArray, TypeEnum, TypeFunc, TypeKind, TypePtr, TypeStruct, TypeTuple};
+use crate::data::types::{
+    TypeApplied, TypeArray, TypeEnum, TypeFunc, TypeKind, TypePtr, TypeStruct, TypeTuple,
+};
 use crate::PathBuf;
 use std::collections::HashMap;
 use w_parse::Ident;
@@ -17,6 +19,8 @@ pub struct ElidedType {
 
 pub enum ETyKind {
     Referred(PathBuf),
+    Param(Ident),
+    Applied(PathBuf, ETyTuple),
     Array(ETyArray),
     Enum(ETyEnum),
     Func(ETyFunc),
@@ -29,6 +33,7 @@ pub enum ETyKind {
 pub struct ETyArray {
     pub ty: Box<ETyKind>,
     pub len: Option<u64>,
+    pub dynamic: bool,
 }
 
 pub struct ETyEnum {
@@ -41,6 +46,7 @@ pub struct ETyFunc {
 }
 
 pub struct ETyPtr {
+    pub mutable: bool,
     pub ty: Box<ETyKind>,
 }
 
@@ -54,10 +60,17 @@ pub struct ETyTuple {
 
 pub fn elide_type_kind<'gc>(ty: TypeKind<'gc>) -> ETyKind {
     match ty {
-        TypeKind::Referred(_, pb) => ETyKind::Referred(pb),
-        TypeKind::Array(TypeArray { ty, len, .. }) => ETyKind::Array(ETyArray {
+        TypeKind::Referred(_, path) => ETyKind::Referred(PathBuf::from(path.path)),
+        TypeKind::Param(name) => ETyKind::Param(name),
+        TypeKind::Applied(TypeApplied { path, args, .. }) => {
+            ETyKind::Applied(PathBuf::from(path.path), elide_tuple_kind(args))
+        }
+        TypeKind::Array(TypeArray {
+            ty, len, dynamic, ..
+        }) => ETyKind::Array(ETyArray {
             ty: Box::new(elide_type_kind(*ty)),
             len,
+            dynamic,
         }),
         TypeKind::Enum(TypeEnum { variants, .. }) => ETyKind::Enum(ETyEnum {
             variants: variants
@@ -70,7 +83,8 @@ pub fn elide_type_kind<'gc>(ty: TypeKind<'gc>) -> ETyKind {
             ret: Box::new(elide_type_kind(*ret)),
         }),
         TypeKind::Never(_) => ETyKind::Never,
-        TypeKind::Ptr(TypePtr { ty, .. }) => ETyKind::Ptr(ETyPtr {
+        TypeKind::Ptr(TypePtr { ty, mutable, .. }) => ETyKind::Ptr(ETyPtr {
+            mutable,
             ty: Box::new(elide_type_kind(*ty)),
         }),
         TypeKind::Struct(TypeStruct { fields, .. }) => ETyKind::Struct(ETyStruct {
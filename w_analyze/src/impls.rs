@@ -0,0 +1,187 @@
+use crate::data::err::{DefinitionKind, ImplError, ImplErrorKind, MultipleDefinitionsError};
+use crate::data::types::{
+    TypeApplied, TypeArray, TypeEnum, TypeFunc, TypeKind, TypePtr, TypeStruct, TypeTuple,
+};
+use crate::pass1_tsys::{build_named_func, build_type, path_start};
+use crate::{ErrorCollector, Module};
+use std::collections::HashMap;
+use std::ptr;
+use w_parse::item::r#impl::ItemImpl;
+use w_parse::Symbol;
+
+/// Checks that `imp` provides exactly the methods of its trait with matching signatures
+pub fn check_impl<'gc>(imp: &ItemImpl, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    let generics = imp.generics.as_ref().map_or(&[][..], |g| &g.params);
+    let target = build_type(&imp.target, tsys, generics, errs);
+    let tr_args = imp.tr_args.as_ref().map_or_else(Vec::new, |args| {
+        args.types
+            .iter()
+            .map(|ty| build_type(ty, tsys, generics, errs))
+            .collect()
+    });
+
+    let (tr_name, tr_path) = imp.tr.path.split_last().unwrap();
+    let md = path_start(tsys, &imp.tr).access_or_create_module(tr_path);
+    let traits = md.traits.borrow();
+    let Some(tr) = traits.get(&tr_name.sym()) else {
+        errs.add_error(ImplError {
+            loc: tr_name.clone(),
+            kind: ImplErrorKind::UnknownTrait,
+        });
+        return;
+    };
+
+    if tr.params.len() != tr_args.len() {
+        errs.add_error(ImplError {
+            loc: tr_name.clone(),
+            kind: ImplErrorKind::TraitArgs {
+                expected: tr.params.len(),
+                found: tr_args.len(),
+            },
+        });
+        return;
+    }
+
+    let mut subst = tr
+        .params
+        .iter()
+        .map(|param| param.sym())
+        .zip(&tr_args)
+        .collect::<HashMap<_, _>>();
+    subst.insert(Symbol::intern("Self"), &target);
+
+    let mut seen = HashMap::new();
+    for method in &imp.methods {
+        if let Some(first) = seen.insert(method.name.sym(), &method.name) {
+            errs.add_error(MultipleDefinitionsError {
+                loc: method.name.clone(),
+                first: first.clone(),
+                kind: DefinitionKind::Method,
+            });
+            continue;
+        }
+
+        let Some((expected_name, expected)) =
+            tr.methods.iter().find(|(name, _)| *name == method.name)
+        else {
+            errs.add_error(ImplError {
+                loc: method.name.clone(),
+                kind: ImplErrorKind::UnexpectedMethod,
+            });
+            continue;
+        };
+
+        let found = build_named_func(&method.func.func, tsys, generics, errs);
+        if !same_func(expected, &found, &subst) {
+            errs.add_error(ImplError {
+                loc: method.name.clone(),
+                kind: ImplErrorKind::SignatureMismatch(expected_name.clone()),
+            });
+        }
+    }
+
+    for (name, _) in &tr.methods {
+        if !seen.contains_key(&name.sym()) {
+            errs.add_error(ImplError {
+                loc: tr_name.clone(),
+                kind: ImplErrorKind::MissingMethod(name.clone()),
+            });
+        }
+    }
+}
+
+/// Parameters of the trait in `expected` are replaced through `subst`
+fn same_type<'gc>(
+    expected: &TypeKind<'gc>,
+    found: &TypeKind<'gc>,
+    subst: &HashMap<Symbol, &TypeKind<'gc>>,
+) -> bool {
+    let no_subst = HashMap::new();
+    let same = |a: &TypeKind<'gc>, b: &TypeKind<'gc>| same_type(a, b, subst);
+    let all = |a: &[TypeKind<'gc>], b: &[TypeKind<'gc>]| {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same(a, b))
+    };
+
+    match (expected, found) {
+        (TypeKind::Param(param), found) if subst.contains_key(&param.sym()) => {
+            // the substitute is written in terms of the impl, just like `found`
+            same_type(subst[&param.sym()], found, &no_subst)
+        }
+        (TypeKind::Param(a), TypeKind::Param(b)) => a == b,
+        (TypeKind::Referred(a, _), TypeKind::Referred(b, _)) => ptr::eq(*a, *b),
+        (
+            TypeKind::Applied(TypeApplied {
+                ty: a, args: aa, ..
+            }),
+            TypeKind::Applied(TypeApplied {
+                ty: b, args: ba, ..
+            }),
+        ) => ptr::eq(*a, *b) && all(&aa.fields, &ba.fields),
+        (
+            TypeKind::Array(TypeArray {
+                ty: a,
+                len: al,
+                dynamic: ad,
+                ..
+            }),
+            TypeKind::Array(TypeArray {
+                ty: b,
+                len: bl,
+                dynamic: bd,
+                ..
+            }),
+        ) => al == bl && ad == bd && same(a, b),
+        (
+            TypeKind::Ptr(TypePtr {
+                ty: a, mutable: am, ..
+            }),
+            TypeKind::Ptr(TypePtr {
+                ty: b, mutable: bm, ..
+            }),
+        ) => am == bm && same(a, b),
+        (
+            TypeKind::Tuple(TypeTuple { fields: a, .. }),
+            TypeKind::Tuple(TypeTuple { fields: b, .. }),
+        ) => all(a, b),
+        (TypeKind::Func(a), TypeKind::Func(b)) => same_func(a, b, subst),
+        (TypeKind::Never(_), TypeKind::Never(_)) => true,
+        (
+            TypeKind::Struct(TypeStruct { fields: a, .. }),
+            TypeKind::Struct(TypeStruct { fields: b, .. }),
+        ) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b)
+                    .all(|((an, at), (bn, bt))| an == bn && same(at, bt))
+        }
+        (
+            TypeKind::Enum(TypeEnum { variants: a, .. }),
+            TypeKind::Enum(TypeEnum { variants: b, .. }),
+        ) => {
+            a.len() == b.len()
+                && a.iter().zip(b).all(|((an, at), (bn, bt))| {
+                    an == bn
+                        && match (at, bt) {
+                            (Some(at), Some(bt)) => all(&at.fields, &bt.fields),
+                            (None, None) => true,
+                            _ => false,
+                        }
+                })
+        }
+        _ => false,
+    }
+}
+
+fn same_func<'gc>(
+    expected: &TypeFunc<'gc>,
+    found: &TypeFunc<'gc>,
+    subst: &HashMap<Symbol, &TypeKind<'gc>>,
+) -> bool {
+    expected.args.len() == found.args.len()
+        && expected
+            .args
+            .iter()
+            .zip(&found.args)
+            .all(|(a, b)| same_type(a, b, subst))
+        && same_type(&expected.ret, &found.ret, subst)
+}
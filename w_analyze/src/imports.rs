@@ -0,0 +1,143 @@
+use crate::data::err::{
+    DefinitionKind, DuplicateImport, ImportError, ImportErrorKind, MultipleDefinitionsError,
+};
+use crate::data::Import;
+use crate::{ErrorCollector, Module};
+use std::collections::hash_map::Entry;
+use std::slice;
+use w_parse::item::import::Imports;
+use w_parse::item::named::{ItemNamed, NamedKind};
+use w_parse::item::Item;
+use w_parse::{Ident, ParsedModule};
+
+/// Registers the child modules declared with `{ a, b } :: mod` and the names of types and traits.
+///
+/// Every module of the tree has to be declared before [`resolve_imports`] runs for any of them.
+pub fn declare_module<'gc>(module: &ParsedModule, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    for item in module.items.iter() {
+        match item {
+            Item::Modules(decl) => {
+                for name in &decl.modules {
+                    declare_child(name, tsys, errs);
+                }
+            }
+            // defining a name twice is reported with the definitions
+            Item::Definer(ItemNamed {
+                name,
+                kind: NamedKind::Type(_) | NamedKind::Trait(_),
+                ..
+            }) => {
+                tsys.items
+                    .borrow_mut()
+                    .entry(name.sym())
+                    .or_insert_with(|| name.clone());
+            }
+            _ => (),
+        }
+    }
+}
+
+fn declare_child<'gc>(name: &Ident, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    match tsys.declared.borrow_mut().entry(name.sym()) {
+        Entry::Occupied(first) => errs.add_error(MultipleDefinitionsError {
+            loc: name.clone(),
+            first: first.get().clone(),
+            kind: DefinitionKind::Module,
+        }),
+        Entry::Vacant(slot) => {
+            slot.insert(name.clone());
+        }
+    }
+    tsys.access_or_create_module(slice::from_ref(name));
+}
+
+/// Resolves imports against the declared module tree,
+/// the modules they are taken from do not have to be analyzed yet
+pub fn resolve_imports<'gc>(module: &ParsedModule, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    for item in module.items.iter() {
+        let Item::Import(imp) = item else {
+            continue;
+        };
+
+        let Some(from) = tsys.path_root(imp.from.root.as_ref()) else {
+            errs.add_error(ImportError {
+                loc: imp.from.root.as_ref().unwrap().span().clone(),
+                kind: ImportErrorKind::AboveRoot,
+            });
+            continue;
+        };
+        let Some(from) = walk(from, &imp.from.path, errs) else {
+            continue;
+        };
+
+        for leaf in &imp.imports {
+            resolve_import(leaf, from, tsys, errs);
+        }
+    }
+}
+
+fn resolve_import<'gc>(
+    imp: &Imports,
+    from: &'gc Module<'gc>,
+    tsys: &'gc Module<'gc>,
+    errs: &ErrorCollector,
+) {
+    let path = match imp {
+        Imports::Multiple(base, leaves) => {
+            if let Some(from) = walk(from, &base.path, errs) {
+                for leaf in leaves {
+                    resolve_import(leaf, from, tsys, errs);
+                }
+            }
+            return;
+        }
+        Imports::Single(path) => path,
+    };
+
+    let (name, base) = path.path.split_last().unwrap();
+    let Some(from) = walk(from, base, errs) else {
+        return;
+    };
+
+    let sym = name.sym();
+    let exists =
+        from.declared.borrow().contains_key(&sym) || from.items.borrow().contains_key(&sym);
+    if !exists {
+        errs.add_error(ImportError {
+            loc: name.0.clone(),
+            kind: ImportErrorKind::Unresolved,
+        });
+        return;
+    }
+
+    match tsys.imports.borrow_mut().entry(sym) {
+        Entry::Occupied(first) => errs.add_error(DuplicateImport {
+            original: first.get().name.0.clone(),
+            new: name.0.clone(),
+        }),
+        Entry::Vacant(slot) => {
+            slot.insert(Import {
+                name: name.clone(),
+                from,
+            });
+        }
+    }
+}
+
+/// Follows the declared modules along the path
+fn walk<'gc>(
+    md: &'gc Module<'gc>,
+    path: &[Ident],
+    errs: &ErrorCollector,
+) -> Option<&'gc Module<'gc>> {
+    path.iter().try_fold(md, |md, name| {
+        if !md.declared.borrow().contains_key(&name.sym()) {
+            errs.add_error(ImportError {
+                loc: name.0.clone(),
+                kind: ImportErrorKind::UnknownModule,
+            });
+            return None;
+        }
+        Some(md.access_or_create_module(slice::from_ref(name)))
+    })
+}
@@ -11,11 +11,23 @@ use crate::vmod::ModuleProvider;
 use typed_arena::Arena;
 use w_parse::Ident;
 
+pub mod attrs;
 pub mod data;
 pub mod elided;
+pub mod impls;
+pub mod imports;
+pub mod lower;
+pub mod mutability;
+pub mod operands;
 pub mod pass1_tsys;
+pub mod patterns;
+mod scope;
+pub mod values;
 pub mod vmod;
 
+#[cfg(test)]
+mod tests;
+
 pub struct AnalyzerOptions {
     dependencies: Vec<Ident>,
 }
@@ -0,0 +1,82 @@
+use crate::data::err::DeferError;
+use crate::pass1_tsys::module_funcs;
+use crate::ErrorCollector;
+use w_parse::expr::{ChildMut, Expr};
+use w_parse::item::named::{ItemNamed, NamedKind};
+use w_parse::item::Item;
+use w_parse::ParsedModule;
+
+/// Reports deferred expressions which try to leave their scope with a jump
+pub fn check_defers(module: &ParsedModule, errs: &ErrorCollector) {
+    for func in module_funcs(module) {
+        func.body.exprs().for_each(|expr| check_expr(expr, errs));
+    }
+    for item in module.items.iter() {
+        if let Item::Definer(ItemNamed {
+            kind: NamedKind::Value(value),
+            ..
+        }) = item
+        {
+            check_expr(&value.value, errs);
+        }
+    }
+}
+
+fn check_expr(expr: &Expr, errs: &ErrorCollector) {
+    if let Expr::Defer(defer) = expr {
+        if let Some((jump, keyword)) = defer.leaving_jump() {
+            errs.add_error(DeferError {
+                defer: defer.span_defer.clone(),
+                jump,
+                keyword,
+            });
+        }
+    }
+    expr.for_each_child(&mut |child| check_expr(child, errs));
+}
+
+/// Rewrites every body onto the expressions later stages handle,
+/// `for` loops become `while` loops and deferred expressions run on every exit of their block.
+///
+/// Only valid once the module passed [`check_defers`].
+pub fn lower_module(module: &mut ParsedModule) {
+    for item in module.items.iter_mut() {
+        match item {
+            Item::Definer(ItemNamed {
+                kind: NamedKind::Func(func),
+                ..
+            }) => {
+                func.body.exprs_mut().for_each(desugar_loops);
+                func.body.lower_defers();
+            }
+            Item::Definer(ItemNamed {
+                kind: NamedKind::Value(value),
+                ..
+            }) => {
+                desugar_loops(&mut value.value);
+                value.value.lower_defers();
+            }
+            Item::Method(method) => {
+                method.func.body.exprs_mut().for_each(desugar_loops);
+                method.func.body.lower_defers();
+            }
+            Item::Impl(imp) => {
+                for method in imp.methods.iter_mut() {
+                    method.func.body.exprs_mut().for_each(desugar_loops);
+                    method.func.body.lower_defers();
+                }
+            }
+            Item::Definer(_) | Item::Import(_) | Item::Modules(_) => (),
+        }
+    }
+}
+
+fn desugar_loops(expr: &mut Expr) {
+    if let Expr::For(lp) = expr {
+        *expr = Expr::Block(lp.desugar());
+    }
+    expr.for_each_child_mut(&mut |child| match child {
+        ChildMut::Expr(expr) => desugar_loops(expr),
+        ChildMut::Block(block) => block.exprs_mut().for_each(desugar_loops),
+    })
+}
@@ -0,0 +1,240 @@
+use crate::data::err::{MutabilityError, MutabilityErrorKind};
+use crate::pass1_tsys::module_funcs;
+use crate::scope::Scope;
+use crate::ErrorCollector;
+use w_parse::expr::block::ExprBlock;
+use w_parse::expr::loops::{ExprFor, ExprLoop, ExprWhile};
+use w_parse::expr::pattern::Pattern;
+use w_parse::expr::unary::{ExprUnary, UnOp};
+use w_parse::expr::Expr;
+use w_parse::item::func::ItemFunc;
+use w_parse::item::named::{ItemNamed, NamedKind};
+use w_parse::item::Item;
+use w_parse::types::ItemTy;
+use w_parse::{Ident, ParsedModule};
+use w_tokenize::Span;
+
+/// Reports writes to immutable bindings and mutable borrows of them in every function body
+pub fn check_mutability(module: &ParsedModule, errs: &ErrorCollector) {
+    for func in module_funcs(module) {
+        check_func(func, globals(module), errs);
+    }
+}
+
+/// Constants can not be written to, statics declared with `mut` can
+fn globals(module: &ParsedModule) -> Scope<Binding> {
+    module
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Definer(ItemNamed {
+                name,
+                kind: NamedKind::Value(value),
+                ..
+            }) => Some((
+                name.clone(),
+                Binding {
+                    mutable: value.mutability.is_some(),
+                    pointer: value.ty.as_ref().and_then(pointer_ty),
+                },
+            )),
+            _ => None,
+        })
+        .collect()
+}
+
+struct Binding {
+    mutable: bool,
+    /// `Some(mutable)` if the binding is known to hold a pointer
+    pointer: Option<bool>,
+}
+
+fn check_func(func: &ItemFunc, mut scope: Scope<Binding>, errs: &ErrorCollector) {
+    for arg in &func.func.args {
+        scope.declare(
+            arg.name.clone(),
+            Binding {
+                mutable: arg.mutability.is_some(),
+                pointer: pointer_ty(&arg.ty),
+            },
+        );
+    }
+
+    check_block(&func.body, &mut scope, errs);
+}
+
+fn pointer_ty(ty: &ItemTy) -> Option<bool> {
+    match ty {
+        ItemTy::Pointer(ptr) => Some(ptr.mutability.is_some()),
+        _ => None,
+    }
+}
+
+fn check_block(block: &ExprBlock, scope: &mut Scope<Binding>, errs: &ErrorCollector) {
+    scope.nested(|scope| block.exprs().for_each(|expr| check_expr(expr, scope, errs)));
+}
+
+fn check_expr(expr: &Expr, scope: &mut Scope<Binding>, errs: &ErrorCollector) {
+    match expr {
+        Expr::Define(define) => {
+            check_expr(&define.value, scope, errs);
+            let pointer = match &define.pattern {
+                Pattern::Binding(_) => pointer_of(&define.value, scope),
+                _ => None,
+            };
+            declare(&define.pattern, pointer, scope);
+        }
+        Expr::Assign(assign) => {
+            check_expr(&assign.value, scope, errs);
+            check_place(
+                &assign.assignee,
+                &assign.span_op,
+                MutabilityErrorKind::Assign,
+                scope,
+                errs,
+            );
+        }
+        Expr::Unary(ExprUnary {
+            op: UnOp::RefMut(and, _),
+            expr,
+        }) => check_place(expr, and, MutabilityErrorKind::Borrow, scope, errs),
+        Expr::Block(block) | Expr::Loop(ExprLoop { body: block, .. }) => {
+            check_block(block, scope, errs)
+        }
+        // bindings in the condition are visible in the bodies
+        Expr::Branch(branch) => scope.nested(|scope| {
+            check_expr(&branch.cond, scope, errs);
+            check_block(&branch.body, scope, errs);
+            if let Some(body_else) = &branch.body_else {
+                check_block(body_else, scope, errs);
+            }
+        }),
+        Expr::While(ExprWhile { cond, body, .. }) => scope.nested(|scope| {
+            check_expr(cond, scope, errs);
+            check_block(body, scope, errs);
+        }),
+        Expr::Match(mt) => {
+            check_expr(&mt.value, scope, errs);
+            for arm in &mt.arms {
+                scope.nested(|scope| {
+                    declare(&arm.pattern, None, scope);
+                    check_block(&arm.body, scope, errs);
+                });
+            }
+        }
+        Expr::For(ExprFor {
+            binding,
+            iterable,
+            body,
+            ..
+        }) => {
+            check_expr(iterable, scope, errs);
+            scope.nested(|scope| {
+                declare(binding, None, scope);
+                check_block(body, scope, errs);
+            });
+        }
+        expr => expr.for_each_child(&mut |child| check_expr(child, scope, errs)),
+    }
+}
+
+/// Nested places like `a.b[c]` are checked against their root
+fn check_place(
+    place: &Expr,
+    write: &Span,
+    kind: MutabilityErrorKind,
+    scope: &mut Scope<Binding>,
+    errs: &ErrorCollector,
+) {
+    let report = |binding: &Ident, kind| {
+        errs.add_error(MutabilityError {
+            binding: binding.clone(),
+            write: write.clone(),
+            kind,
+        })
+    };
+
+    match place {
+        Expr::Ident(name) => match scope.lookup(name) {
+            Some((name, binding)) if !binding.mutable => report(name, kind),
+            _ => (),
+        },
+        Expr::Field(field) => check_base(&field.base, write, kind, scope, errs),
+        Expr::Index(index) => {
+            index
+                .args
+                .values
+                .iter()
+                .for_each(|arg| check_expr(arg, scope, errs));
+            check_base(&index.base, write, kind, scope, errs);
+        }
+        Expr::Unary(ExprUnary {
+            op: UnOp::Deref(_),
+            expr,
+        }) => match &**expr {
+            Expr::Ident(name) => match scope.lookup(name) {
+                Some((name, binding)) if binding.pointer == Some(false) => {
+                    report(name, MutabilityErrorKind::ThroughPointer)
+                }
+                _ => (),
+            },
+            expr => check_expr(expr, scope, errs),
+        },
+        // temporaries can always be written to
+        expr => check_expr(expr, scope, errs),
+    }
+}
+
+/// Fields and elements are reached through pointers implicitly
+fn check_base(
+    base: &Expr,
+    write: &Span,
+    kind: MutabilityErrorKind,
+    scope: &mut Scope<Binding>,
+    errs: &ErrorCollector,
+) {
+    if let Expr::Ident(name) = base {
+        if let Some((name, binding)) = scope.lookup(name) {
+            match binding.pointer {
+                Some(false) => errs.add_error(MutabilityError {
+                    binding: name.clone(),
+                    write: write.clone(),
+                    kind: MutabilityErrorKind::ThroughPointer,
+                }),
+                Some(true) => (),
+                None => check_place(base, write, kind, scope, errs),
+            }
+            return;
+        }
+    }
+
+    check_place(base, write, kind, scope, errs)
+}
+
+/// Whether `value` is known to be a pointer, and if so whether it is mutable
+fn pointer_of(value: &Expr, scope: &Scope<Binding>) -> Option<bool> {
+    match value {
+        Expr::Unary(ExprUnary {
+            op: UnOp::Ref(_), ..
+        }) => Some(false),
+        Expr::Unary(ExprUnary {
+            op: UnOp::RefMut(_, _),
+            ..
+        }) => Some(true),
+        Expr::Ident(name) => scope.lookup(name).and_then(|(_, bind)| bind.pointer),
+        _ => None,
+    }
+}
+
+/// Declares every name bound by `pattern`
+fn declare(pattern: &Pattern, pointer: Option<bool>, scope: &mut Scope<Binding>) {
+    pattern.for_each_binding(&mut |binding| {
+        scope.declare(
+            binding.name.clone(),
+            Binding {
+                mutable: binding.mutability.is_some(),
+                pointer,
+            },
+        )
+    });
+}
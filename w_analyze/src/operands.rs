@@ -0,0 +1,352 @@
+use crate::attrs::find_attr;
+use crate::data::err::{AssignError, AssignErrorKind, CastError};
+use crate::data::types::{FloatTy, Primitive, Resolved, TypeKind};
+use crate::pass1_tsys::{generic_param, lookup_type};
+use crate::scope::Scope;
+use crate::{ErrorCollector, Module};
+use w_parse::expr::assign::ExprAssignment;
+use w_parse::expr::block::ExprBlock;
+use w_parse::expr::cast::ExprCast;
+use w_parse::expr::loops::{ExprFor, ExprLoop, ExprWhile};
+use w_parse::expr::ops::BiOp;
+use w_parse::expr::pattern::Pattern;
+use w_parse::expr::unary::{ExprUnary, UnOp};
+use w_parse::expr::Expr;
+use w_parse::item::func::ItemFunc;
+use w_parse::item::generics::GenericParam;
+use w_parse::item::named::{ItemNamed, NamedKind};
+use w_parse::item::Item;
+use w_parse::types::applied::TyApplied;
+use w_parse::types::ItemTy;
+use w_parse::ParsedModule;
+use w_tokenize::IntTy;
+
+/// Numbers cast to numbers and pointers to pointers,
+/// integers only cast to pointers in functions marked with `@int_to_ptr`.
+///
+/// Assignments need a place on the left, compound ones numbers or integers on both sides.
+/// Both sides of compound arithmetic are the same kind and width of number,
+/// the amount of a shift may be any integer.
+pub fn check_operands<'gc>(module: &ParsedModule, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    for item in module.items.iter() {
+        let (attrs, generics, funcs) = match item {
+            Item::Definer(ItemNamed {
+                attrs,
+                generics,
+                kind: NamedKind::Func(func),
+                ..
+            }) => (&attrs[..], generics, vec![func]),
+            Item::Method(method) => (&method.attrs[..], &method.generics, vec![&method.func]),
+            Item::Impl(imp) => (
+                &[][..],
+                &imp.generics,
+                imp.methods.iter().map(|m| &m.func).collect(),
+            ),
+            Item::Definer(_) | Item::Import(_) | Item::Modules(_) => continue,
+        };
+
+        let checker = Checker {
+            tsys,
+            errs,
+            generics: generics.as_ref().map_or(&[][..], |g| &g.params),
+            int_to_ptr: find_attr(attrs, "int_to_ptr").is_some(),
+        };
+        for func in funcs {
+            checker.check_func(func);
+        }
+    }
+}
+
+/// What the checks know about the type of a value
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ValueClass {
+    /// The width is `None` when it is inferred, e.g. for literals without a suffix
+    Integer(Option<IntTy>),
+    Float(Option<FloatTy>),
+    Pointer,
+    /// Only casts to an integer, which gives its code point
+    Char,
+    /// Anything else, described with its article, e.g. `"a tuple"`
+    Other(&'static str),
+}
+
+impl ValueClass {
+    pub fn is_numeric(self) -> bool {
+        matches!(self, ValueClass::Integer(_) | ValueClass::Float(_))
+    }
+
+    /// Whether both are numbers of the same kind whose widths do not differ
+    fn same_number(self, other: ValueClass) -> bool {
+        fn same<T: Eq>(a: Option<T>, b: Option<T>) -> bool {
+            !matches!((a, b), (Some(a), Some(b)) if a != b)
+        }
+
+        match (self, other) {
+            (ValueClass::Integer(a), ValueClass::Integer(b)) => same(a, b),
+            (ValueClass::Float(a), ValueClass::Float(b)) => same(a, b),
+            _ => false,
+        }
+    }
+
+    pub(crate) fn describe(self) -> &'static str {
+        match self {
+            ValueClass::Integer(_) => "an integer",
+            ValueClass::Float(_) => "a float",
+            ValueClass::Pointer => "a pointer",
+            ValueClass::Char => "a character",
+            ValueClass::Other(desc) => desc,
+        }
+    }
+
+    /// Like [`ValueClass::describe`], numbers of a known width are named by their type
+    pub(crate) fn name(self) -> String {
+        match self {
+            ValueClass::Integer(Some(ty)) => format!("`{}`", ty.name()),
+            ValueClass::Float(Some(ty)) => format!("`{}`", ty.name()),
+            class => class.describe().to_string(),
+        }
+    }
+}
+
+struct Checker<'a, 'gc> {
+    tsys: &'gc Module<'gc>,
+    errs: &'a ErrorCollector,
+    /// Generic parameters of the item, their values are not known
+    generics: &'a [GenericParam],
+    int_to_ptr: bool,
+}
+
+/// What is known about the type of each binding
+type Classes = Scope<Option<ValueClass>>;
+
+impl<'a, 'gc> Checker<'a, 'gc> {
+    fn check_func(&self, func: &ItemFunc) {
+        let mut scope = func
+            .func
+            .args
+            .iter()
+            .map(|arg| (arg.name.clone(), self.target(&arg.ty)))
+            .collect();
+        self.check_block(&func.body, &mut scope);
+    }
+
+    fn check_block(&self, block: &ExprBlock, scope: &mut Classes) {
+        scope.nested(|scope| block.exprs().for_each(|expr| self.check_expr(expr, scope)));
+    }
+
+    fn check_expr(&self, expr: &Expr, scope: &mut Classes) {
+        match expr {
+            Expr::Define(define) => {
+                self.check_expr(&define.value, scope);
+                let class = match &define.pattern {
+                    Pattern::Binding(_) => self.value_class(&define.value, scope),
+                    _ => None,
+                };
+                declare(&define.pattern, class, scope);
+            }
+            Expr::Cast(cast) => {
+                self.check_expr(&cast.base, scope);
+                self.check_cast(cast, scope);
+            }
+            Expr::Assign(assign) => {
+                self.check_expr(&assign.assignee, scope);
+                self.check_expr(&assign.value, scope);
+                self.check_assign(assign, scope);
+            }
+            Expr::Block(block) | Expr::Loop(ExprLoop { body: block, .. }) => {
+                self.check_block(block, scope)
+            }
+            // bindings in the condition are visible in the bodies
+            Expr::Branch(branch) => scope.nested(|scope| {
+                self.check_expr(&branch.cond, scope);
+                self.check_block(&branch.body, scope);
+                if let Some(body_else) = &branch.body_else {
+                    self.check_block(body_else, scope);
+                }
+            }),
+            Expr::While(ExprWhile { cond, body, .. }) => scope.nested(|scope| {
+                self.check_expr(cond, scope);
+                self.check_block(body, scope);
+            }),
+            Expr::Match(mt) => {
+                self.check_expr(&mt.value, scope);
+                for arm in &mt.arms {
+                    scope.nested(|scope| {
+                        declare(&arm.pattern, None, scope);
+                        self.check_block(&arm.body, scope);
+                    });
+                }
+            }
+            Expr::For(ExprFor {
+                binding,
+                iterable,
+                body,
+                ..
+            }) => {
+                self.check_expr(iterable, scope);
+                scope.nested(|scope| {
+                    declare(binding, None, scope);
+                    self.check_block(body, scope);
+                });
+            }
+            expr => expr.for_each_child(&mut |child| self.check_expr(child, scope)),
+        }
+    }
+
+    fn check_cast(&self, cast: &ExprCast, scope: &Classes) {
+        let (Some(from), Some(to)) = (self.value_class(&cast.base, scope), self.target(&cast.ty))
+        else {
+            return;
+        };
+
+        let allowed = match (from, to) {
+            (from, to) if from.is_numeric() && to.is_numeric() => true,
+            (ValueClass::Pointer, ValueClass::Pointer) => true,
+            (ValueClass::Char, ValueClass::Integer(_)) => true,
+            (ValueClass::Integer(_), ValueClass::Pointer) => self.int_to_ptr,
+            _ => false,
+        };
+        if !allowed {
+            self.errs.add_error(CastError {
+                loc: cast.span_as.clone(),
+                from,
+                to,
+            });
+        }
+    }
+
+    fn check_assign(&self, assign: &ExprAssignment, scope: &Classes) {
+        let report = |kind| {
+            self.errs.add_error(AssignError {
+                loc: assign.span_op.clone(),
+                kind,
+            })
+        };
+
+        let place = matches!(
+            &*assign.assignee,
+            Expr::Ident(_)
+                | Expr::Field(_)
+                | Expr::Index(_)
+                | Expr::Unary(ExprUnary {
+                    op: UnOp::Deref(_),
+                    ..
+                })
+        );
+        if !place {
+            return report(AssignErrorKind::NotAPlace);
+        }
+
+        let Some(op) = assign.op.binary() else {
+            return;
+        };
+        let fits = |class: ValueClass| match op {
+            BiOp::And | BiOp::Or | BiOp::Xor | BiOp::Shl | BiOp::Shr => {
+                matches!(class, ValueClass::Integer(_))
+            }
+            _ => class.is_numeric(),
+        };
+        let assignee = self.value_class(&assign.assignee, scope);
+        let value = self.value_class(&assign.value, scope);
+        if let Some(found) = [assignee, value].into_iter().flatten().find(|c| !fits(*c)) {
+            return report(AssignErrorKind::Operand {
+                op: assign.op,
+                found,
+            });
+        }
+
+        let shift = matches!(op, BiOp::Shl | BiOp::Shr);
+        if let (Some(assignee), Some(value), false) = (assignee, value, shift) {
+            if !assignee.same_number(value) {
+                report(AssignErrorKind::Mismatch {
+                    op: assign.op,
+                    place: assignee,
+                    value,
+                });
+            }
+        }
+    }
+
+    /// What is known about the type of the value, `None` if it needs inference
+    fn value_class(&self, expr: &Expr, scope: &Classes) -> Option<ValueClass> {
+        match expr {
+            Expr::Number(num) => Some(ValueClass::Integer(num.ty())),
+            Expr::Byte(_, _) => Some(ValueClass::Integer(Some(IntTy::U8))),
+            Expr::Float(num) => Some(ValueClass::Float(
+                num.suffix
+                    .as_ref()
+                    .and_then(|suffix| FloatTy::from_suffix(suffix.fragment())),
+            )),
+            Expr::Char(_, _) => Some(ValueClass::Char),
+            Expr::String(_, _) | Expr::ByteString(_, _) => Some(ValueClass::Other("a string")),
+            Expr::Tuple(_) => Some(ValueClass::Other("a tuple")),
+            Expr::Array(_) => Some(ValueClass::Other("an array")),
+            Expr::Ctor(_) => Some(ValueClass::Other("a struct")),
+            Expr::Range(_) => Some(ValueClass::Other("a range")),
+            Expr::Unary(unary) => match unary.op {
+                UnOp::Ref(_) | UnOp::RefMut(_, _) => Some(ValueClass::Pointer),
+                UnOp::Not(_) | UnOp::BitNot(_) => self.value_class(&unary.expr, scope),
+                UnOp::Neg(_) => self
+                    .value_class(&unary.expr, scope)
+                    .filter(|class| class.is_numeric()),
+                UnOp::Deref(_) => None,
+            },
+            Expr::Binary(bin) => match bin.op {
+                BiOp::Add
+                | BiOp::Sub
+                | BiOp::Mul
+                | BiOp::Div
+                | BiOp::Mod
+                | BiOp::And
+                | BiOp::Or
+                | BiOp::Xor
+                | BiOp::Shl
+                | BiOp::Shr => self
+                    .value_class(&bin.left, scope)
+                    .filter(|class| class.is_numeric()),
+                _ => None,
+            },
+            Expr::Cast(cast) => self.target(&cast.ty),
+            Expr::Ident(name) => scope.lookup(name).and_then(|(_, class)| *class),
+            _ => None,
+        }
+    }
+
+    /// Named types are looked up, they may be aliases of numbers or pointers
+    fn target(&self, ty: &ItemTy) -> Option<ValueClass> {
+        let class = |desc| Some(ValueClass::Other(desc));
+        match ty {
+            ItemTy::Referred(path) | ItemTy::Applied(TyApplied { path, .. }) => {
+                if generic_param(path, self.generics).is_some() {
+                    return None;
+                }
+                match lookup_type(self.tsys, path).resolve()? {
+                    Resolved::Primitive(Primitive::Int(ty)) => Some(ValueClass::Integer(Some(ty))),
+                    Resolved::Primitive(Primitive::Float(ty)) => Some(ValueClass::Float(Some(ty))),
+                    Resolved::Kind(kind) => match kind {
+                        TypeKind::Param(_) | TypeKind::Referred(..) | TypeKind::Applied(_) => None,
+                        TypeKind::Ptr(_) => Some(ValueClass::Pointer),
+                        TypeKind::Struct(_) => class("a struct"),
+                        TypeKind::Enum(_) => class("an enum"),
+                        TypeKind::Tuple(_) => class("a tuple"),
+                        TypeKind::Func(_) => class("a function"),
+                        TypeKind::Array(_) => class("an array"),
+                        TypeKind::Never(_) => class("`!`"),
+                    },
+                }
+            }
+            ItemTy::Pointer(_) => Some(ValueClass::Pointer),
+            ItemTy::Struct(_) => class("a struct"),
+            ItemTy::Enum(_) => class("an enum"),
+            ItemTy::Tuple(_) => class("a tuple"),
+            ItemTy::Func(_) => class("a function"),
+            ItemTy::Array(_) => class("an array"),
+            ItemTy::Never(_) => class("`!`"),
+        }
+    }
+}
+
+/// Bindings of patterns are not inferred, they only shadow
+fn declare(pattern: &Pattern, class: Option<ValueClass>, scope: &mut Classes) {
+    pattern.for_each_binding(&mut |bind| scope.declare(bind.name.clone(), class));
+}
@@ -1,53 +1,78 @@
+use crate::attrs::check_attributes;
 use crate::data::err::{
     ArrayNumberFix, DefinitionKind, MultipleDefinitionsError, UnresolvedTypeError,
 };
 use crate::data::types::{
-    TypeArray, TypeEnum, TypeFunc, TypeInfo, TypeKind, TypeNever, TypePtr, TypeStruct, TypeTuple,
+    MethodRef, Primitive, TraitDef, TypeApplied, TypeArray, TypeEnum, TypeFunc, TypeInfo, TypeKind,
+    TypeNever, TypePtr, TypeRef, TypeStruct, TypeTuple,
 };
-use crate::{ErrorCollector, Module, PathBuf};
-use std::borrow::Cow;
+use crate::impls::check_impl;
+use crate::imports::resolve_imports;
+use crate::lower::{check_defers, lower_module};
+use crate::mutability::check_mutability;
+use crate::operands::check_operands;
+use crate::patterns::check_patterns;
+use crate::values::order_values;
+use crate::{ErrorCollector, Module};
+use std::collections::hash_map::Entry;
 use w_parse::expr::path::ExprPath;
-use w_parse::item::named::NamedKind;
+use w_parse::item::func::ItemFunc;
+use w_parse::item::generics::GenericParam;
+use w_parse::item::method::ItemMethod;
+use w_parse::item::named::{ItemNamed, NamedKind};
+use w_parse::item::r#trait::{ItemTrait, TraitFunc};
 use w_parse::item::Item;
-use w_parse::types::array::TyArray;
-use w_parse::types::func::TyFunc;
+use w_parse::types::applied::TyApplied;
+use w_parse::types::array::{ArraySize, TyArray};
+use w_parse::types::func::{TyFunc, TyNamedFunc};
 use w_parse::types::never::TyNever;
 use w_parse::types::ptr::TyPtr;
-use w_parse::types::r#enum::TyEnum;
-use w_parse::types::r#struct::TyStruct;
+use w_parse::types::r#enum::{TyEnum, TyEnumVariant};
+use w_parse::types::r#struct::{TyStruct, TyStructField};
 use w_parse::types::tuple::TyTuple;
 use w_parse::types::ItemTy;
-use w_parse::util::NameTyPair;
-use w_parse::ParsedModule;
-use w_tokenize::Number;
-
-pub fn run_pass1<'gc>(module: &ParsedModule, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
-    // Imports
-    // for item in module.items.iter() {
-    //     let def = match item {
-    //         Item::Import(def) => def,
-    //         Item::Definer(_) => continue,
-    //     };
-    //
-    //     let (root, base) = conv_path(tsys, &def.from);
-    //     let root = root.access_or_create_module(&base);
-    //
-    //     resolve_imports(&def.imports, root, tsys, errs);
-    // }
+use w_parse::{Ident, ParsedModule, Symbol};
+use w_tokenize::{IntTy, IntValue, Number, Span, TokenError};
+
+/// Builds the type system of `module` and checks its bodies,
+/// once there are no errors the bodies are lowered with [`lower_module`].
+///
+/// Every module of the tree has to be declared with
+/// [`declare_module`](crate::imports::declare_module) first,
+/// then the modules can be run in any order.
+pub fn run_pass1<'gc>(module: &mut ParsedModule, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    check_attributes(module, errs);
+    check_mutability(module, errs);
+    check_defers(module, errs);
+
+    // Imports, before any path is looked up
+    resolve_imports(module, tsys, errs);
 
     // Type definitions
     for item in module.items.iter() {
         let def = match item {
             Item::Definer(def) => def,
-            Item::Import(_) => continue,
+            Item::Method(_) | Item::Impl(_) | Item::Import(_) | Item::Modules(_) => continue,
         };
 
         let ty = match &def.kind {
             NamedKind::Type(ty) => ty,
+            NamedKind::Trait(tr) => {
+                register_trait(def, tr, tsys, errs);
+                continue;
+            }
+            // the type of the value has to exist like any other referenced type
+            NamedKind::Value(value) => {
+                if let Some(ty) = &value.ty {
+                    let generics = def.generics.as_ref().map_or(&[][..], |g| &g.params);
+                    build_type(ty, tsys, generics, errs);
+                }
+                continue;
+            }
             NamedKind::Func(_) => continue,
         };
 
-        let tref = tsys.access_or_create_type(&PathBuf::from([def.name.clone()]));
+        let tref = tsys.access_or_create_type(std::slice::from_ref(&def.name));
 
         if tref.definition.borrow().is_some() {
             errs.add_error(MultipleDefinitionsError {
@@ -58,52 +83,209 @@ pub fn run_pass1<'gc>(module: &ParsedModule, tsys: &'gc Module<'gc>, errs: &Erro
             continue;
         }
 
-        let kind = build_type(&ty.ty, tsys, errs);
+        let generics = def.generics.as_ref().map_or(&[][..], |g| &g.params);
+        let kind = build_type(&ty.ty, tsys, generics, errs);
 
         *tref.definition.borrow_mut() = Some(TypeInfo::Owned { kind });
     }
 
+    // Methods
+    for item in module.items.iter() {
+        if let Item::Method(method) = item {
+            register_method(method, tsys, errs);
+        }
+    }
+
+    // Implementations, after all traits are known
+    for item in module.items.iter() {
+        if let Item::Impl(imp) = item {
+            check_impl(imp, tsys, errs);
+        }
+    }
+
+    check_patterns(module, tsys, errs);
+    check_operands(module, tsys, errs);
+    order_values(module, tsys, errs);
+
     undefined_type_check(tsys, errs);
     if errs.has_errors() {
         return;
     }
 
     rrc::recursive_reference_check(tsys, errs);
+    if errs.has_errors() {
+        return;
+    }
+
+    lower_module(module);
 }
 
-// fn resolve_imports<'a, 'gc>(
-//     imps: &[Imports<'a>],
-//     root: &'gc Module<'a, 'gc>,
-//     tsys: &'gc Module<'a, 'gc>,
-//     errs: &ErrorCollector<'a>,
-// ) {
-//     for imp in imps {
-//         match imp {
-//             Imports::Single(single) => {
-//                 // import paths can not be absolute
-//                 let (_, path) = conv_path(tsys, single);
-//
-//                 let name = path.last().expect("imported paths may not be empty");
-//                 let path = path.slice(0..path.len() - 1);
-//
-//                 let md = root.access_or_create_module(path);
-//                 tsys.imports.borrow_mut().insert(name.clone(), md);
-//             }
-//             Imports::Multiple(offset, imps) => {
-//                 let (_, path) = conv_path(tsys, offset);
-//                 let rel_root = root.access_or_create_module(&path);
-//                 resolve_imports(imps, rel_root, tsys, errs);
-//             }
-//         }
-//     }
-// }
-
-fn build_type<'gc>(ty: &ItemTy, tsys: &'gc Module<'gc>, errs: &ErrorCollector) -> TypeKind<'gc> {
-    match ty {
-        ItemTy::Referred(reference) => {
-            let (root, path) = conv_path(tsys, reference);
-            TypeKind::Referred(root.access_or_create_type(&path), path)
+/// The bodies of all functions, methods and implementations in the module
+pub(crate) fn module_funcs(module: &ParsedModule) -> impl Iterator<Item = &ItemFunc> {
+    module.items.iter().flat_map(|item| {
+        let funcs: Box<dyn Iterator<Item = &ItemFunc>> = match item {
+            Item::Definer(ItemNamed {
+                kind: NamedKind::Func(func),
+                ..
+            }) => Box::new(std::iter::once(func)),
+            Item::Method(method) => Box::new(std::iter::once(&method.func)),
+            Item::Impl(imp) => Box::new(imp.methods.iter().map(|method| &method.func)),
+            Item::Definer(_) | Item::Import(_) | Item::Modules(_) => Box::new(std::iter::empty()),
+        };
+        funcs
+    })
+}
+
+fn register_method<'gc>(method: &ItemMethod, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    let generics = method.generics.as_ref().map_or(&[][..], |g| &g.params);
+    let func = build_named_func(&method.func.func, tsys, generics, errs);
+
+    // the receiver has to exist like any other referenced type
+    let receiver = lookup_type(tsys, &method.ty);
+    let ty_name = method.ty.path.last().unwrap().sym();
+
+    let mut methods = receiver.loc.home.methods.borrow_mut();
+    match methods.entry(ty_name).or_default().entry(method.name.sym()) {
+        Entry::Occupied(first) => errs.add_error(MultipleDefinitionsError {
+            loc: method.name.clone(),
+            first: first.get().name.clone(),
+            kind: DefinitionKind::Method,
+        }),
+        Entry::Vacant(slot) => {
+            slot.insert(MethodRef {
+                name: method.name.clone(),
+                func,
+            });
         }
+    }
+}
+
+fn register_trait<'gc>(
+    def: &ItemNamed,
+    tr: &ItemTrait,
+    tsys: &'gc Module<'gc>,
+    errs: &ErrorCollector,
+) {
+    let mut generics = def
+        .generics
+        .as_ref()
+        .map_or_else(Vec::new, |g| g.params.clone());
+    let self_sym = Symbol::intern("Self");
+    let params = generics
+        .iter()
+        .map(|param| param.name.clone())
+        .filter(|name| name.sym() != self_sym)
+        .collect();
+    // `Self` is the implementing type and may be used without declaring it
+    if generics.iter().all(|param| param.name.sym() != self_sym) {
+        generics.push(GenericParam {
+            name: Ident(tr.span_trait.clone(), self_sym),
+            bounds: vec![],
+        });
+    }
+
+    let methods = tr
+        .methods
+        .iter()
+        .map(|method| {
+            (
+                method.name.clone(),
+                build_trait_func(&method.func, tsys, &generics, errs),
+            )
+        })
+        .collect();
+
+    let mut traits = tsys.traits.borrow_mut();
+    match traits.entry(def.name.sym()) {
+        Entry::Occupied(first) => errs.add_error(MultipleDefinitionsError {
+            loc: def.name.clone(),
+            first: first.get().name.clone(),
+            kind: DefinitionKind::Trait,
+        }),
+        Entry::Vacant(slot) => {
+            slot.insert(TraitDef {
+                name: def.name.clone(),
+                params,
+                methods,
+            });
+        }
+    }
+}
+
+pub(crate) fn build_named_func<'gc>(
+    func: &TyNamedFunc,
+    tsys: &'gc Module<'gc>,
+    generics: &[GenericParam],
+    errs: &ErrorCollector,
+) -> TypeFunc<'gc> {
+    let args = func.args.iter().map(|arg| &arg.ty);
+    build_signature(
+        &func.span_func,
+        args,
+        func.ret_ty.as_deref(),
+        tsys,
+        generics,
+        errs,
+    )
+}
+
+fn build_trait_func<'gc>(
+    func: &TraitFunc,
+    tsys: &'gc Module<'gc>,
+    generics: &[GenericParam],
+    errs: &ErrorCollector,
+) -> TypeFunc<'gc> {
+    let args = func.args.iter();
+    build_signature(
+        &func.span_func,
+        args,
+        func.ret_ty.as_deref(),
+        tsys,
+        generics,
+        errs,
+    )
+}
+
+fn build_signature<'a, 'gc>(
+    span_func: &Span,
+    args: impl Iterator<Item = &'a ItemTy>,
+    ret_ty: Option<&ItemTy>,
+    tsys: &'gc Module<'gc>,
+    generics: &[GenericParam],
+    errs: &ErrorCollector,
+) -> TypeFunc<'gc> {
+    TypeFunc {
+        def: span_func.clone(),
+        args: args
+            .map(|ty| build_type(ty, tsys, generics, errs))
+            .collect(),
+        ret: Box::new(match ret_ty {
+            Some(ty) => build_type(ty, tsys, generics, errs),
+            None => TypeKind::Tuple(TypeTuple {
+                def: span_func.clone(),
+                fields: vec![],
+            }),
+        }),
+    }
+}
+
+/// `generics` are the parameters of the item the type is defined in
+pub(crate) fn build_type<'gc>(
+    ty: &ItemTy,
+    tsys: &'gc Module<'gc>,
+    generics: &[GenericParam],
+    errs: &ErrorCollector,
+) -> TypeKind<'gc> {
+    match ty {
+        ItemTy::Referred(reference) => match generic_param(reference, generics) {
+            Some(param) => TypeKind::Param(param.name.clone()),
+            None => TypeKind::Referred(lookup_type(tsys, reference), reference.clone()),
+        },
+        ItemTy::Applied(TyApplied { path, args }) => TypeKind::Applied(TypeApplied {
+            ty: lookup_type(tsys, path),
+            path: path.clone(),
+            args: conv_tuple(args, tsys, generics, errs),
+        }),
         ItemTy::Struct(TyStruct {
             span_struct,
             fields,
@@ -111,7 +293,9 @@ fn build_type<'gc>(ty: &ItemTy, tsys: &'gc Module<'gc>, errs: &ErrorCollector) -
             def: span_struct.clone(),
             fields: fields
                 .iter()
-                .map(|NameTyPair { name, ty }| (name.clone(), build_type(ty, tsys, errs)))
+                .map(|TyStructField { name, ty, .. }| {
+                    (name.clone(), build_type(ty, tsys, generics, errs))
+                })
                 .collect(),
         }),
         ItemTy::Enum(TyEnum {
@@ -121,34 +305,39 @@ fn build_type<'gc>(ty: &ItemTy, tsys: &'gc Module<'gc>, errs: &ErrorCollector) -
             def: span_enum.clone(),
             variants: variants
                 .iter()
-                .map(|(name, ty)| {
+                .map(|TyEnumVariant { name, fields, .. }| {
                     (
                         name.clone(),
-                        ty.as_ref().map(|tp| conv_tuple(tp, tsys, errs)),
+                        fields
+                            .as_ref()
+                            .map(|tp| conv_tuple(tp, tsys, generics, errs)),
                     )
                 })
                 .collect(),
         }),
-        ItemTy::Tuple(tp) => TypeKind::Tuple(conv_tuple(tp, tsys, errs)),
+        ItemTy::Tuple(tp) => TypeKind::Tuple(conv_tuple(tp, tsys, generics, errs)),
         ItemTy::Func(TyFunc {
             span_func,
             args,
             ret_ty,
-        }) => TypeKind::Func(TypeFunc {
-            def: span_func.clone(),
-            args: args.iter().map(|ty| build_type(ty, tsys, errs)).collect(),
-            ret: Box::new(build_type(ret_ty, tsys, errs)),
-        }),
+        }) => TypeKind::Func(build_signature(
+            span_func,
+            args.iter(),
+            ret_ty.as_deref(),
+            tsys,
+            generics,
+            errs,
+        )),
         ItemTy::Array(TyArray { span, ty, size }) => TypeKind::Array(TypeArray {
             def: span.clone(),
-            ty: Box::new(build_type(ty, tsys, errs)),
-            len: if let Some(num) = size {
-                match array_num_to_sized(&**num) {
+            ty: Box::new(build_type(ty, tsys, generics, errs)),
+            len: if let Some(ArraySize::Sized(num)) = size {
+                match array_num_to_sized(num) {
                     Ok(n) => Some(n),
                     Err(err) => {
                         errs.add_error(ArrayNumberFix {
-                            loc: num.number.clone(),
-                            msg: err,
+                            loc: err.span,
+                            msg: err.reason.unwrap_or_else(|| "Invalid array size".into()),
                         });
                         None
                     }
@@ -156,35 +345,48 @@ fn build_type<'gc>(ty: &ItemTy, tsys: &'gc Module<'gc>, errs: &ErrorCollector) -
             } else {
                 None
             },
+            dynamic: matches!(size, Some(ArraySize::Unsized(_))),
         }),
-        ItemTy::Pointer(TyPtr { span_ptr, ty }) => TypeKind::Ptr(TypePtr {
+        ItemTy::Pointer(TyPtr {
+            span_ptr,
+            mutability,
+            ty,
+        }) => TypeKind::Ptr(TypePtr {
             def: span_ptr.clone(),
-            ty: Box::new(build_type(ty, tsys, errs)),
+            mutable: mutability.is_some(),
+            ty: Box::new(build_type(ty, tsys, generics, errs)),
         }),
         ItemTy::Never(TyNever(span)) => TypeKind::Never(TypeNever(span.clone())),
     }
 }
 
+/// Names of built in types which the module did not define are those types from now on
 fn undefined_type_check<'gc>(tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
-    tsys.types
-        .borrow()
-        .iter()
-        .filter(|(_, v)| v.definition.borrow().is_none())
-        .for_each(|(_, v)| errs.add_error(UnresolvedTypeError(v.loc.name.clone())))
+    for tref in tsys.types.borrow().values() {
+        let mut definition = tref.definition.borrow_mut();
+        if definition.is_some() {
+            continue;
+        }
+
+        match Primitive::from_name(tref.loc.name.1.as_str()) {
+            Some(prim) => *definition = Some(TypeInfo::Primitive(prim)),
+            None => errs.add_error(UnresolvedTypeError(tref.loc.name.clone())),
+        }
+    }
 }
 
 mod rrc {
     use crate::data::err::RecursiveTypeError;
     use crate::data::types::{
-        TypeArray, TypeEnum, TypeInfo, TypeKind, TypeRef, TypeStruct, TypeTuple,
+        TypeApplied, TypeArray, TypeEnum, TypeInfo, TypeKind, TypeRef, TypeStruct, TypeTuple,
     };
     use crate::{ErrorCollector, Module};
     use std::ptr;
     use w_parse::Ident;
 
     pub fn recursive_reference_check<'gc>(tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
-        tsys.types.borrow().iter().for_each(|(id, ty)| {
-            rrc_investigate(ty, id.clone(), errs, &mut vec![]);
+        tsys.types.borrow().values().for_each(|ty| {
+            rrc_investigate(ty, ty.loc.name.clone(), errs, &mut vec![]);
         })
     }
 
@@ -195,9 +397,12 @@ mod rrc {
         stack: &mut Vec<&'gc TypeRef<'gc>>,
     ) {
         stack.push(ty);
-        match ty.definition.borrow().as_ref().unwrap() {
-            TypeInfo::Owned { kind } => rrc_investigate_tk(kind, errs, stack),
-            TypeInfo::Proxy(ty) => {
+        match ty.definition.borrow().as_ref() {
+            // types of other modules are checked once those are analyzed
+            None => (),
+            Some(TypeInfo::Owned { kind }) => rrc_investigate_tk(kind, errs, stack),
+            Some(TypeInfo::Primitive(_)) => (),
+            Some(TypeInfo::Proxy(ty)) => {
                 if let Some(found) = stack.iter().find(|otr| ptr::eq(*otr, ty)) {
                     errs.add_error(RecursiveTypeError {
                         og: found.loc.name.clone(),
@@ -216,9 +421,17 @@ mod rrc {
     ) {
         match ty {
             TypeKind::Referred(tr, path) => {
-                rrc_investigate(*tr, path.last().unwrap().clone(), errs, stack)
+                rrc_investigate(tr, path.path.last().unwrap().clone(), errs, stack)
+            }
+            TypeKind::Applied(TypeApplied { ty, path, args }) => {
+                rrc_investigate(ty, path.path.last().unwrap().clone(), errs, stack);
+                args.fields
+                    .iter()
+                    .for_each(|ty| rrc_investigate_tk(ty, errs, stack));
             }
-            TypeKind::Array(TypeArray { ty, .. }) => rrc_investigate_tk(&*ty, errs, stack),
+            // parameters are only known once the type is applied
+            TypeKind::Param(_) => (),
+            TypeKind::Array(TypeArray { ty, .. }) => rrc_investigate_tk(ty, errs, stack),
             TypeKind::Enum(TypeEnum { variants, .. }) => variants
                 .iter()
                 .filter_map(|(_, v)| v.as_ref())
@@ -246,51 +459,58 @@ mod rrc {
 fn conv_tuple<'gc>(
     TyTuple { span, types }: &TyTuple,
     tsys: &'gc Module<'gc>,
+    generics: &[GenericParam],
     errs: &ErrorCollector,
 ) -> TypeTuple<'gc> {
     TypeTuple {
         def: span.clone(),
-        fields: types.iter().map(|ty| build_type(ty, tsys, errs)).collect(),
+        fields: types
+            .iter()
+            .map(|ty| build_type(ty, tsys, generics, errs))
+            .collect(),
     }
 }
 
-fn conv_path<'gc>(tsys: &'gc Module<'gc>, path: &ExprPath) -> (&'gc Module<'gc>, PathBuf) {
-    let md = if path.root.is_some() {
-        tsys.root()
-    } else {
-        tsys
-    };
-    (md, PathBuf::from(path.path.as_slice()))
+pub(crate) fn lookup_type<'gc>(tsys: &'gc Module<'gc>, path: &ExprPath) -> &'gc TypeRef<'gc> {
+    path_start(tsys, path).access_or_create_type(&path.path)
 }
 
-fn array_num_to_sized(num: &Number) -> Result<u64, Cow<'static, str>> {
-    if let Some(sign) = &num.sign {
-        if **sign != "+" {
-            return Err("Unsigned integers must be positive".into());
+/// The module the path is relative to, imported names are looked up where they are from
+pub(crate) fn path_start<'gc>(tsys: &'gc Module<'gc>, path: &ExprPath) -> &'gc Module<'gc> {
+    if path.root.is_none() {
+        if let Some(import) = tsys.imports.borrow().get(&path.path[0].sym()) {
+            return import.from;
         }
     }
+
+    // `super` above the root module stays at the root
+    tsys.path_root(path.root.as_ref())
+        .unwrap_or_else(|| tsys.root())
+}
+
+/// Generic parameters shadow types of the same name
+pub(crate) fn generic_param<'a>(
+    path: &ExprPath,
+    generics: &'a [GenericParam],
+) -> Option<&'a GenericParam> {
+    match (&path.root, path.path.as_slice()) {
+        (None, [name]) => generics.iter().find(|param| param.name == *name),
+        _ => None,
+    }
+}
+
+fn array_num_to_sized(num: &Number) -> Result<u64, TokenError> {
     if let Some(suffix) = &num.suffix {
-        if **suffix != "usize" {
-            return Err("Only usize numbers are allowed as array size".into());
+        if num.ty() != Some(IntTy::Usize) {
+            return Err(TokenError::new(
+                suffix.clone(),
+                "Only usize numbers are allowed as array size",
+            ));
         }
     }
 
-    let base = num
-        .base
-        .as_ref()
-        .map(|span| match &***span {
-            "0x" => 16,
-            "0o" => 8,
-            "0b" => 2,
-            _ => unreachable!("allowed bases exceeded"),
-        })
-        .unwrap_or(10);
-
-    let num = if num.number.find('_').is_some() {
-        Cow::Owned(num.number.replace('_', ""))
-    } else {
-        Cow::Borrowed(&**num.number)
-    };
-
-    u64::from_str_radix(&num, base).map_err(|err| format!("Number out of scope: {err}").into())
+    match num.value(IntTy::Usize)? {
+        IntValue::Usize(n) => Ok(n),
+        _ => unreachable!("numbers are evaluated as usize"),
+    }
 }
@@ -0,0 +1,414 @@
+use crate::data::err::{PatternError, PatternErrorKind};
+use crate::data::types::{Resolved, TypeKind, TypeRef};
+use crate::pass1_tsys::{lookup_type, module_funcs};
+use crate::{ErrorCollector, Module};
+use std::fmt::{Display, Formatter};
+use std::ptr;
+use w_parse::expr::branch::ExprBranch;
+use w_parse::expr::loops::{ExprFor, ExprWhile};
+use w_parse::expr::path::ExprPath;
+use w_parse::expr::pattern::{ExprMatch, PatVariant, Pattern};
+use w_parse::expr::Expr;
+use w_parse::{Ident, ParsedModule, Symbol};
+use w_tokenize::Span;
+
+/// Checks variant and struct patterns against the types they name and `match` for exhaustiveness
+pub fn check_patterns<'gc>(module: &ParsedModule, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    for func in module_funcs(module) {
+        func.body
+            .exprs()
+            .for_each(|expr| check_expr(expr, false, tsys, errs));
+    }
+}
+
+/// `condition` is set for the condition of `if` and `while`, which may use refutable patterns
+fn check_expr<'gc>(expr: &Expr, condition: bool, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    match expr {
+        Expr::Define(define) => {
+            check_pattern(&define.pattern, tsys, errs);
+            if !condition {
+                check_irrefutable(&define.pattern, &define.span_op, errs);
+            }
+        }
+        Expr::For(ExprFor {
+            binding, span_in, ..
+        }) => {
+            check_pattern(binding, tsys, errs);
+            check_irrefutable(binding, span_in, errs);
+        }
+        Expr::Match(mt) => {
+            mt.arms
+                .iter()
+                .for_each(|arm| check_pattern(&arm.pattern, tsys, errs));
+            check_exhaustive(mt, tsys, errs);
+        }
+        _ => (),
+    }
+
+    let cond = match expr {
+        Expr::Branch(ExprBranch { cond, .. }) | Expr::While(ExprWhile { cond, .. }) => Some(cond),
+        _ => None,
+    };
+    expr.for_each_child(&mut |child| {
+        let condition = cond.is_some_and(|cond| ptr::eq(&**cond, child));
+        check_expr(child, condition, tsys, errs)
+    });
+}
+
+/// Outside of conditions there is no code to run instead if the pattern does not match
+fn check_irrefutable(pattern: &Pattern, loc: &Span, errs: &ErrorCollector) {
+    if !pattern.is_irrefutable() {
+        errs.add_error(PatternError {
+            loc: loc.clone(),
+            ty: None,
+            kind: PatternErrorKind::Refutable,
+        });
+    }
+}
+
+fn check_pattern<'gc>(pattern: &Pattern, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    match pattern {
+        Pattern::Wildcard(_) | Pattern::Binding(_) | Pattern::Literal(_) => (),
+        Pattern::Tuple(tuple) => tuple
+            .fields
+            .iter()
+            .for_each(|pat| check_pattern(pat, tsys, errs)),
+        Pattern::Variant(variant) => {
+            check_variant(variant, tsys, errs);
+            variant
+                .fields
+                .iter()
+                .flat_map(|tuple| &tuple.fields)
+                .for_each(|pat| check_pattern(pat, tsys, errs));
+        }
+        Pattern::Struct(st) => {
+            let tref = lookup_type(tsys, &st.path);
+            let name = st.path.path.last().unwrap();
+            let report = |loc: &Span, kind| {
+                errs.add_error(PatternError {
+                    loc: loc.clone(),
+                    ty: Some(tref.loc.name.clone()),
+                    kind,
+                })
+            };
+
+            match tref.resolve() {
+                Some(Resolved::Kind(TypeKind::Struct(def))) => {
+                    for (field, _) in &st.fields {
+                        if !def.fields.iter().any(|(name, _)| name == field) {
+                            report(&field.0, PatternErrorKind::UnknownField);
+                        }
+                    }
+                }
+                Some(_) => report(&name.0, PatternErrorKind::NotAStruct),
+                // reported as an undefined type
+                None => (),
+            }
+
+            st.fields
+                .iter()
+                .for_each(|(_, pat)| check_pattern(pat, tsys, errs));
+        }
+    }
+}
+
+fn check_variant<'gc>(variant: &PatVariant, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    // a lone `some(x)` needs the type of the matched value to be resolved
+    let Some((name, tref)) = variant_enum(variant, tsys) else {
+        return;
+    };
+    let report = |kind| {
+        errs.add_error(PatternError {
+            loc: name.clone(),
+            ty: Some(tref.loc.name.clone()),
+            kind,
+        })
+    };
+
+    let def = match tref.resolve() {
+        Some(Resolved::Kind(TypeKind::Enum(def))) => def,
+        Some(_) => return report(PatternErrorKind::NotAnEnum),
+        None => return,
+    };
+    let Some((_, fields)) = def
+        .variants
+        .iter()
+        .find(|(var, _)| *var == *variant.path.path.last().unwrap())
+    else {
+        return report(PatternErrorKind::UnknownVariant);
+    };
+
+    let expected = fields.as_ref().map(|tuple| tuple.fields.len());
+    let found = variant.fields.as_ref().map(|tuple| tuple.fields.len());
+    if expected != found {
+        report(PatternErrorKind::FieldCount { expected, found });
+    }
+}
+
+/// The enum named in front of the variant, with the span of the variant
+fn variant_enum<'gc>(
+    variant: &PatVariant,
+    tsys: &'gc Module<'gc>,
+) -> Option<(Span, &'gc TypeRef<'gc>)> {
+    let (name, path) = variant.path.path.split_last()?;
+    if path.is_empty() {
+        return None;
+    }
+
+    let path = ExprPath {
+        root: variant.path.root.clone(),
+        path: path.to_vec(),
+    };
+    Some((name.0.clone(), lookup_type(tsys, &path)))
+}
+
+fn check_exhaustive<'gc>(mt: &ExprMatch, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    let rows = mt
+        .arms
+        .iter()
+        .map(|arm| vec![refutable(&arm.pattern)])
+        .collect::<Vec<_>>();
+
+    let missing = missing(&rows, 1, tsys);
+    if !missing.is_empty() {
+        errs.add_error(PatternError {
+            loc: mt.span_match.clone(),
+            ty: None,
+            kind: PatternErrorKind::NonExhaustive(
+                missing
+                    .into_iter()
+                    .map(|mut w| w.remove(0).to_string())
+                    .collect(),
+            ),
+        });
+    }
+}
+
+/// How many uncovered values are searched for, one is enough to fix the match
+const MAX_MISSING: usize = 3;
+
+/// A row of patterns matched against values side by side, `None` matches anything
+type Row<'a> = Vec<Option<&'a Pattern>>;
+
+fn refutable(pattern: &Pattern) -> Option<&Pattern> {
+    match pattern {
+        Pattern::Wildcard(_) | Pattern::Binding(_) => None,
+        pattern => Some(pattern),
+    }
+}
+
+/// Values of `width` columns no row matches.
+///
+/// The first column is split by the shapes its patterns have: tuples and structs have one,
+/// enums one for each variant. Literals can not be enumerated, so only a wildcard covers them.
+fn missing<'gc>(rows: &[Row], width: usize, tsys: &'gc Module<'gc>) -> Vec<Vec<Witness>> {
+    if width == 0 {
+        return match rows.is_empty() {
+            true => vec![vec![]],
+            false => vec![],
+        };
+    }
+
+    let first = rows.iter().find_map(|row| row[0]);
+    let ctors = match first {
+        Some(Pattern::Tuple(tuple)) => vec![Ctor::Tuple(tuple.fields.len())],
+        Some(Pattern::Struct(st)) => {
+            let mut fields = vec![];
+            for (field, _) in rows
+                .iter()
+                .filter_map(|row| match row[0] {
+                    Some(Pattern::Struct(st)) => Some(&st.fields),
+                    _ => None,
+                })
+                .flatten()
+            {
+                if !fields.contains(&field.sym()) {
+                    fields.push(field.sym());
+                }
+            }
+            vec![Ctor::Struct(path_text(&st.path.path), fields)]
+        }
+        Some(Pattern::Variant(variant)) => enum_ctors(variant, tsys),
+        _ => vec![],
+    };
+
+    if ctors.is_empty() {
+        let rest = rows
+            .iter()
+            .filter(|row| row[0].is_none())
+            .map(|row| row[1..].to_vec())
+            .collect::<Vec<_>>();
+        return missing(&rest, width - 1, tsys)
+            .into_iter()
+            .map(|rest| std::iter::once(Witness::Any).chain(rest).collect())
+            .collect();
+    }
+
+    let mut found = vec![];
+    for ctor in &ctors {
+        let arity = ctor.arity();
+        let specialized = rows
+            .iter()
+            .filter_map(|row| {
+                let mut fields = ctor.specialize(row[0], tsys)?;
+                fields.extend_from_slice(&row[1..]);
+                Some(fields)
+            })
+            .collect::<Vec<_>>();
+
+        for mut fields in missing(&specialized, arity + width - 1, tsys) {
+            let rest = fields.split_off(arity);
+            found.push(std::iter::once(ctor.witness(fields)).chain(rest).collect());
+            if found.len() == MAX_MISSING {
+                return found;
+            }
+        }
+    }
+    found
+}
+
+/// Every variant of the enum `variant` is from, none if the enum is not known
+fn enum_ctors<'gc>(variant: &PatVariant, tsys: &'gc Module<'gc>) -> Vec<Ctor<'gc>> {
+    let Some((_, tref)) = variant_enum(variant, tsys) else {
+        return vec![];
+    };
+    let Some(Resolved::Kind(TypeKind::Enum(def))) = tref.resolve() else {
+        return vec![];
+    };
+
+    let path = &variant.path.path;
+    let prefix = path_text(&path[..path.len() - 1]);
+    def.variants
+        .iter()
+        .map(|(name, fields)| Ctor::Variant {
+            tref,
+            name: name.sym(),
+            text: format!("{prefix}:{}", name.1),
+            fields: fields.as_ref().map(|tuple| tuple.fields.len()),
+        })
+        .collect()
+}
+
+fn path_text(path: &[Ident]) -> String {
+    path.iter()
+        .map(|ident| ident.1.as_str())
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// A shape the values of a column can have
+enum Ctor<'gc> {
+    Tuple(usize),
+    Struct(String, Vec<Symbol>),
+    Variant {
+        tref: &'gc TypeRef<'gc>,
+        name: Symbol,
+        text: String,
+        /// `None` for variants without a field tuple
+        fields: Option<usize>,
+    },
+}
+
+impl<'gc> Ctor<'gc> {
+    fn arity(&self) -> usize {
+        match self {
+            Ctor::Tuple(len) => *len,
+            Ctor::Struct(_, fields) => fields.len(),
+            Ctor::Variant { fields, .. } => fields.unwrap_or(0),
+        }
+    }
+
+    /// The patterns for the fields of the shape, `None` if `pattern` has a different one
+    fn specialize<'a>(
+        &self,
+        pattern: Option<&'a Pattern>,
+        tsys: &'gc Module<'gc>,
+    ) -> Option<Row<'a>> {
+        let Some(pattern) = pattern else {
+            return Some(vec![None; self.arity()]);
+        };
+
+        match (self, pattern) {
+            (Ctor::Tuple(len), Pattern::Tuple(tuple)) if tuple.fields.len() == *len => {
+                Some(tuple.fields.iter().map(refutable).collect())
+            }
+            (Ctor::Struct(_, fields), Pattern::Struct(st)) => Some(
+                fields
+                    .iter()
+                    .map(|field| {
+                        st.fields
+                            .iter()
+                            .find(|(name, _)| name.sym() == *field)
+                            .and_then(|(_, pat)| refutable(pat))
+                    })
+                    .collect(),
+            ),
+            (Ctor::Variant { tref, name, .. }, Pattern::Variant(variant)) => {
+                let (_, other) = variant_enum(variant, tsys)?;
+                if !ptr::eq(*tref, other) || variant.path.path.last()?.sym() != *name {
+                    return None;
+                }
+                match &variant.fields {
+                    Some(tuple) if tuple.fields.len() == self.arity() => {
+                        Some(tuple.fields.iter().map(refutable).collect())
+                    }
+                    // the wrong field count is reported already
+                    _ => Some(vec![None; self.arity()]),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn witness(&self, fields: Vec<Witness>) -> Witness {
+        match self {
+            Ctor::Tuple(_) => Witness::Tuple(fields),
+            Ctor::Struct(path, names) => {
+                Witness::Struct(path.clone(), names.iter().copied().zip(fields).collect())
+            }
+            Ctor::Variant {
+                text,
+                fields: arity,
+                ..
+            } => Witness::Variant(text.clone(), arity.map(|_| fields)),
+        }
+    }
+}
+
+/// A value no arm matches
+enum Witness {
+    /// Any value, or any not listed by a literal pattern
+    Any,
+    Tuple(Vec<Witness>),
+    Struct(String, Vec<(Symbol, Witness)>),
+    Variant(String, Option<Vec<Witness>>),
+}
+
+impl Display for Witness {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let list = |fields: &[Witness]| {
+            fields
+                .iter()
+                .map(Witness::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Witness::Any => f.write_str("_"),
+            Witness::Tuple(fields) => write!(f, "({})", list(fields)),
+            Witness::Struct(path, fields) => {
+                let fields = fields
+                    .iter()
+                    .filter(|(_, field)| !matches!(field, Witness::Any))
+                    .map(|(name, field)| format!("{name} = {field}"))
+                    .collect::<Vec<_>>();
+                match fields.is_empty() {
+                    true => write!(f, "{path} {{}}"),
+                    false => write!(f, "{path} {{ {} }}", fields.join(", ")),
+                }
+            }
+            Witness::Variant(path, None) => f.write_str(path),
+            Witness::Variant(path, Some(fields)) => write!(f, "{path}({})", list(fields)),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use w_parse::Ident;
+
+/// Bindings in declaration order, later ones shadow earlier ones.
+///
+/// `B` is what a check knows about each binding.
+pub(crate) struct Scope<B> {
+    bindings: Vec<(Ident, B)>,
+}
+
+impl<B> Default for Scope<B> {
+    fn default() -> Self {
+        Self { bindings: vec![] }
+    }
+}
+
+impl<B> FromIterator<(Ident, B)> for Scope<B> {
+    fn from_iter<T: IntoIterator<Item = (Ident, B)>>(iter: T) -> Self {
+        Self {
+            bindings: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<B> Scope<B> {
+    pub fn declare(&mut self, name: Ident, binding: B) {
+        self.bindings.push((name, binding));
+    }
+
+    /// The binding `name` refers to, with the name it was declared with
+    pub fn lookup(&self, name: &Ident) -> Option<(&Ident, &B)> {
+        self.bindings
+            .iter()
+            .rev()
+            .find(|(bind, _)| bind == name)
+            .map(|(bind, binding)| (bind, binding))
+    }
+
+    /// Runs `f` and forgets every binding declared in it afterwards
+    pub fn nested<R>(&mut self, f: impl FnOnce(&mut Self) -> R) -> R {
+        let len = self.bindings.len();
+        let result = f(self);
+        self.bindings.truncate(len);
+        result
+    }
+}
@@ -0,0 +1,622 @@
+use crate::data::err::fmt::ErrorFormatter;
+use crate::data::err::{ErrKind, ErrorCollector};
+use crate::data::Module;
+use crate::imports::declare_module;
+use crate::pass1_tsys::run_pass1;
+use std::rc::Rc;
+use typed_arena::Arena;
+use w_parse::expr::Expr;
+use w_parse::item::named::{ItemNamed, NamedKind};
+use w_parse::item::Item;
+use w_parse::{parse_module, Ident, ParsedModule, Symbol, TokenSpan};
+use w_rcstr::{Origin, RcStr, SourceMap};
+use w_tokenize::{tokenize, Span};
+
+fn pass1(src: &str) -> ErrorCollector {
+    pass1_file(RcStr::new(src.to_string(), Origin::Unknown))
+}
+
+fn parse(file: RcStr) -> ParsedModule {
+    let file = Span::new(file);
+    let (_, tokens) = tokenize(file.clone()).unwrap();
+    let name = Ident::new(file.clone());
+    let (_, module) = parse_module(TokenSpan::new(file, Rc::from(tokens)), name).unwrap();
+    module
+}
+
+fn pass1_file(file: RcStr) -> ErrorCollector {
+    let mut module = parse(file);
+
+    let types_arena = Arena::new();
+    let modules_arena = Arena::new();
+    let root = Module::new_root(&modules_arena, &types_arena);
+
+    let errs = ErrorCollector::default();
+    declare_module(&module, root, &errs);
+    run_pass1(&mut module, root, &errs);
+    errs
+}
+
+/// Declares the root module and each of the modules it declared, then runs them in order
+fn pass1_tree(root_src: &str, children: &[(&str, &str)]) -> ErrorCollector {
+    let types_arena = Arena::new();
+    let modules_arena = Arena::new();
+    let root = Module::new_root(&modules_arena, &types_arena);
+    let errs = ErrorCollector::default();
+
+    let source = |src: &str| parse(RcStr::new(src.to_string(), Origin::Unknown));
+    let mut modules = vec![(source(root_src), root)];
+    declare_module(&modules[0].0, root, &errs);
+    for (name, src) in children {
+        let child = root.modules.borrow()[&Symbol::intern(name)];
+        let module = source(src);
+        declare_module(&module, child, &errs);
+        modules.push((module, child));
+    }
+
+    for (module, tsys) in &mut modules {
+        run_pass1(module, tsys, &errs);
+    }
+    errs
+}
+
+/// Renders the diagnostics the way the command line prints them
+fn messages(errs: &ErrorCollector) -> String {
+    let mut f = ErrorFormatter::default();
+    errs.format(&mut f);
+    f.render(&SourceMap::default())
+}
+
+/// Asserts that exactly one error was reported and that its message contains `expected`
+#[track_caller]
+fn assert_error(errs: &ErrorCollector, expected: &str) {
+    let msgs = messages(errs);
+    assert_eq!(
+        errs.count(ErrKind::Error),
+        1,
+        "expected `{expected}`, got:\n{msgs}"
+    );
+    assert!(
+        msgs.contains(expected),
+        "expected `{expected}`, got:\n{msgs}"
+    );
+}
+
+#[test]
+fn unknown_attributes_warn() {
+    let errs = pass1(
+        "a :: @(no_copy, fast) struct { @packed x func() ! }\n\
+         b :: enum { @weird c }",
+    );
+    assert_eq!(errs.count(ErrKind::Warning), 3);
+    assert_eq!(errs.count(ErrKind::Error), 0);
+}
+
+#[test]
+fn formatter_aligns_markers() {
+    let mut sources = SourceMap::default();
+    let file = sources.add_virtual(
+        "test.w",
+        "a :: struct {\n\t@ätsch x func() !\n}".to_string(),
+    );
+    let errs = pass1_file(file);
+
+    let mut f = ErrorFormatter::default();
+    errs.format(&mut f);
+    assert_eq!(
+        f.render(&sources),
+        "warning: Unknown attribute, it will be ignored\n\
+         \x20--> test.w:2:3\n\
+         \x20 |\n\
+         2 |     @ätsch x func() !\n\
+         \x20 |      ^^^^^\n\
+         \x20 = note: Known attributes are: no_copy, extern, int_to_ptr\n\n"
+    );
+}
+
+#[test]
+fn type_references_resolve() {
+    let errs = pass1("a :: struct { x b }\nb :: enum { c(*a) }");
+    assert!(!errs.has_errors());
+
+    let errs = pass1("a :: struct { x b }\nb :: struct { y c }");
+    assert_error(&errs, "error: Unable to resolve type");
+}
+
+#[test]
+fn generic_parameters_resolve() {
+    let errs = pass1(
+        "option(T) :: enum { some(T), none }\n\
+         vec3 :: struct { x f32 }\n\
+         opt_vec3 :: option(vec3);",
+    );
+    assert!(!errs.has_errors());
+
+    // parameters are only visible in their own item
+    let errs = pass1("option(T) :: enum { some(T) }\nbad :: struct { x T }");
+    assert_error(&errs, "error: Unable to resolve type");
+}
+
+#[test]
+fn method_tables() {
+    let src = "vec(T) :: struct { len usize }\n\
+               vec(T):len :: func(self *vec(T)) usize { self }\n\
+               vec(T):push :: func(self *vec(T), val T) ! { self }";
+    let errs = pass1(src);
+    assert!(!errs.has_errors());
+
+    let errs = pass1(&format!(
+        "{src}\nvec(T):len :: func(self *vec(T)) usize {{ self }}"
+    ));
+    assert_error(&errs, "error: The type already has a method with this name");
+
+    // methods on types which do not exist
+    let errs = pass1("nope:len :: func() usize { x }");
+    assert_error(&errs, "error: Unable to resolve type");
+}
+
+#[test]
+fn impls_match_traits() {
+    let src = "rc(T) :: struct { ptr *T }\n\
+               disposable :: trait { dispose func(self *Self) }\n\
+               add(R) :: trait { add func(self Self, other R) Self }\n\
+               add_t(A, B, C) :: trait { add func(A, B) C }\n\
+               impl(T) :: disposable (rc(T)) { dispose func(self *rc(T)) { self } }\n\
+               impl :: add(i32) (i32) { add func(self i32, other i32) i32 { self } }\n\
+               impl :: add_t(i32, i32, i32) (i32) { add func(a i32, b i32) i32 { a } }";
+    let errs = pass1(src);
+    assert!(!errs.has_errors());
+
+    let cases = [
+        (
+            "impl :: disposable (i32) {}",
+            "error: Missing method `dispose`",
+        ),
+        (
+            "impl :: disposable (i32) { dispose func(self *i32) { self } drop func() { x } }",
+            "error: The method is not part of the trait",
+        ),
+        (
+            "impl :: disposable (i32) { dispose func(self i32) { self } }",
+            "error: The signature does not match the trait",
+        ),
+        (
+            "impl :: add (i32) { add func(self i32, other i32) i32 { self } }",
+            "error: The trait takes 1 parameters but 0 were given",
+        ),
+        // signatures of traits without argument names are checked just the same
+        (
+            "impl :: add_t(i32, i32, rc(i32)) (i32) { add func(a i32, b i32) i32 { a } }",
+            "error: The signature does not match the trait",
+        ),
+        ("impl :: nope (i32) {}", "error: Unable to resolve trait"),
+    ];
+    for (case, expected) in cases {
+        assert_error(&pass1(&format!("{src}\n{case}")), expected);
+    }
+}
+
+#[test]
+fn mutability() {
+    let errs = pass1(
+        "f :: func(p *i32, q *mut i32, mut n i32) {\n\
+             mut a := 1;\n\
+             a = 2;\n\
+             n = 3;\n\
+             *q = 4;\n\
+             q.x = 5;\n\
+             c := &mut a;\n\
+             *c = 6;\n\
+             if a { mut a := 7; a = 8 }\n\
+             x\n\
+         }",
+    );
+    assert!(!errs.has_errors());
+
+    let assign_twice =
+        |name| format!("error: Can not assign twice to the immutable binding `{name}`");
+    let write_through =
+        |name| format!("error: Can not write through `{name}`, it is not a mutable pointer");
+    let cases = [
+        ("b := 1; b = 2;", assign_twice("b")),
+        (
+            "b := 1; c := &mut b;",
+            "error: Can not borrow the immutable binding `b` as mutable".to_string(),
+        ),
+        ("*p = 1;", write_through("p")),
+        ("p.x[0] = 1;", write_through("p")),
+        ("r := &n; *r = 1;", write_through("r")),
+        ("mut b := 1; if a { b := 2; b = 3 }", assign_twice("b")),
+        ("for i in items() { i = 1 }", assign_twice("i")),
+    ];
+    for (case, expected) in cases {
+        let errs = pass1(&format!("f :: func(p *i32, n i32) {{\n{case}\nx\n}}"));
+        assert_error(&errs, &expected);
+    }
+}
+
+#[test]
+fn patterns_match_enums() {
+    let src = "option(T) :: enum { some(T), none }\n\
+               vec2 :: struct { x i32, y i32 }\n";
+    let check = |body: &str| pass1(&format!("{src}f :: func(o option(i32)) {{\n{body}\nx\n}}"));
+
+    let errs = check(
+        "if option:some(x) := o { x }\n\
+         match o { option:some(vec2 { x, y = _ }) -> x, option:none -> 0 };\n\
+         match o { option:some(_) -> 1, _ -> 0 };",
+    );
+    assert!(!errs.has_errors());
+
+    let cases = [
+        (
+            "if option:other := o { x };",
+            "error: The enum has no such variant",
+        ),
+        (
+            "if option:some := o { x };",
+            "error: The variant has 1 fields but the pattern has no field tuple",
+        ),
+        (
+            "if option:none(a) := o { x };",
+            "error: The variant has no field tuple but the pattern has 1 fields",
+        ),
+        (
+            "if vec2:some(a) := o { x };",
+            "error: Only enums have variants",
+        ),
+        ("vec2 { z } := o;", "error: The struct has no such field"),
+    ];
+    for (case, expected) in cases {
+        assert_error(&check(case), expected);
+    }
+
+    // refutable patterns only work as conditions
+    let errs = check(
+        "(a, b) := t;\n\
+         vec2 { x, y = _ } := v;\n\
+         while option:some(x) := o { x };\n\
+         for (i, _) in items { i }",
+    );
+    assert!(!errs.has_errors());
+    for case in [
+        "(1, x) := t;",
+        "option:some(x) := o;",
+        "for option:some(x) in items { x }",
+    ] {
+        assert_error(
+            &check(case),
+            "error: The pattern does not match every value",
+        );
+    }
+
+    let errs = check(
+        "match t { (option:some(_), _) -> 1, (option:none, 0) -> 2, (_, _) -> 3 };\n\
+         match v { vec2 { x = 1 } -> 1, vec2 { y } -> y };",
+    );
+    assert!(!errs.has_errors());
+    let cases = [
+        ("match o { option:some(x) -> x };", "`option:none`"),
+        (
+            "match o { option:some(1) -> 1, option:none -> 0 };",
+            "`option:some(_)`",
+        ),
+        ("match o { 1 -> 1, 2 -> 2 };", "`_`"),
+        (
+            "match t { (option:some(_), 1) -> 1, (option:none, _) -> 2 };",
+            "`(option:some(_), _)`",
+        ),
+        ("match v { vec2 { x = 1 } -> 1 };", "`vec2 {}`"),
+    ];
+    for (case, missing) in cases {
+        let errs = check(case);
+        assert_error(&errs, "error: The arms do not cover every value");
+        assert_error(&errs, &format!("note: Not covered: {missing}"));
+    }
+}
+
+#[test]
+fn casts_between_numbers_and_pointers() {
+    let src = "addr :: *u8;\n\
+               vec2 :: struct { x u32 }\n";
+    let check = |attrs: &str, body: &str| {
+        pass1(&format!(
+            "{src}f :: {attrs} func(n u32, p *u8) {{\n{body}\nx\n}}"
+        ))
+    };
+
+    let errs = check(
+        "",
+        "a := n as u8 as f64 as u32;\n\
+         b := p as *u32 as addr;\n\
+         c := &a as *mut u8;\n\
+         loop { n := p; break } d := n as u8;\n\
+         if c { n := p; n } else { n as u8 };\n\
+         e := n as u8;\n\
+         g := 'a' as u8;\n\
+         h := 'b' as u32;",
+    );
+    assert!(!errs.has_errors());
+    assert!(!check("@int_to_ptr", "q := n as *u8;").has_errors());
+
+    let int_to_ptr =
+        "error: Integers can only be cast to pointers in functions marked `@int_to_ptr`";
+    let cases = [
+        ("q := n as *u8;", int_to_ptr),
+        ("q := 1 + 2 as addr;", int_to_ptr),
+        (
+            "q := p as u32;",
+            "error: Can not cast a pointer to an integer",
+        ),
+        (
+            "q := \"text\" as u32;",
+            "error: Can not cast a string to an integer",
+        ),
+        (
+            "q := n as vec2;",
+            "error: Can not cast an integer to a struct",
+        ),
+        (
+            "q := (1, 2) as u8;",
+            "error: Can not cast a tuple to an integer",
+        ),
+        (
+            "q := 'a' as f32;",
+            "error: Can not cast a character to a float",
+        ),
+        (
+            "q := 'a' as *u8;",
+            "error: Can not cast a character to a pointer",
+        ),
+    ];
+    for (case, expected) in cases {
+        assert_error(&check("", case), expected);
+    }
+
+    // generic parameters are unknown, a type named like a number replaces it
+    assert!(!pass1("f(T) :: func(v T) { v as u8 }").has_errors());
+    let errs = pass1("u8 :: struct {}\nf :: func(n u32) { n as u8 }");
+    assert_error(&errs, "error: Can not cast an integer to a struct");
+}
+
+#[test]
+fn modules_resolve_imports() {
+    let root = "{iter, utf8_chk} :: mod;\nvec2 :: struct {}";
+    let errs = pass1_tree(
+        root,
+        &[
+            ("iter", "{ vec2 } :: super;\niter :: struct { at vec2 }"),
+            (
+                "utf8_chk",
+                "{ iter, vec2 } :: super;\nline :: struct { it iter:iter, at vec2 }",
+            ),
+        ],
+    );
+    assert!(!errs.has_errors());
+
+    // siblings are declared before any imports are resolved, whichever runs first
+    let errs = pass1_tree(
+        "{a, b} :: mod;",
+        &[
+            (
+                "a",
+                "{ point } :: super:b;\nline :: struct { from point, to point }",
+            ),
+            ("b", "point :: struct {}"),
+        ],
+    );
+    assert!(!errs.has_errors());
+
+    let cases = [
+        (
+            "{ a } :: super;",
+            None,
+            "error: The root module has no parent",
+        ),
+        (
+            "{ a, a } :: mod;",
+            None,
+            "error: The module is declared more than once",
+        ),
+        ("{ iter } :: self;", None, "error: Unable to resolve module"),
+        (
+            "{ vec2 } :: super:missing;",
+            Some("{ b } :: mod;"),
+            "error: Unable to resolve module",
+        ),
+        (
+            "{ nope } :: super;",
+            Some("{ b } :: mod;"),
+            "error: Unable to resolve import",
+        ),
+        (
+            "{ vec2 } :: super;\n{ vec2 } :: super;",
+            Some("{ b } :: mod;"),
+            "error: Item imported more than once",
+        ),
+        (
+            "{ vec2 } :: super:super;",
+            Some("{ b } :: mod;"),
+            "error: The root module has no parent",
+        ),
+    ];
+    for (src, declared_by, expected) in cases {
+        let errs = match declared_by {
+            Some(parent) => pass1_tree(&format!("{parent}\nvec2 :: struct {{}}"), &[("b", src)]),
+            None => pass1(src),
+        };
+        assert_error(&errs, expected);
+    }
+}
+
+#[test]
+fn values_initialize_in_order() {
+    let types_arena = Arena::new();
+    let modules_arena = Arena::new();
+    let root = Module::new_root(&modules_arena, &types_arena);
+    let errs = ErrorCollector::default();
+    let mut module = parse(RcStr::new(
+        "b :: usize := a + 1;\n\
+         c :: := f();\n\
+         a :: := 1;\n\
+         f :: func() usize { x := b; x }"
+            .to_string(),
+        Origin::Unknown,
+    ));
+    declare_module(&module, root, &errs);
+    run_pass1(&mut module, root, &errs);
+    assert!(!errs.has_errors());
+    let order = root
+        .values
+        .borrow()
+        .iter()
+        .map(|v| v.1.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(order, ["a", "b", "c"]);
+
+    let errs = pass1("a :: := f();\nf :: func() { g() }\ng :: func() { b }\nb :: := a;");
+    assert_error(&errs, "note: The cycle is a -> f -> g -> b -> a");
+
+    // methods are called through their receiver or their type
+    let errs = pass1(
+        "point :: struct { x i32 }\n\
+         point:get :: func(self *point) i32 { a }\n\
+         a :: i32 := origin().get();\n\
+         origin :: func() point { point { x = 0 } }",
+    );
+    assert_error(&errs, "note: The cycle is a -> get -> a");
+    let errs = pass1(
+        "size(T) :: trait { size func(self *T) usize }\n\
+         impl :: size(u8) (u8) { size func(self *u8) usize { b } }\n\
+         b :: usize := size:size(&1);",
+    );
+    assert_error(&errs, "note: The cycle is b -> size -> b");
+
+    // locals and recursive functions are no cycles
+    assert!(!pass1("f :: func() { f() }\na :: := f();\nb :: := match 1 { a -> a };").has_errors());
+    let cases = [
+        (
+            "a :: := a;",
+            "error: The initializer of `a` depends on itself",
+        ),
+        (
+            "a :: := 1;\na :: func() { x }",
+            "error: The value name is conflicting with another previous definition",
+        ),
+        (
+            "x :: := 1;\ng :: func() { x = 2 }",
+            "error: Can not assign twice to the immutable binding `x`",
+        ),
+        ("x :: nope := 1;", "error: Unable to resolve type"),
+        ("x :: mut *nope := 1;", "error: Unable to resolve type"),
+    ];
+    for (case, expected) in cases {
+        assert_error(&pass1(case), expected);
+    }
+    assert!(!pass1("x :: mut := 1;\ng :: func() { x = 2 }").has_errors());
+}
+
+#[test]
+fn compound_assignments_check_operands() {
+    let check = |body: &str| {
+        pass1(&format!(
+            "f :: func(mut n u32, mut p *mut u32) {{\n{body}\nx\n}}"
+        ))
+    };
+
+    let errs = check("n += 1;\nn -= 1u32;\nn <<= 2u8;\n*p |= n;\nmut f := 1.5;\nf /= 2.0f64;");
+    assert!(!errs.has_errors());
+
+    let not_a_place = "error: Can not assign to this expression";
+    let cases = [
+        ("f() = 1;", not_a_place),
+        ("1 += n;", not_a_place),
+        ("n += \"a\";", "error: `+=` can not be used with a string"),
+        ("n ^= 1.5;", "error: `^=` can not be used with a float"),
+        ("p += 1;", "error: `+=` can not be used with a pointer"),
+        (
+            "n += 1.5;",
+            "error: `+=` can not combine `u32` with a float",
+        ),
+        ("n *= 2u8;", "error: `*=` can not combine `u32` with `u8`"),
+        (
+            "n %= n as u64;",
+            "error: `%=` can not combine `u32` with `u64`",
+        ),
+        (
+            "mut f := 1.5;\nf /= 2;",
+            "error: `/=` can not combine a float with an integer",
+        ),
+        (
+            "mut f := 1.5f32;\nf -= 1.5f64;",
+            "error: `-=` can not combine `f32` with `f64`",
+        ),
+    ];
+    for (case, expected) in cases {
+        assert_error(&check(case), expected);
+    }
+}
+
+#[test]
+fn defers_and_for_loops_are_lowered() {
+    let types_arena = Arena::new();
+    let modules_arena = Arena::new();
+    let root = Module::new_root(&modules_arena, &types_arena);
+    let errs = ErrorCollector::default();
+    let mut module = parse(RcStr::new(
+        "f :: func(n usize) usize {\n\
+             defer g();\n\
+             for i in items(n) { defer h(i); if i break }\n\
+             for j in 0..=n { defer h(j); if j continue }\n\
+             n\n\
+         }"
+        .to_string(),
+        Origin::Unknown,
+    ));
+    declare_module(&module, root, &errs);
+    run_pass1(&mut module, root, &errs);
+    assert!(!errs.has_errors());
+
+    fn lowered(expr: &Expr) -> bool {
+        let mut all = !matches!(expr, Expr::Defer(_) | Expr::For(_));
+        expr.for_each_child(&mut |child| all &= lowered(child));
+        all
+    }
+    let [Item::Definer(ItemNamed {
+        kind: NamedKind::Func(f),
+        ..
+    })] = module.items.as_slice()
+    else {
+        panic!("expected a function");
+    };
+    assert!(f.body.exprs().all(lowered));
+
+    let cases = [
+        ("f :: func() { defer if x return y; z }", "return"),
+        (
+            "f :: func() { loop @outer { defer loop { break @outer }; z } }",
+            "break",
+        ),
+        ("f :: func() { while go { defer continue } }", "continue"),
+    ];
+    for (src, keyword) in cases {
+        let msg = format!("error: `{keyword}` can not leave a deferred expression");
+        assert_error(&pass1(src), &msg);
+    }
+    assert!(!pass1("f :: func() { defer loop { if x break; continue }; z }").has_errors());
+}
+
+#[test]
+fn samples_analyze() {
+    // the samples use `option` without defining it
+    let prelude = "option(T) :: enum { some(T), none }\n";
+    for name in ["iter.w", "vec.w", "trait.w"] {
+        let path = format!(
+            "{}/../WIP_tests/mod_test/{name}",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let src = std::fs::read_to_string(path).unwrap();
+        let errs = pass1_file(RcStr::new(prelude.to_string() + &src, Origin::Unknown));
+        assert!(!errs.has_errors(), "{name}:\n{}", messages(&errs));
+    }
+}
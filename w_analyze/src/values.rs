@@ -0,0 +1,247 @@
+use crate::data::err::{DefinitionKind, InitCycleError, MultipleDefinitionsError};
+use crate::{ErrorCollector, Module};
+use std::collections::HashMap;
+use w_parse::expr::call::ExprCall;
+use w_parse::expr::loops::ExprFor;
+use w_parse::expr::pattern::Pattern;
+use w_parse::expr::Expr;
+use w_parse::item::func::ItemFunc;
+use w_parse::item::named::{ItemNamed, NamedKind};
+use w_parse::item::Item;
+use w_parse::{Ident, ParsedModule, Symbol};
+
+/// Orders the initializers of module level values so every value is initialized before it is used.
+///
+/// Functions called by an initializer count as part of it, cycles are reported with every step.
+pub fn order_values<'gc>(module: &ParsedModule, tsys: &'gc Module<'gc>, errs: &ErrorCollector) {
+    let mut graph = Graph::default();
+
+    for item in module.items.iter() {
+        match item {
+            Item::Definer(ItemNamed {
+                name,
+                kind: NamedKind::Func(func),
+                ..
+            }) => {
+                graph
+                    .nodes
+                    .entry(Key::Named(name.sym()))
+                    .or_insert_with(|| Node::func(name, func));
+            }
+            Item::Method(method) => graph.add_method(&method.name, &method.func),
+            Item::Impl(imp) => imp
+                .methods
+                .iter()
+                .for_each(|method| graph.add_method(&method.name, &method.func)),
+            Item::Definer(_) | Item::Import(_) | Item::Modules(_) => (),
+        }
+    }
+
+    let mut values = vec![];
+    for item in module.items.iter() {
+        let Item::Definer(ItemNamed {
+            name,
+            kind: NamedKind::Value(value),
+            ..
+        }) = item
+        else {
+            continue;
+        };
+
+        if let Some(first) = graph.nodes.get(&Key::Named(name.sym())) {
+            errs.add_error(MultipleDefinitionsError {
+                loc: name.clone(),
+                first: first.name.clone(),
+                kind: DefinitionKind::Value,
+            });
+            continue;
+        }
+
+        let mut refs = vec![];
+        references(&value.value, &mut vec![], &mut refs);
+        graph.nodes.insert(
+            Key::Named(name.sym()),
+            Node {
+                name: name.clone(),
+                value: true,
+                refs,
+            },
+        );
+        values.push(Key::Named(name.sym()));
+    }
+
+    let mut walk = Walk::default();
+    for value in values {
+        if !walk.state.contains_key(&value) {
+            graph.visit(value, &mut walk, errs);
+        }
+    }
+    *tsys.values.borrow_mut() = walk.order;
+}
+
+#[derive(Default)]
+struct Graph {
+    /// Values and the functions they may call
+    nodes: HashMap<Key, Node>,
+}
+
+/// Methods are only known by their name here, a call of `name` may use every method called so
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Named(Symbol),
+    Method(Symbol),
+}
+
+struct Node {
+    name: Ident,
+    value: bool,
+    /// Every name used which is not bound locally
+    refs: Vec<(Key, Ident)>,
+}
+
+impl Node {
+    fn func(name: &Ident, func: &ItemFunc) -> Self {
+        let mut bound = func.func.args.iter().map(|arg| arg.name.sym()).collect();
+        let mut refs = vec![];
+        func.body
+            .exprs()
+            .for_each(|expr| references(expr, &mut bound, &mut refs));
+        Node {
+            name: name.clone(),
+            value: false,
+            refs,
+        }
+    }
+}
+
+#[derive(Copy, Clone)]
+enum State {
+    Visiting,
+    Done,
+}
+
+#[derive(Default)]
+struct Walk {
+    state: HashMap<Key, State>,
+    /// The nodes being visited with the use leading to the next one
+    stack: Vec<(Key, Ident)>,
+    order: Vec<Ident>,
+}
+
+impl Graph {
+    fn add_method(&mut self, name: &Ident, func: &ItemFunc) {
+        let node = Node::func(name, func);
+        self.nodes
+            .entry(Key::Method(name.sym()))
+            .and_modify(|first| first.refs.extend(node.refs.iter().cloned()))
+            .or_insert(node);
+    }
+
+    fn visit(&self, key: Key, walk: &mut Walk, errs: &ErrorCollector) {
+        walk.state.insert(key, State::Visiting);
+
+        let node = &self.nodes[&key];
+        for (next, usage) in &node.refs {
+            let next = *next;
+            if !self.nodes.contains_key(&next) {
+                continue;
+            }
+
+            walk.stack.push((key, usage.clone()));
+            match walk.state.get(&next) {
+                None => self.visit(next, walk, errs),
+                Some(State::Visiting) => self.report_cycle(next, walk, errs),
+                Some(State::Done) => (),
+            }
+            walk.stack.pop();
+        }
+
+        walk.state.insert(key, State::Done);
+        if node.value {
+            walk.order.push(node.name.clone());
+        }
+    }
+
+    fn report_cycle(&self, start: Key, walk: &Walk, errs: &ErrorCollector) {
+        let from = walk
+            .stack
+            .iter()
+            .rposition(|(key, _)| *key == start)
+            .unwrap();
+        let mut cycle = walk.stack[from..].to_vec();
+
+        // functions calling each other are fine as long as no value is part of it
+        let Some(first_value) = cycle.iter().position(|(key, _)| self.nodes[key].value) else {
+            return;
+        };
+        cycle.rotate_left(first_value);
+
+        errs.add_error(InitCycleError {
+            chain: cycle
+                .into_iter()
+                .map(|(key, usage)| (self.nodes[&key].name.clone(), usage))
+                .collect(),
+        });
+    }
+}
+
+/// Collects the names used in `expr` which are not bound in it, `bound` are the names in scope
+fn references(expr: &Expr, bound: &mut Vec<Symbol>, refs: &mut Vec<(Key, Ident)>) {
+    // bindings of nested expressions end with them, blocks are flattened by `for_each_child`
+    let len = bound.len();
+    match expr {
+        Expr::Ident(name) => {
+            if !bound.contains(&name.sym()) {
+                refs.push((Key::Named(name.sym()), name.clone()));
+            }
+        }
+        // `x.name(...)` and `ty:name(...)` may call any method called `name`
+        Expr::Call(ExprCall { base, args }) => {
+            match &**base {
+                Expr::Field(field) => {
+                    references(&field.base, bound, refs);
+                    refs.push((Key::Method(field.field.sym()), field.field.clone()));
+                }
+                Expr::Path(path) if path.path.len() > 1 => {
+                    let name = path.path.last().unwrap();
+                    refs.push((Key::Method(name.sym()), name.clone()));
+                }
+                base => references(base, bound, refs),
+            }
+            args.values
+                .iter()
+                .for_each(|arg| references(arg, bound, refs));
+        }
+        Expr::Define(define) => {
+            references(&define.value, bound, refs);
+            declare(&define.pattern, bound);
+            return;
+        }
+        Expr::Match(mt) => {
+            references(&mt.value, bound, refs);
+            for arm in &mt.arms {
+                declare(&arm.pattern, bound);
+                arm.body
+                    .exprs()
+                    .for_each(|expr| references(expr, bound, refs));
+                bound.truncate(len);
+            }
+        }
+        Expr::For(ExprFor {
+            binding,
+            iterable,
+            body,
+            ..
+        }) => {
+            references(iterable, bound, refs);
+            declare(binding, bound);
+            body.exprs().for_each(|expr| references(expr, bound, refs));
+        }
+        expr => expr.for_each_child(&mut |child| references(child, bound, refs)),
+    }
+    bound.truncate(len);
+}
+
+fn declare(pattern: &Pattern, bound: &mut Vec<Symbol>) {
+    pattern.for_each_binding(&mut |bind| bound.push(bind.name.sym()));
+}
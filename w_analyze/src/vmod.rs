@@ -3,6 +3,7 @@ use w_parse::{Ident, ParsedModule};
 
 pub trait ModuleProvider {
     fn get(&self, path: &Path) -> Option<ParsedModule>;
+    #[allow(clippy::result_unit_err)]
     fn submit(&mut self, path: &Path, md: ParsedModule) -> Result<(), ()>;
 
     fn root(&self) -> Ident;
@@ -1,8 +1,8 @@
 use crate::parser::TokenSpan;
 use nom::error::{ErrorKind, ParseError};
-use nom::Offset;
 use std::borrow::Cow;
 use std::fmt::{Debug, Formatter};
+use w_rcstr::expand_tabs;
 
 pub struct ErrorChain {
     err_acc: Vec<Error>,
@@ -68,35 +68,29 @@ impl Debug for Error {
             return Ok(());
         }
 
-        let last_t = &self.location[0].span;
-        let top_lines = (last_t.location_line() - 1).saturating_sub(3);
-        let top_offset = (last_t.location_line() - 1) - top_lines;
-        let mut lines = self.location.file.lines().skip(top_lines as usize);
-        let mut tmp = vec![];
-
-        for _ in 0..top_offset {
-            tmp.push(lines.next().unwrap());
-        }
-
-        let main = lines.next().unwrap();
-        let line_offset = (**self.location.file).offset(main);
-        let offset = last_t.location_offset() - line_offset;
+        let last_t = self.location[0].span.fragment();
+        let pos = last_t.line_col();
+        let top_line = pos.line.saturating_sub(3);
 
         writeln!(
             f,
-            "At {top_lines}:{offset}\n--------------------------------"
+            "At {}:{}\n--------------------------------",
+            pos.line + 1,
+            pos.chars + 1
         )?;
 
-        for line in tmp {
-            writeln!(f, "{line}")?;
+        for line in top_line..pos.line {
+            writeln!(f, "{}", expand_tabs(last_t.line(line).unwrap()))?;
         }
+        let main = expand_tabs(last_t.line(pos.line).unwrap());
         writeln!(f, "{main}")?;
-        write!(f, "{}", String::from_iter((0..offset).map(|_| ' ')))?;
-        let len = last_t.len().max(1);
-        writeln!(f, "{}", String::from_iter((0..len).map(|_| '^')))?;
 
-        for line in lines.take(3) {
-            writeln!(f, "{line}")?;
+        let marker = last_t.marker();
+        write!(f, "{}", " ".repeat(marker.start))?;
+        writeln!(f, "{}", "^".repeat(marker.len()))?;
+
+        for line in (pos.line + 1..).take(3).map_while(|line| last_t.line(line)) {
+            writeln!(f, "{}", expand_tabs(line))?;
         }
 
         write!(f, "--------------------------------")?;
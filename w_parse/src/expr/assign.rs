@@ -1,26 +1,104 @@
-use crate::expr::{parse_expression, Expr};
-use crate::{tag, ParResult, TokenSpan};
+use crate::expr::ops::BiOp;
+use crate::expr::{parse_expr_pre_pass, parse_expression, Expr};
+use crate::{ErrorChain, ParResult, TokenSpan};
 use nom::combinator::map;
-use w_tokenize::Span;
+use nom::error::{ErrorKind, ParseError};
+use nom::{Err, InputTake};
+use w_tokenize::{Kind, Span};
 
 #[derive(Debug, Clone)]
 pub struct ExprAssignment {
+    pub op: AssignOp,
     pub span_op: Span,
     pub assignee: Box<Expr>,
     pub value: Box<Expr>,
 }
 
+/// `=` or one of the compound operators, `a += b` is `a = a + b` evaluating `a` once
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AssignOp {
+    Assign,
+
+    // Math operands
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+
+    // Bitwise operands
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
 pub fn parse_assignment(i: TokenSpan) -> ParResult<ExprAssignment> {
-    let (i, assignee) = map(parse_expression, Box::new)(i)?;
-    let (i, span_op) = tag!(Kind::Assign)(i)?;
+    // not `parse_expression`, it would try to parse an assignment again
+    let (i, assignee) = map(|i| parse_expr_pre_pass(i, true), Box::new)(i)?;
+    let (i, (span_op, op)) = parse_assign_op(i)?;
     let (i, value) = map(parse_expression, Box::new)(i)?;
 
     Ok((
         i,
         ExprAssignment {
+            op,
             span_op,
             assignee,
             value,
         },
     ))
 }
+
+pub fn parse_assign_op(i: TokenSpan) -> ParResult<(Span, AssignOp)> {
+    if i.is_empty() {
+        return Err(Err::Error(ErrorChain::from_error_kind(i, ErrorKind::Eof)));
+    }
+
+    let (i, took) = TokenSpan::take_split(&i, 1);
+    let Some(op) = AssignOp::from_kind(&took[0].kind) else {
+        return Err(Err::Error(ErrorChain::from_error_kind(
+            took.clone(),
+            ErrorKind::Tag,
+        )));
+    };
+
+    Ok((i, (took[0].span.clone(), op)))
+}
+
+impl AssignOp {
+    pub fn from_kind(kind: &Kind) -> Option<Self> {
+        Some(match kind {
+            Kind::Assign => AssignOp::Assign,
+            Kind::AddAssign => AssignOp::Add,
+            Kind::SubAssign => AssignOp::Sub,
+            Kind::MulAssign => AssignOp::Mul,
+            Kind::DivAssign => AssignOp::Div,
+            Kind::ModAssign => AssignOp::Mod,
+            Kind::AndAssign => AssignOp::And,
+            Kind::OrAssign => AssignOp::Or,
+            Kind::XorAssign => AssignOp::Xor,
+            Kind::ShlAssign => AssignOp::Shl,
+            Kind::ShrAssign => AssignOp::Shr,
+            _ => return None,
+        })
+    }
+
+    /// The operator applied before assigning, `None` for a plain `=`
+    pub fn binary(self) -> Option<BiOp> {
+        Some(match self {
+            AssignOp::Assign => return None,
+            AssignOp::Add => BiOp::Add,
+            AssignOp::Sub => BiOp::Sub,
+            AssignOp::Mul => BiOp::Mul,
+            AssignOp::Div => BiOp::Div,
+            AssignOp::Mod => BiOp::Mod,
+            AssignOp::And => BiOp::And,
+            AssignOp::Or => BiOp::Or,
+            AssignOp::Xor => BiOp::Xor,
+            AssignOp::Shl => BiOp::Shl,
+            AssignOp::Shr => BiOp::Shr,
+        })
+    }
+}
@@ -1,3 +1,8 @@
+use crate::expr::branch::parse_branch;
+use crate::expr::defer::parse_defer;
+use crate::expr::jump::{parse_jump, Label};
+use crate::expr::loops::{parse_for, parse_loop, parse_while};
+use crate::expr::pattern::parse_match;
 use crate::expr::{parse_expression, Expr};
 use crate::{tag, ParResult, TokenSpan, Weak};
 use assert_matches::assert_matches;
@@ -30,21 +35,23 @@ pub enum BlockKind {
     Inline(Box<Expr>),
 }
 
+/// `{ ... }` or `-> expr`, jumps and control flow like `else return false`
+/// or `else if ...` can be used without the arrow
 pub fn parse_block(i: TokenSpan) -> ParResult<ExprBlock> {
-    alt((parse_block_many, parse_block_inline))(i)
+    alt((parse_block_many, parse_block_inline, parse_block_bare))(i)
 }
 
-fn parse_block_many(i: TokenSpan) -> ParResult<ExprBlock> {
+/// `{ ... }`, also a scope of its own when used as an expression
+pub fn parse_block_many(i: TokenSpan) -> ParResult<ExprBlock> {
     let (oi, block) = Weak(Kind::Block(Rc::from([]))).parse(i)?;
     let span = block.span;
-    let mut i =
-        assert_matches!(block.kind, Kind::Block(vals) => TokenSpan::new(oi.file.clone(), vals));
+    let mut i = assert_matches!(block.kind, Kind::Block(vals) => TokenSpan::without_docs(oi.file.clone(), vals));
 
     let mut acc = vec![];
-    let last;
+    let mut last = None;
 
-    loop {
-        let (ni, expr) = parse_expression(i)?;
+    while !i.is_empty() {
+        let (ni, expr) = alt((map(parse_defer, Expr::Defer), parse_expression))(i)?;
         let (ni, sim) = alt((
             map(
                 pair(
@@ -62,7 +69,6 @@ fn parse_block_many(i: TokenSpan) -> ParResult<ExprBlock> {
         match sim {
             Either::Left((sim, Some(_))) => {
                 acc.push(Statement { expr, sim });
-                last = None;
                 break;
             }
             Either::Left((sim, None)) => acc.push(Statement { expr, sim }),
@@ -103,3 +109,54 @@ fn parse_block_inline(oi: TokenSpan) -> ParResult<ExprBlock> {
         },
     ))
 }
+
+fn parse_block_bare(oi: TokenSpan) -> ParResult<ExprBlock> {
+    let (i, expr) = alt((
+        parse_jump,
+        map(parse_branch, Expr::Branch),
+        map(parse_match, Expr::Match),
+        map(parse_while, Expr::While),
+        map(parse_for, Expr::For),
+        map(parse_loop, Expr::Loop),
+    ))(oi.clone())?;
+
+    let offset = oi.offset(&i);
+    let span = TokenSpan::slice(&oi, ..offset);
+
+    Ok((
+        i,
+        ExprBlock {
+            span: (&span).into(),
+            kind: BlockKind::Inline(Box::new(expr)),
+        },
+    ))
+}
+
+impl ExprBlock {
+    /// The statements and the returned expression of the block
+    pub fn exprs(&self) -> impl Iterator<Item = &Expr> {
+        let (stmts, last) = match &self.kind {
+            BlockKind::Many { stmts, returning } => (&stmts[..], returning.as_deref()),
+            BlockKind::Inline(expr) => (&[][..], Some(&**expr)),
+        };
+        stmts.iter().map(|stmt| &stmt.expr).chain(last)
+    }
+
+    pub fn exprs_mut(&mut self) -> impl Iterator<Item = &mut Expr> {
+        let (stmts, last) = match &mut self.kind {
+            BlockKind::Many { stmts, returning } => (&mut stmts[..], returning.as_deref_mut()),
+            BlockKind::Inline(expr) => (&mut [][..], Some(&mut **expr)),
+        };
+        stmts.iter_mut().map(|stmt| &mut stmt.expr).chain(last)
+    }
+
+    pub fn diverges(&self) -> bool {
+        self.exprs().any(Expr::diverges)
+    }
+
+    /// Whether a `break` in the block leaves the loop labelled `label`,
+    /// `innermost` is false once another loop is in between
+    pub(crate) fn breaks_from(&self, label: Option<&Label>, innermost: bool) -> bool {
+        self.exprs().any(|expr| expr.breaks_from(label, innermost))
+    }
+}
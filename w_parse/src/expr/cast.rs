@@ -0,0 +1,32 @@
+use crate::expr::Expr;
+use crate::types::{parse_type, ItemTy};
+use crate::{parse_keyword, ParResult, TokenSpan};
+use nom::Err;
+use w_tokenize::Span;
+
+/// `value as ty`, binds looser than unary operators but tighter than binary ones
+#[derive(Debug, Clone)]
+pub struct ExprCast {
+    pub base: Box<Expr>,
+    pub span_as: Span,
+    pub ty: ItemTy,
+}
+
+pub fn parse_cast_wrapper(i: TokenSpan) -> ParResult<Box<dyn FnOnce(Expr) -> Expr>> {
+    let (i, span_as) = parse_keyword("as")(i)?;
+    let (i, ty) = parse_type(i).map_err(|err| match err {
+        Err::Error(err) => Err::Failure(err),
+        err => err,
+    })?;
+
+    Ok((
+        i,
+        Box::new(move |expr| {
+            Expr::Cast(ExprCast {
+                base: Box::new(expr),
+                span_as,
+                ty,
+            })
+        }),
+    ))
+}
@@ -0,0 +1,260 @@
+use crate::error::{Error, ErrorChain};
+use crate::expr::block::{BlockKind, ExprBlock, Statement};
+use crate::expr::define::ExprDefine;
+use crate::expr::jump::{ExprBreak, ExprContinue, ExprReturn, Label};
+use crate::expr::pattern::{PatBinding, Pattern};
+use crate::expr::{parse_expression, ChildMut, Expr};
+use crate::{parse_keyword, Ident, ParResult, Symbol, TokenSpan};
+use nom::combinator::cut;
+use nom::Err;
+use w_tokenize::Span;
+
+/// `defer r.dispose()`, runs `expr` whenever the enclosing block is left
+#[derive(Debug, Clone)]
+pub struct ExprDefer {
+    pub span_defer: Span,
+    pub expr: Box<Expr>,
+}
+
+/// `defer` is only parsed as a statement of a `{ ... }` block, see [`reject_defer`]
+pub fn parse_defer(i: TokenSpan) -> ParResult<ExprDefer> {
+    let (i, span_defer) = parse_keyword("defer")(i)?;
+    let (i, expr) = cut(parse_expression)(i)?;
+
+    Ok((
+        i,
+        ExprDefer {
+            span_defer,
+            expr: Box::new(expr),
+        },
+    ))
+}
+
+/// `defer` anywhere else, there is no block exit the expression could run at
+pub(crate) fn reject_defer(i: TokenSpan) -> ParResult<Expr> {
+    parse_keyword("defer")(i.clone())?;
+
+    Err(Err::Failure(ErrorChain::from(Error::new(
+        i,
+        "`defer` can only be used as a statement of a `{ ... }` block",
+    ))))
+}
+
+impl ExprDefer {
+    /// The first `return`, `break` or `continue` which would leave the deferred expression,
+    /// it already runs while leaving the scope so there is nowhere to go
+    pub fn leaving_jump(&self) -> Option<(Span, &'static str)> {
+        leaving_jump(&self.expr, 0, &mut vec![])
+    }
+}
+
+impl ExprBlock {
+    /// Removes all `defer` statements in the block and the blocks nested in it.
+    ///
+    /// Deferred expressions are copied in front of every exit of their block in reverse order,
+    /// so `{ defer a; defer b; c }` turns into `{ it := c; b; a; it }`
+    /// and `return x` into `{ it := x; b; a; return it }`.
+    /// The same happens for `break` and `continue` leaving the block.
+    pub fn lower_defers(&mut self) {
+        for expr in self.exprs_mut() {
+            expr.lower_defers();
+        }
+
+        let BlockKind::Many { stmts, returning } = &mut self.kind else {
+            return;
+        };
+        // `{ a; defer b }` returns nothing, `b` still runs when the block is left
+        if let Some(Expr::Defer(_)) = returning.as_deref() {
+            let expr = *returning.take().unwrap();
+            stmts.push(statement(expr));
+        }
+        if !stmts.iter().any(|stmt| matches!(stmt.expr, Expr::Defer(_))) {
+            return;
+        }
+
+        let mut deferred = vec![];
+        let mut lowered = vec![];
+        for mut stmt in std::mem::take(stmts) {
+            match stmt.expr {
+                Expr::Defer(defer) => deferred.push(*defer.expr),
+                _ => {
+                    rewrite_exits(&mut stmt.expr, &deferred, 0, &mut vec![]);
+                    lowered.push(stmt);
+                }
+            }
+        }
+
+        let span = self.span.clone();
+        let ret = match returning.take() {
+            Some(mut ret) => {
+                rewrite_exits(&mut ret, &deferred, 0, &mut vec![]);
+                let value = hidden(&span);
+                lowered.push(define(&value, *ret));
+                Some(Box::new(Expr::Ident(value)))
+            }
+            None => None,
+        };
+        lowered.extend(deferred.into_iter().rev().map(statement));
+
+        *stmts = lowered;
+        *returning = ret;
+    }
+}
+
+impl Expr {
+    /// [`ExprBlock::lower_defers`] for every block nested in the expression
+    pub fn lower_defers(&mut self) {
+        self.for_each_child_mut(&mut |child| match child {
+            ChildMut::Expr(expr) => expr.lower_defers(),
+            ChildMut::Block(block) => block.lower_defers(),
+        })
+    }
+}
+
+/// Runs `deferred` in front of every `return`, `break` and `continue` leaving the block,
+/// `depth` counts the loops entered since and `labels` collects their labels
+fn rewrite_exits(expr: &mut Expr, deferred: &[Expr], depth: usize, labels: &mut Vec<Symbol>) {
+    if deferred.is_empty() {
+        return;
+    }
+
+    let leaves = |label: &Option<Label>, labels: &Vec<Symbol>| match label {
+        Some(label) => !labels.contains(&label.name.sym()),
+        None => depth == 0,
+    };
+
+    let (span, value) = match expr {
+        Expr::Return(ExprReturn { span_return, value }) => (span_return.clone(), value),
+        Expr::Break(ExprBreak {
+            span_break,
+            label,
+            value,
+        }) if leaves(label, labels) => (span_break.clone(), value),
+        Expr::Continue(ExprContinue {
+            span_continue,
+            label,
+        }) if leaves(label, labels) => {
+            let span = span_continue.clone();
+            let jump = std::mem::replace(expr, Expr::Ident(hidden(&span)));
+            *expr = run_before(span, None, deferred, jump);
+            return;
+        }
+        Expr::Loop(lp) => {
+            let label = lp.label.as_ref().map(|label| label.name.sym());
+            labels.extend(label);
+            rewrite_children(expr, deferred, depth + 1, labels);
+            labels.truncate(labels.len() - label.is_some() as usize);
+            return;
+        }
+        Expr::While(_) | Expr::For(_) => {
+            return rewrite_children(expr, deferred, depth + 1, labels);
+        }
+        _ => return rewrite_children(expr, deferred, depth, labels),
+    };
+
+    // the value is computed before the deferred expressions run
+    let mut value = value.take();
+    if let Some(value) = &mut value {
+        rewrite_exits(value, deferred, depth, labels);
+    }
+    let tmp = value.as_ref().map(|_| hidden(&span));
+    if let Some(tmp) = &tmp {
+        match expr {
+            Expr::Return(ExprReturn { value, .. }) | Expr::Break(ExprBreak { value, .. }) => {
+                *value = Some(Box::new(Expr::Ident(tmp.clone())))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    let jump = std::mem::replace(expr, Expr::Ident(hidden(&span)));
+    let value = tmp.zip(value).map(|(tmp, value)| define(&tmp, *value));
+    *expr = run_before(span, value, deferred, jump);
+}
+
+fn rewrite_children(expr: &mut Expr, deferred: &[Expr], depth: usize, labels: &mut Vec<Symbol>) {
+    expr.for_each_child_mut(&mut |child| match child {
+        ChildMut::Expr(expr) => rewrite_exits(expr, deferred, depth, labels),
+        ChildMut::Block(block) => block
+            .exprs_mut()
+            .for_each(|expr| rewrite_exits(expr, deferred, depth, labels)),
+    })
+}
+
+/// `{ value; deferred..; jump }`
+fn run_before(span: Span, value: Option<Statement>, deferred: &[Expr], jump: Expr) -> Expr {
+    let stmts = value
+        .into_iter()
+        .chain(deferred.iter().rev().cloned().map(statement))
+        .collect();
+
+    Expr::Block(ExprBlock {
+        span,
+        kind: BlockKind::Many {
+            stmts,
+            returning: Some(Box::new(jump)),
+        },
+    })
+}
+
+/// A name which can not be written in source, so it never shadows anything
+fn hidden(span: &Span) -> Ident {
+    Ident(span.clone(), Symbol::intern("defer value"))
+}
+
+fn define(name: &Ident, value: Expr) -> Statement {
+    statement(Expr::Define(ExprDefine {
+        span_op: name.0.clone(),
+        pattern: Pattern::Binding(PatBinding {
+            mutability: None,
+            name: name.clone(),
+        }),
+        value: Box::new(value),
+    }))
+}
+
+fn statement(expr: Expr) -> Statement {
+    Statement { expr, sim: None }
+}
+
+/// The first `return`, `break` or `continue` in `expr` which leaves it
+fn leaving_jump(
+    expr: &Expr,
+    depth: usize,
+    labels: &mut Vec<Symbol>,
+) -> Option<(Span, &'static str)> {
+    let leaves = |label: &Option<Label>, labels: &Vec<Symbol>| match label {
+        Some(label) => !labels.contains(&label.name.sym()),
+        None => depth == 0,
+    };
+
+    let depth = match expr {
+        Expr::Return(ret) => return Some((ret.span_return.clone(), "return")),
+        Expr::Break(brk) if leaves(&brk.label, labels) => {
+            return Some((brk.span_break.clone(), "break"))
+        }
+        Expr::Continue(cont) if leaves(&cont.label, labels) => {
+            return Some((cont.span_continue.clone(), "continue"))
+        }
+        Expr::Loop(lp) => {
+            let label = lp.label.as_ref().map(|label| label.name.sym());
+            labels.extend(label);
+            let mut found = None;
+            expr.for_each_child(&mut |child| {
+                found = found
+                    .take()
+                    .or_else(|| leaving_jump(child, depth + 1, labels))
+            });
+            labels.truncate(labels.len() - label.is_some() as usize);
+            return found;
+        }
+        Expr::While(_) | Expr::For(_) => depth + 1,
+        _ => depth,
+    };
+
+    let mut found = None;
+    expr.for_each_child(&mut |child| {
+        found = found.take().or_else(|| leaving_jump(child, depth, labels))
+    });
+    found
+}
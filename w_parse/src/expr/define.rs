@@ -1,17 +1,22 @@
+use crate::expr::pattern::{parse_pattern, Pattern};
 use crate::expr::{parse_expression, Expr};
 use crate::{tag, ParResult, TokenSpan};
 use nom::combinator::map;
 use w_tokenize::Span;
 
+/// `name := value`, `mut name := value`
+///
+/// As the condition of `if` and `while` the pattern may be refutable,
+/// `option:some(x) := it.next()` only continues if the value matches.
 #[derive(Debug, Clone)]
 pub struct ExprDefine {
     pub span_op: Span,
-    pub assignee: Box<Expr>,
+    pub pattern: Pattern,
     pub value: Box<Expr>,
 }
 
 pub fn parse_define(i: TokenSpan) -> ParResult<ExprDefine> {
-    let (i, assignee) = map(parse_expression, Box::new)(i)?;
+    let (i, pattern) = parse_pattern(i)?;
     let (i, span_op) = tag!(Kind::Define)(i)?;
     let (i, value) = map(parse_expression, Box::new)(i)?;
 
@@ -19,7 +24,7 @@ pub fn parse_define(i: TokenSpan) -> ParResult<ExprDefine> {
         i,
         ExprDefine {
             span_op,
-            assignee,
+            pattern,
             value,
         },
     ))
@@ -1,4 +1,5 @@
 use crate::expr::many::{parse_array, ExprArray};
+use crate::expr::range::ExprRange;
 use crate::expr::Expr;
 use crate::{ParResult, TokenSpan};
 
@@ -20,3 +21,13 @@ pub fn parse_index_wrapper(i: TokenSpan) -> ParResult<Box<dyn FnOnce(Expr) -> Ex
         }),
     ))
 }
+
+impl ExprIndex {
+    /// The range of a slicing operation like `buf[..len]`
+    pub fn slice(&self) -> Option<&ExprRange> {
+        match self.args.values.as_slice() {
+            [Expr::Range(range)] => Some(range),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::expr::{parse_expression, Expr};
+use crate::{parse_keyword, parse_name, Ident, ParResult, TokenSpan, Weak};
+use nom::branch::alt;
+use nom::combinator::{map, opt};
+use nom::sequence::pair;
+use w_tokenize::{Kind, Span};
+
+/// `@outer`, names the loop a `break` or `continue` refers to
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub span_at: Span,
+    pub name: Ident,
+}
+
+/// `break`, `break @outer`, `break value`
+#[derive(Debug, Clone)]
+pub struct ExprBreak {
+    pub span_break: Span,
+    pub label: Option<Label>,
+    pub value: Option<Box<Expr>>,
+}
+
+/// `continue`, `continue @outer`
+#[derive(Debug, Clone)]
+pub struct ExprContinue {
+    pub span_continue: Span,
+    pub label: Option<Label>,
+}
+
+/// `return`, `return value`
+#[derive(Debug, Clone)]
+pub struct ExprReturn {
+    pub span_return: Span,
+    pub value: Option<Box<Expr>>,
+}
+
+pub fn parse_label(i: TokenSpan) -> ParResult<Label> {
+    map(pair(Weak(Kind::At), parse_name), |(at, name)| Label {
+        span_at: at.span,
+        name,
+    })(i)
+}
+
+/// Any expression leaving the current control flow, all of them have the type `!`
+pub fn parse_jump(i: TokenSpan) -> ParResult<Expr> {
+    alt((
+        map(parse_break, Expr::Break),
+        map(parse_continue, Expr::Continue),
+        map(parse_return, Expr::Return),
+    ))(i)
+}
+
+pub fn parse_break(i: TokenSpan) -> ParResult<ExprBreak> {
+    let (i, span_break) = parse_keyword("break")(i)?;
+    let (i, label) = opt(parse_label)(i)?;
+    let (i, value) = opt(map(parse_expression, Box::new))(i)?;
+
+    Ok((
+        i,
+        ExprBreak {
+            span_break,
+            label,
+            value,
+        },
+    ))
+}
+
+pub fn parse_continue(i: TokenSpan) -> ParResult<ExprContinue> {
+    let (i, span_continue) = parse_keyword("continue")(i)?;
+    let (i, label) = opt(parse_label)(i)?;
+
+    Ok((
+        i,
+        ExprContinue {
+            span_continue,
+            label,
+        },
+    ))
+}
+
+pub fn parse_return(i: TokenSpan) -> ParResult<ExprReturn> {
+    let (i, span_return) = parse_keyword("return")(i)?;
+    let (i, value) = opt(map(parse_expression, Box::new))(i)?;
+
+    Ok((i, ExprReturn { span_return, value }))
+}
@@ -1,8 +1,21 @@
-use crate::expr::block::{parse_block, ExprBlock};
+use crate::expr::assign::{AssignOp, ExprAssignment};
+use crate::expr::block::{parse_block, BlockKind, ExprBlock, Statement};
+use crate::expr::branch::ExprBranch;
+use crate::expr::call::ExprCall;
+use crate::expr::define::ExprDefine;
+use crate::expr::field::ExprField;
+use crate::expr::jump::{parse_label, Label};
+use crate::expr::many::ExprTuple;
+use crate::expr::ops::{BiOp, ExprBinary};
+use crate::expr::path::{ExprPath, PathRoot};
+use crate::expr::pattern::{parse_pattern, PatBinding, PatTuple, PatVariant, Pattern};
+use crate::expr::range::ExprRange;
 use crate::expr::{parse_expression, Expr};
-use crate::{parse_keyword, ParResult, TokenSpan};
-use nom::combinator::map;
-use w_tokenize::Span;
+use crate::{parse_keyword, Error, ErrorChain, Ident, ParResult, Symbol, TokenSpan};
+use nom::combinator::{map, opt};
+use nom::Err;
+use w_rcstr::{Origin, RcStr};
+use w_tokenize::{Number, Span};
 
 #[derive(Debug, Clone)]
 pub struct ExprWhile {
@@ -11,6 +24,28 @@ pub struct ExprWhile {
     pub body: ExprBlock,
 }
 
+/// `loop { ... }`, `loop @outer { ... }`
+#[derive(Debug, Clone)]
+pub struct ExprLoop {
+    pub span_loop: Span,
+    pub label: Option<Label>,
+    pub body: ExprBlock,
+}
+
+/// `for i in 0..len { ... }` or `for _ in 0..extra -> ...`
+///
+/// The iterable is evaluated once. Ranges with a start count up to their end,
+/// anything else has to provide `next func(self *Self) option(T)` like `iter(T)` does.
+/// See [`ExprFor::desugar`] for the loop it stands for.
+#[derive(Debug, Clone)]
+pub struct ExprFor {
+    pub span_for: Span,
+    pub binding: Pattern,
+    pub span_in: Span,
+    pub iterable: Box<Expr>,
+    pub body: ExprBlock,
+}
+
 pub fn parse_while(i: TokenSpan) -> ParResult<ExprWhile> {
     let (i, span_while) = parse_keyword("while")(i)?;
     let (i, cond) = map(parse_expression, Box::new)(i)?;
@@ -25,3 +60,305 @@ pub fn parse_while(i: TokenSpan) -> ParResult<ExprWhile> {
         },
     ))
 }
+
+pub fn parse_loop(i: TokenSpan) -> ParResult<ExprLoop> {
+    let (i, span_loop) = parse_keyword("loop")(i)?;
+    let (i, label) = opt(parse_label)(i)?;
+    let (i, body) = parse_block(i)?;
+
+    Ok((
+        i,
+        ExprLoop {
+            span_loop,
+            label,
+            body,
+        },
+    ))
+}
+
+pub fn parse_for(i: TokenSpan) -> ParResult<ExprFor> {
+    let (i, span_for) = parse_keyword("for")(i)?;
+
+    // once `for` is there, everything else is mandatory
+    let (i, binding) = parse_pattern(i).map_err(failure)?;
+    let (i, span_in) = parse_keyword("in")(i).map_err(failure)?;
+    let (ni, iterable) = map(parse_expression, Box::new)(i.clone()).map_err(failure)?;
+    if matches!(&*iterable, Expr::Range(ExprRange { start: None, .. })) {
+        return Err(Err::Failure(ErrorChain::from(Error::new(
+            i,
+            "Ranges without a start can not be iterated",
+        ))));
+    }
+    let i = ni;
+    let (i, body) = parse_block(i).map_err(failure)?;
+
+    Ok((
+        i,
+        ExprFor {
+            span_for,
+            binding,
+            span_in,
+            iterable,
+            body,
+        },
+    ))
+}
+
+fn failure(err: Err<ErrorChain>) -> Err<ErrorChain> {
+    match err {
+        Err::Error(err) => Err::Failure(err),
+        err => err,
+    }
+}
+
+impl ExprFor {
+    /// Lowers the loop onto `while`, ranges count from their start:
+    ///
+    /// ```text
+    /// {
+    ///     mut it := start;
+    ///     end := end;
+    ///     while it < end {
+    ///         binding := it;
+    ///         it += 1;
+    ///         body
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// `a..=b` is described in [`ExprFor::desugar_inclusive`], `a.._` loops with `loop`.
+    /// Every other iterable is asked for its next value:
+    ///
+    /// ```text
+    /// {
+    ///     mut it := iterable;
+    ///     while :option:some(binding) := it.next() body
+    /// }
+    /// ```
+    ///
+    /// `it` and `end` can not be written in source, so they never shadow anything the body uses.
+    pub fn desugar(&self) -> ExprBlock {
+        let Expr::Range(ExprRange {
+            start: Some(start),
+            end,
+            inclusive,
+            ..
+        }) = &*self.iterable
+        else {
+            return self.desugar_next();
+        };
+
+        let lw = Lowering::new(&self.span_for);
+        let mut stmts = vec![lw.define(lw.counter(), start.clone(), true)];
+        let body = || {
+            lw.block(
+                vec![lw.bind(&self.binding), lw.statement(lw.increment())],
+                Expr::Block(self.body.clone()),
+            )
+        };
+        let lowered = match end {
+            Some(end) => {
+                stmts.push(lw.define(lw.end(), end.clone(), false));
+                if *inclusive {
+                    return self.desugar_inclusive(&lw, stmts);
+                }
+                Expr::While(ExprWhile {
+                    span_while: lw.span.clone(),
+                    cond: Box::new(lw.compare(BiOp::Lt)),
+                    body: body(),
+                })
+            }
+            None => Expr::Loop(ExprLoop {
+                span_loop: lw.span.clone(),
+                label: None,
+                body: body(),
+            }),
+        };
+        lw.block(stmts, lowered)
+    }
+
+    /// The counter must not step past the end, it could be the largest value of its type:
+    ///
+    /// ```text
+    /// {
+    ///     mut it := start;
+    ///     end := end;
+    ///     mut more := it <= end;
+    ///     while more {
+    ///         binding := it;
+    ///         more = it < end;
+    ///         if more -> it += 1;
+    ///         body
+    ///     }
+    /// }
+    /// ```
+    fn desugar_inclusive(&self, lw: &Lowering, mut stmts: Vec<Statement>) -> ExprBlock {
+        let more = lw.hidden("for more");
+        stmts.push(lw.define(more.clone(), Box::new(lw.compare(BiOp::Le)), true));
+
+        let update = Expr::Assign(ExprAssignment {
+            op: AssignOp::Assign,
+            span_op: lw.span.clone(),
+            assignee: Box::new(Expr::Ident(more.clone())),
+            value: Box::new(lw.compare(BiOp::Lt)),
+        });
+        let step = Expr::Branch(ExprBranch {
+            span_if: lw.span.clone(),
+            cond: Box::new(Expr::Ident(more.clone())),
+            body: ExprBlock {
+                span: lw.span.clone(),
+                kind: BlockKind::Inline(Box::new(lw.increment())),
+            },
+            span_else: None,
+            body_else: None,
+        });
+
+        let body = lw.block(
+            vec![
+                lw.bind(&self.binding),
+                lw.statement(update),
+                lw.statement(step),
+            ],
+            Expr::Block(self.body.clone()),
+        );
+        lw.block(
+            stmts,
+            Expr::While(ExprWhile {
+                span_while: lw.span.clone(),
+                cond: Box::new(Expr::Ident(more)),
+                body,
+            }),
+        )
+    }
+
+    fn desugar_next(&self) -> ExprBlock {
+        let lw = Lowering::new(&self.span_for);
+        let iter = lw.counter();
+
+        // `next` takes the iterator by pointer
+        let define = lw.define(iter.clone(), self.iterable.clone(), true);
+
+        let next = Expr::Call(ExprCall {
+            base: Box::new(Expr::Field(ExprField {
+                base: Box::new(Expr::Ident(iter)),
+                dot: lw.span.clone(),
+                field: lw.hidden("next"),
+            })),
+            args: ExprTuple {
+                span: lw.span.clone(),
+                values: vec![],
+            },
+        });
+
+        // taken from the root, the module of the loop may not see `option`
+        let some = Pattern::Variant(PatVariant {
+            path: ExprPath {
+                root: Some(PathRoot::Absolute(lw.span.clone())),
+                path: vec![lw.hidden("option"), lw.hidden("some")],
+            },
+            fields: Some(PatTuple {
+                span: lw.span.clone(),
+                fields: vec![self.binding.clone()],
+            }),
+        });
+
+        let cond = Expr::Define(ExprDefine {
+            span_op: lw.span.clone(),
+            pattern: some,
+            value: Box::new(next),
+        });
+
+        lw.block(
+            vec![define],
+            Expr::While(ExprWhile {
+                span_while: lw.span.clone(),
+                cond: Box::new(cond),
+                body: self.body.clone(),
+            }),
+        )
+    }
+}
+
+/// Builds the expressions a `for` loop is lowered onto, all of them point at `for`
+struct Lowering {
+    span: Span,
+}
+
+impl Lowering {
+    fn new(span: &Span) -> Self {
+        Self { span: span.clone() }
+    }
+
+    fn hidden(&self, name: &str) -> Ident {
+        Ident(self.span.clone(), Symbol::intern(name))
+    }
+
+    /// The iterator, or the next value of a range
+    fn counter(&self) -> Ident {
+        self.hidden("for iter")
+    }
+
+    fn end(&self) -> Ident {
+        self.hidden("for end")
+    }
+
+    fn define(&self, name: Ident, value: Box<Expr>, mutable: bool) -> Statement {
+        self.statement(Expr::Define(ExprDefine {
+            span_op: self.span.clone(),
+            pattern: Pattern::Binding(PatBinding {
+                mutability: mutable.then(|| self.span.clone()),
+                name,
+            }),
+            value,
+        }))
+    }
+
+    /// `binding := it;`
+    fn bind(&self, binding: &Pattern) -> Statement {
+        self.statement(Expr::Define(ExprDefine {
+            span_op: self.span.clone(),
+            pattern: binding.clone(),
+            value: Box::new(Expr::Ident(self.counter())),
+        }))
+    }
+
+    /// `it += 1;`, the `1` does not appear in the source
+    fn increment(&self) -> Expr {
+        let one = Span::new(RcStr::new("1".to_string(), Origin::Unknown));
+        Expr::Assign(ExprAssignment {
+            op: AssignOp::Add,
+            span_op: self.span.clone(),
+            assignee: Box::new(Expr::Ident(self.counter())),
+            value: Box::new(Expr::Number(Box::new(Number {
+                sign: None,
+                number: one,
+                suffix: None,
+                base: None,
+            }))),
+        })
+    }
+
+    /// `it < end` or `it <= end`
+    fn compare(&self, op: BiOp) -> Expr {
+        Expr::Binary(ExprBinary {
+            op,
+            op_span: self.span.clone(),
+            left: Box::new(Expr::Ident(self.counter())),
+            right: Box::new(Expr::Ident(self.end())),
+        })
+    }
+
+    fn statement(&self, expr: Expr) -> Statement {
+        Statement { expr, sim: None }
+    }
+
+    fn block(&self, stmts: Vec<Statement>, last: Expr) -> ExprBlock {
+        ExprBlock {
+            span: self.span.clone(),
+            kind: BlockKind::Many {
+                stmts,
+                returning: Some(Box::new(last)),
+            },
+        }
+    }
+}
@@ -29,8 +29,7 @@ pub struct ExprObject {
 pub fn parse_tuple(i: TokenSpan) -> ParResult<ExprTuple> {
     let (i, tuple) = Weak(Kind::Tuple(Rc::from([]))).parse(i)?;
     let span = tuple.span;
-    let tuple =
-        assert_matches!(tuple.kind, Kind::Tuple(vals) => TokenSpan::new(i.file.clone(), vals));
+    let tuple = assert_matches!(tuple.kind, Kind::Tuple(vals) => TokenSpan::without_docs(i.file.clone(), vals));
     let (_, vals) = all_consuming(parse_many0(parse_expression))(tuple)?;
 
     Ok((i, ExprTuple { span, values: vals }))
@@ -39,8 +38,7 @@ pub fn parse_tuple(i: TokenSpan) -> ParResult<ExprTuple> {
 pub fn parse_array(i: TokenSpan) -> ParResult<ExprArray> {
     let (i, array) = Weak(Kind::Array(Rc::from([]))).parse(i)?;
     let span = array.span;
-    let array =
-        assert_matches!(array.kind, Kind::Array(vals) => TokenSpan::new(i.file.clone(), vals));
+    let array = assert_matches!(array.kind, Kind::Array(vals) => TokenSpan::without_docs(i.file.clone(), vals));
     let (_, vals) = all_consuming(parse_many0(parse_expression))(array)?;
 
     Ok((i, ExprArray { span, values: vals }))
@@ -49,8 +47,7 @@ pub fn parse_array(i: TokenSpan) -> ParResult<ExprArray> {
 pub fn parse_object(i: TokenSpan) -> ParResult<ExprObject> {
     let (i, block) = Weak(Kind::Block(Rc::from([]))).parse(i)?;
     let span = block.span;
-    let block =
-        assert_matches!(block.kind, Kind::Block(vals) => TokenSpan::new(i.file.clone(), vals));
+    let block = assert_matches!(block.kind, Kind::Block(vals) => TokenSpan::without_docs(i.file.clone(), vals));
     let (_, vals) = all_consuming(parse_many0(map(
         tuple((parse_name, Weak(Kind::Assign), parse_expression)),
         |(k, _, v)| (k, v),
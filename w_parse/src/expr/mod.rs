@@ -1,25 +1,30 @@
 use crate::expr::call::{parse_call_wrapper, ExprCall};
+use crate::expr::cast::{parse_cast_wrapper, ExprCast};
 use crate::expr::field::{parse_field_wrapper, ExprField};
 use crate::expr::index::{parse_index_wrapper, ExprIndex};
 use crate::expr::many::{parse_array, parse_tuple, ExprArray, ExprTuple};
 use crate::expr::path::{parse_path, ExprPath};
+use crate::expr::pattern::{parse_match, ExprMatch};
+use crate::expr::range::ExprRange;
 use crate::expr::unary::{parse_unary, ExprUnary};
 use crate::{parse_name, ErrorChain, Ident, ParResult, TokenSpan};
 
 use nom::branch::alt;
 
-use crate::expr::block::{BlockKind, ExprBlock};
+use crate::expr::block::{parse_block_many, BlockKind, ExprBlock};
 use crate::expr::branch::{parse_branch, ExprBranch};
 use crate::expr::ctor::{parse_ctor, ExprCtor};
-use crate::expr::loops::{parse_while, ExprWhile};
+use crate::expr::jump::{parse_jump, ExprBreak, ExprContinue, ExprReturn, Label};
+use crate::expr::loops::{parse_for, parse_loop, parse_while, ExprFor, ExprLoop, ExprWhile};
 use crate::expr::ops::{parse_binary_ops, ExprBinary};
-use nom::combinator::{cond, map, map_opt, opt, verify};
+use nom::combinator::{map, opt, verify};
 use nom::error::{ErrorKind, ParseError};
 use nom::multi::many0;
 use nom::{Err, InputTake};
-use w_tokenize::{Number, Span, Token};
+use w_tokenize::{Float, Kind, Number, Span, Token};
 
-use crate::expr::assign::{parse_assignment, ExprAssignment};
+use crate::expr::assign::{parse_assignment, AssignOp, ExprAssignment};
+use crate::expr::defer::{reject_defer, ExprDefer};
 use crate::expr::define::{parse_define, ExprDefine};
 pub use many::parse_many0;
 
@@ -27,14 +32,19 @@ pub mod assign;
 pub mod block;
 pub mod branch;
 pub mod call;
+pub mod cast;
 pub mod ctor;
+pub mod defer;
 pub mod define;
 pub mod field;
 pub mod index;
+pub mod jump;
 pub mod loops;
 pub mod many;
 pub mod ops;
 pub mod path;
+pub mod pattern;
+pub mod range;
 pub mod unary;
 
 #[macro_export]
@@ -64,6 +74,12 @@ macro_rules! tag {
     }};
 }
 
+/// A child handed out by [`Expr::for_each_child_mut`]
+pub enum ChildMut<'a> {
+    Expr(&'a mut Expr),
+    Block(&'a mut ExprBlock),
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
     Tuple(ExprTuple),
@@ -74,18 +90,32 @@ pub enum Expr {
 
     Block(ExprBlock),
     Binary(ExprBinary),
+    Range(ExprRange),
 
     Branch(ExprBranch),
+    Match(ExprMatch),
     While(ExprWhile),
+    For(ExprFor),
+    Loop(ExprLoop),
+
+    Break(ExprBreak),
+    Continue(ExprContinue),
+    Return(ExprReturn),
+    Defer(ExprDefer),
 
     Define(ExprDefine),
     Assign(ExprAssignment),
 
     Number(Box<Number>),
+    Float(Box<Float>),
     String(Span, String),
+    ByteString(Span, Vec<u8>),
+    Char(Span, char),
+    Byte(Span, u8),
     Ident(Ident),
 
     Unary(ExprUnary),
+    Cast(ExprCast),
     Field(ExprField),
     Call(ExprCall),
     Index(ExprIndex),
@@ -96,9 +126,27 @@ pub fn parse_expression(i: TokenSpan) -> ParResult<Expr> {
 }
 
 pub fn parse_expr_pre_pass(i: TokenSpan, deep: bool) -> ParResult<Expr> {
+    // the assignee would parse as an expression of its own otherwise,
+    // `*a = b` has to be `(*a) = b` and not `*(a = b)`
+    if !deep && assigns_ahead(&i) {
+        match alt((
+            map(parse_assignment, Expr::Assign),
+            map(parse_define, Expr::Define),
+        ))(i.clone())
+        {
+            Err(Err::Error(_)) => (),
+            res => return res,
+        }
+    }
+
+    // prefix operators only reach the first operand, `*a * *b` is `(*a) * (*b)`
+    if !deep {
+        return parse_binary_ops(i);
+    }
+
     let (i, unaries) = many0(parse_unary)(i)?;
 
-    let (i, mut expr) = parse_expr_mid_pass(i, deep)?;
+    let (i, mut expr) = parse_expr_mid_pass(i)?;
 
     for op in unaries.into_iter().rev() {
         expr = Expr::Unary(ExprUnary {
@@ -106,11 +154,16 @@ pub fn parse_expr_pre_pass(i: TokenSpan, deep: bool) -> ParResult<Expr> {
             expr: Box::new(expr),
         });
     }
+
+    // `-a as T` casts `-a`, the operands of binary operators take their casts along
+    let (i, casts) = many0(parse_cast_wrapper)(i)?;
+    let expr = casts.into_iter().fold(expr, |expr, cast| cast(expr));
+
     Ok((i, expr))
 }
 
-fn parse_expr_mid_pass(i: TokenSpan, deep: bool) -> ParResult<Expr> {
-    let (mut i, mut expr) = parse_expr_post_pass(i, deep)?;
+fn parse_expr_mid_pass(i: TokenSpan) -> ParResult<Expr> {
+    let (mut i, mut expr) = parse_expr_post_pass(i)?;
 
     loop {
         let (ni, (nexpr, cont)) = parse_succeeding(i, expr)?;
@@ -141,23 +194,40 @@ fn parse_succeeding(i: TokenSpan, expr: Expr) -> ParResult<(Expr, bool)> {
     Ok((i, ret))
 }
 
-fn parse_expr_post_pass(i: TokenSpan, deep: bool) -> ParResult<Expr> {
+fn parse_expr_post_pass(i: TokenSpan) -> ParResult<Expr> {
     alt((
-        map_opt(cond(!deep, map(parse_binary_ops, Expr::Binary)), |x| x),
         map(parse_ctor, Expr::Ctor),
-        map(verify(parse_path, |pt| pt.path.len() >= 2), Expr::Path),
+        map(
+            verify(parse_path, |pt| pt.root.is_some() || pt.path.len() >= 2),
+            Expr::Path,
+        ),
         map(parse_name, Expr::Ident),
         map(parse_tuple, Expr::Tuple),
         map(parse_array, Expr::Array),
+        map(parse_block_many, Expr::Block),
         map(parse_branch, Expr::Branch),
+        map(parse_match, Expr::Match),
         map(parse_while, Expr::While),
-        map(parse_assignment, Expr::Assign),
-        map(parse_define, Expr::Define),
-        tag!(Kind::String(_), Token { kind: Kind::String(num), span } => Expr::String(span, num)),
+        map(parse_for, Expr::For),
+        map(parse_loop, Expr::Loop),
+        parse_jump,
+        reject_defer,
+        tag!(Kind::String(_), Token { kind: Kind::String(num), span, .. } => Expr::String(span, num)),
+        tag!(Kind::ByteString(_), Token { kind: Kind::ByteString(bytes), span, .. } => Expr::ByteString(span, bytes)),
+        tag!(Kind::Char(_), Token { kind: Kind::Char(c), span, .. } => Expr::Char(span, c)),
+        tag!(Kind::Byte(_), Token { kind: Kind::Byte(b), span, .. } => Expr::Byte(span, b)),
         tag!(Kind::Number(_), Token { kind: Kind::Number(num), .. } => Expr::Number(num)),
+        tag!(Kind::Float(_), Token { kind: Kind::Float(num), .. } => Expr::Float(num)),
     ))(i)
 }
 
+/// Whether an assignment operator follows before the end of the statement
+fn assigns_ahead(i: &TokenSpan) -> bool {
+    i.iter()
+        .take_while(|tk| !matches!(tk.kind, Kind::Semicolon))
+        .any(|tk| matches!(tk.kind, Kind::Define) || AssignOp::from_kind(&tk.kind).is_some())
+}
+
 pub fn tag<O>(
     parser: fn(&Token) -> bool,
     map: fn(Token) -> O,
@@ -187,19 +257,224 @@ impl Expr {
             | Expr::Path(_)
             | Expr::Ctor(_)
             | Expr::Number(_)
+            | Expr::Float(_)
             | Expr::String(_, _)
+            | Expr::ByteString(_, _)
+            | Expr::Char(_, _)
+            | Expr::Byte(_, _)
             | Expr::Ident(_)
             | Expr::Unary(_)
+            | Expr::Cast(_)
             | Expr::Field(_)
             | Expr::Call(_)
             | Expr::Index(_)
             | Expr::Binary(_)
+            | Expr::Range(_)
             | Expr::Assign(_)
-            | Expr::Define(_) => true,
-            Expr::Branch(_) => false,
-            Expr::Block(body) | Expr::While(ExprWhile { body, .. }) => {
+            | Expr::Define(_)
+            | Expr::Break(_)
+            | Expr::Continue(_)
+            | Expr::Return(_)
+            | Expr::Defer(_) => true,
+            Expr::Branch(_) | Expr::Match(_) => false,
+            Expr::Block(body)
+            | Expr::While(ExprWhile { body, .. })
+            | Expr::For(ExprFor { body, .. })
+            | Expr::Loop(ExprLoop { body, .. }) => {
                 matches!(body.kind, BlockKind::Inline(_))
             }
         }
     }
+
+    /// Whether the expression has the type `!`, control flow never continues after it
+    pub fn diverges(&self) -> bool {
+        match self {
+            Expr::Break(_) | Expr::Continue(_) | Expr::Return(_) => true,
+            Expr::Loop(lp) => !lp.body.breaks_from(lp.label.as_ref(), true),
+            Expr::Block(block) => block.diverges(),
+            // the deferred expression only runs once the block is left
+            Expr::Defer(_) => false,
+            Expr::Branch(branch) => {
+                branch.cond.diverges()
+                    || branch
+                        .body_else
+                        .as_ref()
+                        .is_some_and(|body_else| branch.body.diverges() && body_else.diverges())
+            }
+            Expr::Match(mt) => {
+                mt.value.diverges()
+                    || !mt.arms.is_empty() && mt.arms.iter().all(|arm| arm.body.diverges())
+            }
+            // the body of `while` and `for` might never run
+            Expr::While(ExprWhile { cond, .. }) => cond.diverges(),
+            Expr::For(ExprFor { iterable, .. }) => iterable.diverges(),
+            expr => {
+                let mut diverges = false;
+                expr.for_each_child(&mut |child| diverges |= child.diverges());
+                diverges
+            }
+        }
+    }
+
+    /// Calls `f` with every expression directly nested in this one, in evaluation order
+    pub fn for_each_child<'a>(&'a self, f: &mut impl FnMut(&'a Expr)) {
+        let block = |block: &'a ExprBlock, f: &mut dyn FnMut(&'a Expr)| block.exprs().for_each(f);
+
+        match self {
+            Expr::Tuple(ExprTuple { values, .. }) | Expr::Array(ExprArray { values, .. }) => {
+                values.iter().for_each(f)
+            }
+            Expr::Ctor(ctor) => ctor.vals.values.iter().for_each(|(_, val)| f(val)),
+            Expr::Block(body) | Expr::Loop(ExprLoop { body, .. }) => block(body, f),
+            Expr::Binary(bin) => {
+                f(&bin.left);
+                f(&bin.right);
+            }
+            Expr::Range(range) => {
+                range.start.iter().for_each(|expr| f(expr));
+                range.end.iter().for_each(|expr| f(expr));
+            }
+            Expr::Branch(branch) => {
+                f(&branch.cond);
+                block(&branch.body, f);
+                if let Some(body_else) = &branch.body_else {
+                    block(body_else, f);
+                }
+            }
+            Expr::While(ExprWhile { cond, body, .. })
+            | Expr::For(ExprFor {
+                iterable: cond,
+                body,
+                ..
+            }) => {
+                f(cond);
+                block(body, f);
+            }
+            Expr::Break(ExprBreak { value, .. }) | Expr::Return(ExprReturn { value, .. }) => {
+                value.iter().for_each(|expr| f(expr))
+            }
+            Expr::Match(mt) => {
+                f(&mt.value);
+                mt.arms.iter().for_each(|arm| block(&arm.body, f));
+            }
+            Expr::Define(define) => f(&define.value),
+            Expr::Assign(ExprAssignment {
+                assignee, value, ..
+            }) => {
+                f(assignee);
+                f(value);
+            }
+            Expr::Defer(defer) => f(&defer.expr),
+            Expr::Unary(unary) => f(&unary.expr),
+            Expr::Cast(cast) => f(&cast.base),
+            Expr::Field(field) => f(&field.base),
+            Expr::Call(call) => {
+                f(&call.base);
+                call.args.values.iter().for_each(f);
+            }
+            Expr::Index(index) => {
+                f(&index.base);
+                index.args.values.iter().for_each(f);
+            }
+            Expr::Path(_)
+            | Expr::Number(_)
+            | Expr::Float(_)
+            | Expr::String(_, _)
+            | Expr::ByteString(_, _)
+            | Expr::Char(_, _)
+            | Expr::Byte(_, _)
+            | Expr::Ident(_)
+            | Expr::Continue(_) => (),
+        }
+    }
+
+    /// Like [`Self::for_each_child`], but nested blocks are handed out as a whole
+    pub fn for_each_child_mut(&mut self, f: &mut impl FnMut(ChildMut)) {
+        let mut expr = |expr: &mut Expr| f(ChildMut::Expr(expr));
+
+        match self {
+            Expr::Tuple(ExprTuple { values, .. }) | Expr::Array(ExprArray { values, .. }) => {
+                values.iter_mut().for_each(expr)
+            }
+            Expr::Ctor(ctor) => ctor.vals.values.iter_mut().for_each(|(_, val)| expr(val)),
+            Expr::Block(body) | Expr::Loop(ExprLoop { body, .. }) => f(ChildMut::Block(body)),
+            Expr::Binary(bin) => {
+                expr(&mut bin.left);
+                expr(&mut bin.right);
+            }
+            Expr::Range(range) => {
+                range.start.iter_mut().for_each(|val| expr(val));
+                range.end.iter_mut().for_each(|val| expr(val));
+            }
+            Expr::Branch(branch) => {
+                f(ChildMut::Expr(&mut branch.cond));
+                f(ChildMut::Block(&mut branch.body));
+                if let Some(body_else) = &mut branch.body_else {
+                    f(ChildMut::Block(body_else));
+                }
+            }
+            Expr::While(ExprWhile { cond, body, .. })
+            | Expr::For(ExprFor {
+                iterable: cond,
+                body,
+                ..
+            }) => {
+                f(ChildMut::Expr(cond));
+                f(ChildMut::Block(body));
+            }
+            Expr::Break(ExprBreak { value, .. }) | Expr::Return(ExprReturn { value, .. }) => {
+                value.iter_mut().for_each(|val| expr(val))
+            }
+            Expr::Match(mt) => {
+                f(ChildMut::Expr(&mut mt.value));
+                mt.arms
+                    .iter_mut()
+                    .for_each(|arm| f(ChildMut::Block(&mut arm.body)));
+            }
+            Expr::Define(define) => expr(&mut define.value),
+            Expr::Assign(ExprAssignment {
+                assignee, value, ..
+            }) => {
+                expr(assignee);
+                expr(value);
+            }
+            Expr::Defer(defer) => expr(&mut defer.expr),
+            Expr::Unary(unary) => expr(&mut unary.expr),
+            Expr::Cast(cast) => expr(&mut cast.base),
+            Expr::Field(field) => expr(&mut field.base),
+            Expr::Call(call) => {
+                expr(&mut call.base);
+                call.args.values.iter_mut().for_each(expr);
+            }
+            Expr::Index(index) => {
+                expr(&mut index.base);
+                index.args.values.iter_mut().for_each(expr);
+            }
+            Expr::Path(_)
+            | Expr::Number(_)
+            | Expr::Float(_)
+            | Expr::String(_, _)
+            | Expr::ByteString(_, _)
+            | Expr::Char(_, _)
+            | Expr::Byte(_, _)
+            | Expr::Ident(_)
+            | Expr::Continue(_) => (),
+        }
+    }
+
+    fn breaks_from(&self, label: Option<&Label>, innermost: bool) -> bool {
+        let innermost = match self {
+            Expr::Break(ExprBreak {
+                label: Some(target),
+                ..
+            }) if label.is_some_and(|label| label.name == target.name) => return true,
+            Expr::Break(ExprBreak { label: None, .. }) if innermost => return true,
+            Expr::While(_) | Expr::For(_) | Expr::Loop(_) => false,
+            _ => innermost,
+        };
+
+        let mut breaks = false;
+        self.for_each_child(&mut |child| breaks |= child.breaks_from(label, innermost));
+        breaks
+    }
 }
@@ -1,8 +1,11 @@
+use crate::expr::cast::parse_cast_wrapper;
+use crate::expr::range::ExprRange;
 use crate::expr::{parse_expr_pre_pass, Expr};
-use crate::{ErrorChain, ParResult, TokenSpan};
-use assert_matches::assert_matches;
-use nom::combinator::opt;
+use crate::{tag, Error, ErrorChain, ParResult, TokenSpan, Weak};
+use nom::branch::alt;
+use nom::combinator::{map, opt, verify};
 use nom::error::{ErrorKind, ParseError};
+use nom::multi::many0;
 use nom::Err;
 use nom::InputTake;
 use w_tokenize::{Kind, Span};
@@ -43,66 +46,133 @@ pub enum BiOp {
     // Logical operands
     AndL,
     OrL,
-}
 
-pub fn parse_binary_ops(mut i: TokenSpan) -> ParResult<ExprBinary> {
-    let mut exprs = vec![];
-    let mut ops = vec![];
+    // Ranges, see `ExprRange`
+    Range,
+    RangeInclusive,
+}
 
-    loop {
-        let (ni, expr) = parse_expr_pre_pass(i, true)?;
-        let (ni, op) = opt(parse_bi_op)(ni)?;
-        i = ni;
+/// Parses a chain of binary operators by precedence climbing, see [`BiOp::precedence`].
+///
+/// A lone operand is returned as is, so it is only parsed once.
+pub fn parse_binary_ops(i: TokenSpan) -> ParResult<Expr> {
+    // `..end` has no start
+    let (i, expr) = match opt(verify(parse_bi_op, |(_, op)| op.is_range()))(i.clone())? {
+        (i, Some((op_span, op))) => parse_range(i, None, op_span, op)?,
+        (i, None) => {
+            let (i, left) = parse_expr_pre_pass(i, true)?;
+            parse_climb(i, left, 0)?
+        }
+    };
 
-        exprs.push(expr);
+    check_unchained(i, expr)
+}
 
-        if let Some(op) = op {
-            ops.push(op);
-        } else {
+/// Folds every operator binding at least as tight as `min` into `left`
+fn parse_climb(mut i: TokenSpan, mut left: Expr, min: u32) -> ParResult<Expr> {
+    loop {
+        let (ni, (op_span, op, right)) = match parse_infix(i.clone()) {
+            Ok(res) => res,
+            Err(Err::Error(_)) => break,
+            Err(err) => return Err(err),
+        };
+        if op.precedence() < min {
             break;
         }
+
+        if op.is_range() {
+            return parse_range(ni, Some(left), op_span, op);
+        }
+
+        let (ni, right) = match right {
+            Some(right) => (ni, right),
+            None => parse_expr_pre_pass(ni, true)?,
+        };
+        // only tighter operators may take the right operand, which keeps chains left associative
+        let (ni, right) = parse_climb(ni, right, op.precedence() + 1)?;
+
+        i = ni;
+        left = Expr::Binary(ExprBinary {
+            op,
+            op_span,
+            left: Box::new(left),
+            right: Box::new(right),
+        });
     }
 
-    debug_assert_eq!(exprs.len() - 1, ops.len());
+    Ok((i, left))
+}
 
-    if exprs.len() == 1 {
-        return Err(Err::Error(ErrorChain::from_error_kind(i, ErrorKind::Count)));
+/// An operator in infix position, `*` always multiplies here while it dereferences in front of an operand.
+///
+/// `a -1` is tokenized as `a` followed by the number `-1`,
+/// the sign is taken as the operator and the number as the start of the right operand.
+fn parse_infix(i: TokenSpan) -> ParResult<(Span, BiOp, Option<Expr>)> {
+    if let Ok((i, (op_span, op))) = parse_bi_op(i.clone()) {
+        return Ok((i, (op_span, op, None)));
     }
 
-    eval_order(&mut exprs, &mut ops);
+    let (i, (sign, expr)) = alt((
+        tag!(Kind::Number(_), Token { kind: Kind::Number(mut num), .. } => {
+            (num.sign.take(), Expr::Number(num))
+        }),
+        tag!(Kind::Float(_), Token { kind: Kind::Float(mut num), .. } => {
+            (num.sign.take(), Expr::Float(num))
+        }),
+    ))(i)?;
+    let Some(sign) = sign else {
+        return Err(Err::Error(ErrorChain::from_error_kind(i, ErrorKind::Tag)));
+    };
+    let op = if *sign == "-" { BiOp::Sub } else { BiOp::Add };
+
+    let (i, casts) = many0(parse_cast_wrapper)(i)?;
+    let expr = casts.into_iter().fold(expr, |expr, cast| cast(expr));
+
+    Ok((i, (sign, op, Some(expr))))
+}
+
+/// The end of a range binds everything but another range
+fn parse_range(i: TokenSpan, start: Option<Expr>, op_span: Span, op: BiOp) -> ParResult<Expr> {
+    let (i, end) = alt((
+        map(Weak(Kind::Fill), |_| None),
+        opt(|i| {
+            let (i, end) = parse_expr_pre_pass(i, true)?;
+            parse_climb(i, end, op.precedence() + 1)
+        }),
+    ))(i)?;
+
+    if end.is_none() && matches!(op, BiOp::RangeInclusive) {
+        return Err(Err::Failure(ErrorChain::from(Error::new(
+            i,
+            "Inclusive ranges require an end",
+        ))));
+    }
 
     Ok((
         i,
-        assert_matches!(exprs.pop().unwrap(), Expr::Binary(bin) => bin),
+        Expr::Range(ExprRange {
+            op_span,
+            inclusive: matches!(op, BiOp::RangeInclusive),
+            start: start.map(Box::new),
+            end: end.map(Box::new),
+        }),
     ))
 }
 
-fn eval_order(exprs: &mut Vec<Expr>, ops: &mut Vec<(Span, BiOp)>) {
-    while !ops.is_empty() {
-        let lowest = ops.iter().map(|op| op.1.priority()).min().unwrap();
-
-        while let Some(idx) = ops
-            .iter()
-            .enumerate()
-            .find_map(|(id, op)| (op.1.priority() == lowest).then_some(id))
-        {
-            let (op_span, op) = ops.remove(idx);
-            let left = exprs.remove(idx);
-            let right = exprs.remove(idx);
-
-            exprs.insert(
-                idx,
-                Expr::Binary(ExprBinary {
-                    op,
-                    op_span,
-                    left: Box::new(left),
-                    right: Box::new(right),
-                }),
-            );
+/// Ranges are non associative, `a..b..c` needs parentheses
+fn check_unchained(i: TokenSpan, expr: Expr) -> ParResult<Expr> {
+    if matches!(expr, Expr::Range(_)) {
+        if let Ok((ni, (_, op))) = parse_bi_op(i.clone()) {
+            if op.assoc() == Assoc::None {
+                return Err(Err::Failure(ErrorChain::from(Error::new(
+                    ni,
+                    "Ranges can not be chained, use parentheses",
+                ))));
+            }
         }
     }
 
-    debug_assert_eq!(exprs.len(), 1);
+    Ok((i, expr))
 }
 
 pub fn parse_bi_op(i: TokenSpan) -> ParResult<(Span, BiOp)> {
@@ -130,6 +200,8 @@ pub fn parse_bi_op(i: TokenSpan) -> ParResult<(Span, BiOp)> {
         Kind::Ge => BiOp::Ge,
         Kind::AndL => BiOp::AndL,
         Kind::OrL => BiOp::OrL,
+        Kind::Range => BiOp::Range,
+        Kind::RangeInclusive => BiOp::RangeInclusive,
         _ => {
             return Err(Err::Error(ErrorChain::from_error_kind(
                 took.clone(),
@@ -141,30 +213,54 @@ pub fn parse_bi_op(i: TokenSpan) -> ParResult<(Span, BiOp)> {
     Ok((i, (took[0].span.clone(), kind)))
 }
 
+/// How a chain of operators with the same precedence groups
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Assoc {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a..b..c` is rejected
+    None,
+}
+
 impl BiOp {
-    pub fn priority(&self) -> u32 {
+    /// Higher values bind tighter, unary operators and casts (`a as T`) bind tighter than any of them.
+    ///
+    /// | precedence | operators                   | associativity |
+    /// |------------|-----------------------------|---------------|
+    /// | 9          | `*` `/` `%`                 | left          |
+    /// | 8          | `+` `-`                     | left          |
+    /// | 7          | `<<` `>>`                   | left          |
+    /// | 6          | `&`                         | left          |
+    /// | 5          | `^`                         | left          |
+    /// | 4          | `\|`                        | left          |
+    /// | 3          | `==` `!=` `<` `>` `<=` `>=` | left          |
+    /// | 2          | `&&`                        | left          |
+    /// | 1          | `\|\|`                      | left          |
+    /// | 0          | `..` `..=`                  | none          |
+    pub fn precedence(&self) -> u32 {
         match self {
-            BiOp::Add => 1,
-            BiOp::Sub => 1,
-            BiOp::Mul => 0,
-            BiOp::Div => 0,
-            BiOp::Mod => 0,
-
-            BiOp::And => 2,
-            BiOp::Or => 2,
-            BiOp::Xor => 2,
-            BiOp::Shl => 3,
-            BiOp::Shr => 3,
-
-            BiOp::Eq => 4,
-            BiOp::Neq => 4,
-            BiOp::Lt => 4,
-            BiOp::Gt => 4,
-            BiOp::Le => 4,
-            BiOp::Ge => 4,
-
-            BiOp::AndL => 6,
-            BiOp::OrL => 5,
+            BiOp::Mul | BiOp::Div | BiOp::Mod => 9,
+            BiOp::Add | BiOp::Sub => 8,
+            BiOp::Shl | BiOp::Shr => 7,
+            BiOp::And => 6,
+            BiOp::Xor => 5,
+            BiOp::Or => 4,
+            BiOp::Eq | BiOp::Neq | BiOp::Lt | BiOp::Gt | BiOp::Le | BiOp::Ge => 3,
+            BiOp::AndL => 2,
+            BiOp::OrL => 1,
+            BiOp::Range | BiOp::RangeInclusive => 0,
         }
     }
+
+    pub fn assoc(&self) -> Assoc {
+        if self.is_range() {
+            Assoc::None
+        } else {
+            Assoc::Left
+        }
+    }
+
+    pub fn is_range(&self) -> bool {
+        matches!(self, BiOp::Range | BiOp::RangeInclusive)
+    }
 }
@@ -1,46 +1,94 @@
-use crate::{parse_name, tag, Ident, ParResult, TokenSpan, Weak};
-use nom::combinator::{map, opt};
+use crate::{parse_keyword, parse_name, tag, Ident, ParResult, TokenSpan, Weak};
+use nom::branch::alt;
+use nom::combinator::map;
 use nom::multi::separated_list1;
-use nom::sequence::pair;
+use nom::sequence::{pair, separated_pair};
 use std::hash::{Hash, Hasher};
 use w_tokenize::{Kind, Span};
 
 #[derive(Debug, Clone)]
 pub struct ExprPath {
-    pub root: Option<Span>,
+    pub root: Option<PathRoot>,
+    /// Only empty for a bare `self` or `super` an import is taken from
     pub path: Vec<Ident>,
 }
 
+#[derive(Debug, Clone)]
+pub enum PathRoot {
+    /// `:a`, starts at the root of the module tree
+    Absolute(Span),
+    /// `self:a`, starts at the current module
+    Current(Span),
+    /// `super:a`, goes up one module per `super`
+    Super(Vec<Span>),
+}
+
 pub fn parse_path(i: TokenSpan) -> ParResult<ExprPath> {
-    map(
-        pair(
-            opt(tag!(Kind::Colon)),
-            separated_list1(Weak(Kind::Colon), parse_name),
+    alt((
+        map(
+            pair(map(tag!(Kind::Colon), PathRoot::Absolute), parse_segments),
+            |(root, path)| ExprPath {
+                root: Some(root),
+                path,
+            },
         ),
-        |(root, path)| ExprPath { root, path },
-    )(i)
+        map(
+            separated_pair(parse_relative_root, Weak(Kind::Colon), parse_segments),
+            |(root, path)| ExprPath {
+                root: Some(root),
+                path,
+            },
+        ),
+        map(parse_segments, |path| ExprPath { root: None, path }),
+    ))(i)
+}
+
+/// `self` or `super:super`, `self` is only a root if a path follows it
+pub fn parse_relative_root(i: TokenSpan) -> ParResult<PathRoot> {
+    alt((
+        map(parse_keyword("self"), PathRoot::Current),
+        map(
+            separated_list1(Weak(Kind::Colon), parse_keyword("super")),
+            PathRoot::Super,
+        ),
+    ))(i)
+}
+
+fn parse_segments(i: TokenSpan) -> ParResult<Vec<Ident>> {
+    separated_list1(Weak(Kind::Colon), parse_name)(i)
+}
+
+impl PathRoot {
+    /// How many modules the root goes up from the current one, `None` for absolute paths
+    pub fn depth(&self) -> Option<usize> {
+        match self {
+            PathRoot::Absolute(_) => None,
+            PathRoot::Current(_) => Some(0),
+            PathRoot::Super(supers) => Some(supers.len()),
+        }
+    }
+
+    pub fn span(&self) -> &Span {
+        match self {
+            PathRoot::Absolute(span) | PathRoot::Current(span) => span,
+            PathRoot::Super(supers) => supers.last().unwrap(),
+        }
+    }
 }
 
 impl Hash for ExprPath {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.root.is_some().hash(state);
+        self.root.as_ref().map(PathRoot::depth).hash(state);
         for ident in &self.path {
-            (*ident.0).hash(state);
+            ident.hash(state);
         }
     }
 }
 
 impl PartialEq for ExprPath {
     fn eq(&self, other: &Self) -> bool {
-        if self.path.len() != other.path.len() {
-            return false;
-        }
-        self.root == other.root
-            && self
-                .path
-                .iter()
-                .zip(other.path.iter())
-                .all(|(a, b)| *a.0 == *b.0)
+        self.root.as_ref().map(PathRoot::depth) == other.root.as_ref().map(PathRoot::depth)
+            && self.path == other.path
     }
 }
 
@@ -0,0 +1,222 @@
+use crate::expr::block::{parse_block, ExprBlock};
+use crate::expr::path::{parse_path, ExprPath};
+use crate::expr::{parse_expression, parse_many0, Expr};
+use crate::{parse_keyword, parse_name, tag, Ident, ParResult, TokenSpan, Weak};
+use nom::branch::alt;
+use nom::combinator::{all_consuming, map, opt, verify};
+use nom::sequence::{pair, preceded};
+use nom::Parser;
+use std::rc::Rc;
+use w_tokenize::{Kind, Span};
+
+/// The left side of `:=` and the arms of `match`
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    /// `_`
+    Wildcard(Span),
+    /// `name` or `mut name`
+    Binding(PatBinding),
+    /// Numbers, strings and characters, only equal values match
+    Literal(Box<Expr>),
+    /// `(a, _)`
+    Tuple(PatTuple),
+    /// `option:some(x)` or `option:none`
+    Variant(PatVariant),
+    /// `vec3 { x = 0, y }`, `y` is short for `y = y`
+    Struct(PatStruct),
+}
+
+#[derive(Debug, Clone)]
+pub struct PatBinding {
+    pub mutability: Option<Span>,
+    pub name: Ident,
+}
+
+#[derive(Debug, Clone)]
+pub struct PatTuple {
+    pub span: Span,
+    pub fields: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PatVariant {
+    /// The enum followed by the variant
+    pub path: ExprPath,
+    pub fields: Option<PatTuple>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PatStruct {
+    pub path: ExprPath,
+    pub span: Span,
+    pub fields: Vec<(Ident, Pattern)>,
+}
+
+/// `match value { option:some(x) -> x, option:none -> 0 }`
+#[derive(Debug, Clone)]
+pub struct ExprMatch {
+    pub span_match: Span,
+    pub value: Box<Expr>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: ExprBlock,
+}
+
+pub fn parse_pattern(i: TokenSpan) -> ParResult<Pattern> {
+    alt((
+        map(parse_pat_struct, Pattern::Struct),
+        parse_pattern_unbraced,
+    ))(i)
+}
+
+/// Every pattern but a struct pattern, braces after the path are left to the caller
+fn parse_pattern_unbraced(i: TokenSpan) -> ParResult<Pattern> {
+    alt((
+        map(Weak(Kind::Fill), |tk| Pattern::Wildcard(tk.span)),
+        map(parse_pat_literal, |lit| Pattern::Literal(Box::new(lit))),
+        map(
+            pair(parse_path, map(parse_pat_tuple, Some)),
+            |(path, fields)| Pattern::Variant(PatVariant { path, fields }),
+        ),
+        map(parse_pat_tuple, Pattern::Tuple),
+        map(
+            pair(map(parse_keyword("mut"), Some), parse_name),
+            |(mutability, name)| Pattern::Binding(PatBinding { mutability, name }),
+        ),
+        // a single name binds, variants without fields need the enum in front
+        map(verify(parse_path, |path| path.path.len() >= 2), |path| {
+            Pattern::Variant(PatVariant { path, fields: None })
+        }),
+        map(parse_name, |name| {
+            Pattern::Binding(PatBinding {
+                mutability: None,
+                name,
+            })
+        }),
+    ))(i)
+}
+
+fn parse_pat_literal(i: TokenSpan) -> ParResult<Expr> {
+    alt((
+        tag!(Kind::String(_), Token { kind: Kind::String(str), span, .. } => Expr::String(span, str)),
+        tag!(Kind::ByteString(_), Token { kind: Kind::ByteString(bytes), span, .. } => Expr::ByteString(span, bytes)),
+        tag!(Kind::Char(_), Token { kind: Kind::Char(c), span, .. } => Expr::Char(span, c)),
+        tag!(Kind::Byte(_), Token { kind: Kind::Byte(b), span, .. } => Expr::Byte(span, b)),
+        tag!(Kind::Number(_), Token { kind: Kind::Number(num), .. } => Expr::Number(num)),
+        tag!(Kind::Float(_), Token { kind: Kind::Float(num), .. } => Expr::Float(num)),
+    ))(i)
+}
+
+fn parse_pat_tuple(i: TokenSpan) -> ParResult<PatTuple> {
+    let (i, tuple) = Weak(Kind::Tuple(Rc::from([]))).parse(i)?;
+    let Kind::Tuple(vals) = tuple.kind else {
+        unreachable!()
+    };
+    let (_, fields) =
+        all_consuming(parse_many0(parse_pattern))(TokenSpan::without_docs(i.file.clone(), vals))?;
+
+    Ok((
+        i,
+        PatTuple {
+            span: tuple.span,
+            fields,
+        },
+    ))
+}
+
+fn parse_pat_struct(i: TokenSpan) -> ParResult<PatStruct> {
+    let (i, path) = parse_path(i)?;
+    let (i, block) = Weak(Kind::Block(Rc::from([]))).parse(i)?;
+    let Kind::Block(vals) = block.kind else {
+        unreachable!()
+    };
+    let (_, fields) =
+        all_consuming(parse_many0(parse_pat_field))(TokenSpan::without_docs(i.file.clone(), vals))?;
+
+    Ok((
+        i,
+        PatStruct {
+            path,
+            span: block.span,
+            fields,
+        },
+    ))
+}
+
+fn parse_pat_field(i: TokenSpan) -> ParResult<(Ident, Pattern)> {
+    let (i, name) = parse_name(i)?;
+    let (i, pattern) = opt(preceded(Weak(Kind::Assign), parse_pattern))(i)?;
+
+    let pattern = pattern.unwrap_or_else(|| {
+        Pattern::Binding(PatBinding {
+            mutability: None,
+            name: name.clone(),
+        })
+    });
+    Ok((i, (name, pattern)))
+}
+
+pub fn parse_match(i: TokenSpan) -> ParResult<ExprMatch> {
+    let (i, span_match) = parse_keyword("match")(i)?;
+    let (i, value) = map(parse_expression, Box::new)(i)?;
+
+    let (i, block) = tag!(Kind::Block(_), Token { kind: Kind::Block(vals), .. } => vals)(i)?;
+    let (_, arms) = all_consuming(parse_many0(parse_match_arm))(TokenSpan::without_docs(
+        i.file.clone(),
+        block,
+    ))?;
+
+    Ok((
+        i,
+        ExprMatch {
+            span_match,
+            value,
+            arms,
+        },
+    ))
+}
+
+/// `option:none { d }` would be a struct pattern without a body,
+/// the braces are only taken as fields if a body follows them
+fn parse_match_arm(i: TokenSpan) -> ParResult<MatchArm> {
+    let (i, (pattern, body)) = alt((
+        pair(parse_pattern, parse_block),
+        pair(parse_pattern_unbraced, parse_block),
+    ))(i)?;
+
+    Ok((i, MatchArm { pattern, body }))
+}
+
+impl Pattern {
+    /// Whether the pattern matches every value of its type
+    pub fn is_irrefutable(&self) -> bool {
+        match self {
+            Pattern::Wildcard(_) | Pattern::Binding(_) => true,
+            Pattern::Literal(_) | Pattern::Variant(_) => false,
+            Pattern::Tuple(tuple) => tuple.fields.iter().all(Pattern::is_irrefutable),
+            Pattern::Struct(st) => st.fields.iter().all(|(_, pat)| pat.is_irrefutable()),
+        }
+    }
+
+    /// Calls `f` with every name the pattern binds
+    pub fn for_each_binding<'a>(&'a self, f: &mut impl FnMut(&'a PatBinding)) {
+        match self {
+            Pattern::Wildcard(_) | Pattern::Literal(_) => (),
+            Pattern::Binding(binding) => f(binding),
+            Pattern::Tuple(tuple) => tuple.fields.iter().for_each(|pat| pat.for_each_binding(f)),
+            Pattern::Variant(variant) => variant
+                .fields
+                .iter()
+                .flat_map(|tuple| &tuple.fields)
+                .for_each(|pat| pat.for_each_binding(f)),
+            Pattern::Struct(st) => st
+                .fields
+                .iter()
+                .for_each(|(_, pat)| pat.for_each_binding(f)),
+        }
+    }
+}
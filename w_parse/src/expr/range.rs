@@ -0,0 +1,14 @@
+use crate::expr::Expr;
+use w_tokenize::Span;
+
+/// `start..end`, `start..=end`, with either bound being optional for exclusive ranges.
+///
+/// `start.._` is an explicitly open ended range.
+#[derive(Debug, Clone)]
+pub struct ExprRange {
+    pub op_span: Span,
+    pub inclusive: bool,
+
+    pub start: Option<Box<Expr>>,
+    pub end: Option<Box<Expr>>,
+}
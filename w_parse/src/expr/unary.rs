@@ -1,7 +1,8 @@
 use crate::expr::Expr;
-use crate::{ParResult, TokenSpan, Weak};
+use crate::{parse_keyword, ParResult, TokenSpan, Weak};
 use nom::branch::alt;
 use nom::combinator::map;
+use nom::sequence::pair;
 use w_tokenize::{Kind, Span};
 
 #[derive(Debug, Clone)]
@@ -13,18 +14,43 @@ pub struct ExprUnary {
 #[derive(Debug, Clone)]
 pub enum UnOp {
     Ref(Span),
+    /// `&mut`, the spans of `&` and `mut`
+    RefMut(Span, Span),
     Deref(Span),
     Not(Span),
+    /// `-a`, a sign directly in front of a number is part of the number instead
+    Neg(Span),
+    /// `~a`, flips every bit
+    BitNot(Span),
 }
 
 pub fn parse_unary(i: TokenSpan) -> ParResult<UnOp> {
+    alt((parse_ref_mut, parse_simple_unary))(i)
+}
+
+fn parse_ref_mut(i: TokenSpan) -> ParResult<UnOp> {
+    map(
+        pair(Weak(Kind::And), parse_keyword("mut")),
+        |(and, mutability)| UnOp::RefMut(and.span, mutability),
+    )(i)
+}
+
+fn parse_simple_unary(i: TokenSpan) -> ParResult<UnOp> {
     map(
-        alt((Weak(Kind::And), Weak(Kind::Mul), Weak(Kind::Not))),
+        alt((
+            Weak(Kind::And),
+            Weak(Kind::Mul),
+            Weak(Kind::Not),
+            Weak(Kind::Sub),
+            Weak(Kind::Tilde),
+        )),
         |tk| {
             (match tk.kind {
                 Kind::And => UnOp::Ref,
                 Kind::Mul => UnOp::Deref,
                 Kind::Not => UnOp::Not,
+                Kind::Sub => UnOp::Neg,
+                Kind::Tilde => UnOp::BitNot,
                 _ => unreachable!(),
             })(tk.span)
         },
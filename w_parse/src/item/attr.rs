@@ -0,0 +1,111 @@
+use crate::expr::path::{parse_path, ExprPath};
+use crate::expr::{parse_many0, tag};
+use crate::{tag, Ident, ParResult, TokenSpan, Weak};
+use nom::branch::alt;
+use nom::combinator::{all_consuming, map, opt};
+use nom::multi::many0;
+use nom::{Offset, Parser, Slice};
+use std::rc::Rc;
+use w_tokenize::{Kind, Span, Token};
+
+/// `@name`, `@name(args)`, or one of many in `@(a, b(c))`
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub span: Span,
+    pub path: ExprPath,
+    pub args: Option<AttrArgs>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttrArgs {
+    pub span: Span,
+    pub args: Vec<AttrArg>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AttrArg {
+    Nested(Attribute),
+    /// A string, number or character literal
+    Literal(Token),
+}
+
+impl Attribute {
+    /// The last segment of the path
+    pub fn name(&self) -> &Ident {
+        self.path.path.last().unwrap()
+    }
+}
+
+/// Any number of attributes, possibly none
+pub fn parse_attributes(i: TokenSpan) -> ParResult<Vec<Attribute>> {
+    map(many0(parse_attribute_group), |groups| {
+        groups.into_iter().flatten().collect()
+    })(i)
+}
+
+fn parse_attribute_group(i: TokenSpan) -> ParResult<Vec<Attribute>> {
+    let (i, _) = Weak(Kind::At).parse(i)?;
+
+    alt((
+        parse_attribute_list,
+        map(parse_attribute, |attr| vec![attr]),
+    ))(i)
+}
+
+fn parse_attribute_list(i: TokenSpan) -> ParResult<Vec<Attribute>> {
+    let (i, list) = tag!(Kind::Tuple(_), Token { kind: Kind::Tuple(vals), .. } => vals)(i)?;
+    let list = TokenSpan::without_docs(i.file.clone(), list);
+    let (_, attrs) = all_consuming(parse_many0(parse_attribute))(list)?;
+
+    Ok((i, attrs))
+}
+
+fn parse_attribute(oi: TokenSpan) -> ParResult<Attribute> {
+    let (i, path) = parse_path(oi.clone())?;
+    let (i, args) = opt(parse_attr_args)(i)?;
+
+    let offset = oi.offset(&i);
+    let span = oi.slice(..offset);
+
+    Ok((
+        i,
+        Attribute {
+            span: (&span).into(),
+            path,
+            args,
+        },
+    ))
+}
+
+fn parse_attr_args(i: TokenSpan) -> ParResult<AttrArgs> {
+    let (i, tuple) = Weak(Kind::Tuple(Rc::from([]))).parse(i)?;
+    let span = tuple.span;
+    let args = match tuple.kind {
+        Kind::Tuple(vals) => TokenSpan::without_docs(i.file.clone(), vals),
+        _ => unreachable!(),
+    };
+
+    let (_, args) = all_consuming(parse_many0(alt((
+        map(parse_attribute, AttrArg::Nested),
+        map(parse_literal, AttrArg::Literal),
+    ))))(args)?;
+
+    Ok((i, AttrArgs { span, args }))
+}
+
+fn parse_literal(i: TokenSpan) -> ParResult<Token> {
+    tag(
+        |tk| {
+            matches!(
+                tk.kind,
+                Kind::String(_)
+                    | Kind::ByteString(_)
+                    | Kind::Char(_)
+                    | Kind::Byte(_)
+                    | Kind::Number(_)
+                    | Kind::Float(_)
+            )
+        },
+        |tk| tk,
+    )(i)
+}
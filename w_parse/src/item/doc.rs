@@ -0,0 +1,45 @@
+use crate::{tag, ParResult, TokenSpan};
+use nom::combinator::map;
+use nom::multi::many0;
+use w_tokenize::Span;
+
+/// Consecutive doc comment lines, in source order
+#[derive(Debug, Clone, Default)]
+pub struct Docs {
+    pub lines: Vec<(Span, String)>,
+}
+
+impl Docs {
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The documentation text with the marker and a single leading space removed per line
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|(_, line)| line.strip_prefix(' ').unwrap_or(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Parses `///` comments documenting the following item
+pub fn parse_docs(i: TokenSpan) -> ParResult<Docs> {
+    map(
+        many0(
+            tag!(Kind::DocOuter(_), Token { kind: Kind::DocOuter(text), span, .. } => (span, text)),
+        ),
+        |lines| Docs { lines },
+    )(i)
+}
+
+/// Parses `//!` comments documenting the enclosing module
+pub fn parse_module_docs(i: TokenSpan) -> ParResult<Docs> {
+    map(
+        many0(
+            tag!(Kind::DocInner(_), Token { kind: Kind::DocInner(text), span, .. } => (span, text)),
+        ),
+        |lines| Docs { lines },
+    )(i)
+}
@@ -0,0 +1,42 @@
+use crate::expr::parse_many0;
+use crate::types::tuple::parse_ty_tuple;
+use crate::{parse_name, tag, Ident, ItemTy, ParResult, TokenSpan};
+use nom::combinator::{all_consuming, opt};
+use w_tokenize::Span;
+
+/// `name(A, B(ord)) :: ...`
+#[derive(Debug, Clone)]
+pub struct Generics {
+    pub span: Span,
+    pub params: Vec<GenericParam>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GenericParam {
+    pub name: Ident,
+    /// Traits the parameter has to implement, `T(ord, disposable)`
+    pub bounds: Vec<ItemTy>,
+}
+
+pub fn parse_generics(i: TokenSpan) -> ParResult<Generics> {
+    let (i, (span, params)) =
+        tag!(Kind::Tuple(_), Token { kind: Kind::Tuple(vals), span, .. } => (span, vals))(i)?;
+    let params = TokenSpan::without_docs(i.file.clone(), params);
+
+    let (_, params) = all_consuming(parse_many0(parse_param))(params)?;
+
+    Ok((i, Generics { span, params }))
+}
+
+fn parse_param(i: TokenSpan) -> ParResult<GenericParam> {
+    let (i, name) = parse_name(i)?;
+    let (i, bounds) = opt(parse_ty_tuple)(i)?;
+
+    Ok((
+        i,
+        GenericParam {
+            name,
+            bounds: bounds.map_or_else(Vec::new, |tp| tp.types),
+        },
+    ))
+}
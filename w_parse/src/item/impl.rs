@@ -0,0 +1,74 @@
+use crate::error::{Error, ErrorChain};
+use crate::expr::path::{parse_path, ExprPath};
+use crate::item::func::{parse_item_func, ItemFunc};
+use crate::item::generics::{parse_generics, Generics};
+use crate::types::tuple::{parse_ty_tuple, TyTuple};
+use crate::{parse_keyword, parse_name, tag, Ident, ItemTy, ParResult, TokenSpan, Weak};
+use nom::combinator::{all_consuming, opt};
+use nom::multi::many0;
+use nom::{Err, Parser};
+use w_tokenize::{Kind, Span};
+
+/// `impl(T) :: disposable (rc(T)) { dispose func(self *rc(T)) { ... } }`
+///
+/// Generic traits take their parameters in front of the target, `impl :: add_t(a, b, c) (a) { ... }`
+#[derive(Debug, Clone)]
+pub struct ItemImpl {
+    pub span_impl: Span,
+    pub generics: Option<Generics>,
+    pub tr: ExprPath,
+    pub tr_args: Option<TyTuple>,
+    pub target: ItemTy,
+    pub methods: Vec<ImplMethod>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImplMethod {
+    pub name: Ident,
+    pub func: ItemFunc,
+}
+
+pub fn parse_item_impl(i: TokenSpan) -> ParResult<ItemImpl> {
+    let (i, span_impl) = parse_keyword("impl")(i)?;
+    let (i, generics) = opt(parse_generics)(i)?;
+    let (i, _) = Weak(Kind::DoubleCol).parse(i)?;
+
+    let (i, tr) = parse_path(i)?;
+    let (i, first) = parse_ty_tuple(i)?;
+    let (i, second) = opt(parse_ty_tuple)(i)?;
+    let (tr_args, mut target) = match second {
+        Some(target) => (Some(first), target),
+        None => (None, first),
+    };
+
+    if target.types.len() != 1 {
+        return Err(Err::Failure(ErrorChain::from(Error::new(
+            i,
+            "Expected exactly one type to implement the trait for",
+        ))));
+    }
+
+    let (i, block) = tag!(Kind::Block(_), Token { kind: Kind::Block(vals), .. } => vals)(i)?;
+    let block = TokenSpan::without_docs(i.file.clone(), block);
+
+    let (_, methods) = all_consuming(many0(parse_impl_method))(block)?;
+
+    Ok((
+        i,
+        ItemImpl {
+            span_impl,
+            generics,
+            tr,
+            tr_args,
+            target: target.types.pop().unwrap(),
+            methods,
+        },
+    ))
+}
+
+fn parse_impl_method(i: TokenSpan) -> ParResult<ImplMethod> {
+    let (i, name) = parse_name(i)?;
+    let (i, func) = parse_item_func(i)?;
+
+    Ok((i, ImplMethod { name, func }))
+}
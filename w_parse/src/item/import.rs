@@ -1,8 +1,8 @@
 use crate::expr::parse_many0;
-use crate::expr::path::{parse_path, ExprPath};
+use crate::expr::path::{parse_path, parse_relative_root, ExprPath};
 use crate::{tag, ParResult, TokenSpan, Weak};
 use nom::branch::alt;
-use nom::combinator::{all_consuming, map, verify};
+use nom::combinator::{all_consuming, map, opt, verify};
 use nom::sequence::tuple;
 use nom::Parser;
 use w_tokenize::Kind;
@@ -16,20 +16,28 @@ pub enum Imports {
 #[derive(Debug, Clone)]
 pub struct ItemImports {
     pub imports: Vec<Imports>,
+    /// `{ a } :: super` imports from the parent module itself
     pub from: ExprPath,
 }
 
 pub fn parse_item_import(i: TokenSpan) -> ParResult<ItemImports> {
     let (i, imports) = parse_imports(i)?;
     let (i, _) = Weak(Kind::DoubleCol).parse(i)?;
-    let (i, from) = parse_path(i)?;
+    let (i, from) = alt((
+        parse_path,
+        map(parse_relative_root, |root| ExprPath {
+            root: Some(root),
+            path: vec![],
+        }),
+    ))(i)?;
+    let (i, _) = opt(Weak(Kind::Semicolon))(i)?;
 
     Ok((i, ItemImports { imports, from }))
 }
 
 fn parse_imports(i: TokenSpan) -> ParResult<Vec<Imports>> {
     let (i, block) = tag!(Kind::Block(_), Token { kind: Kind::Block(vals), .. } => vals)(i)?;
-    let block = TokenSpan::new(i.file.clone(), block);
+    let block = TokenSpan::without_docs(i.file.clone(), block);
 
     let (_, imports) = all_consuming(parse_many0(alt((
         map(
@@ -0,0 +1,58 @@
+use crate::error::{Error, ErrorChain};
+use crate::expr::path::{parse_path, ExprPath};
+use crate::item::attr::{parse_attributes, Attribute};
+use crate::item::doc::Docs;
+use crate::item::func::{parse_item_func, ItemFunc};
+use crate::item::generics::{parse_generics, Generics};
+use crate::{parse_name, Ident, ParResult, TokenSpan, Weak};
+use nom::combinator::opt;
+use nom::{Err, Parser};
+use w_tokenize::Kind;
+
+/// `vec(T):push :: func(self *vec(T), val T) { ... }`
+#[derive(Debug, Clone)]
+pub struct ItemMethod {
+    pub docs: Docs,
+    /// Path of the type the method is defined on
+    pub ty: ExprPath,
+    pub generics: Option<Generics>,
+    pub name: Ident,
+    pub attrs: Vec<Attribute>,
+    pub func: ItemFunc,
+}
+
+pub fn parse_item_method(oi: TokenSpan) -> ParResult<ItemMethod> {
+    let (i, mut ty) = parse_path(oi.clone())?;
+    let (i, generics) = opt(parse_generics)(i)?;
+
+    // without generics the path parser already took the method name
+    let (i, name) = match generics {
+        Some(_) => {
+            let (i, _) = Weak(Kind::Colon).parse(i)?;
+            parse_name(i)?
+        }
+        None if ty.path.len() > 1 => (i, ty.path.pop().unwrap()),
+        None => {
+            return Err(Err::Error(ErrorChain::from(Error::new(
+                oi,
+                "Expected a method, `type:name`",
+            ))))
+        }
+    };
+
+    let (i, _) = Weak(Kind::DoubleCol).parse(i)?;
+    let (i, attrs) = parse_attributes(i)?;
+    let (i, func) = parse_item_func(i)?;
+
+    Ok((
+        i,
+        ItemMethod {
+            docs: Docs::default(),
+            ty,
+            generics,
+            name,
+            attrs,
+            func,
+        },
+    ))
+}
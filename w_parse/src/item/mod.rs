@@ -1,23 +1,52 @@
+use crate::item::doc::parse_docs;
 use crate::item::import::ItemImports;
+use crate::item::method::ItemMethod;
+use crate::item::module::ItemModules;
 use crate::item::named::ItemNamed;
+use crate::item::r#impl::ItemImpl;
 use crate::{ParResult, TokenSpan};
 use nom::branch::alt;
 use nom::combinator::map;
 
+pub mod attr;
+pub mod doc;
 pub mod func;
+pub mod generics;
+pub mod r#impl;
 pub mod import;
+pub mod method;
+pub mod module;
 pub mod named;
+pub mod r#trait;
+pub mod value;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum Item {
     Definer(ItemNamed),
+    Method(ItemMethod),
+    Impl(ItemImpl),
     Import(ItemImports),
+    Modules(ItemModules),
 }
 
 pub fn parse_item(i: TokenSpan) -> ParResult<Item> {
-    alt((
+    let (i, docs) = parse_docs(i)?;
+
+    let (i, mut item) = alt((
         map(named::parse_named, Item::Definer),
+        map(method::parse_item_method, Item::Method),
+        map(r#impl::parse_item_impl, Item::Impl),
         map(import::parse_item_import, Item::Import),
-    ))(i)
+        map(module::parse_item_modules, Item::Modules),
+    ))(i)?;
+
+    match &mut item {
+        Item::Definer(named) => named.docs = docs,
+        Item::Method(method) => method.docs = docs,
+        // nothing to document, the comments are kept as plain comments
+        Item::Impl(_) | Item::Import(_) | Item::Modules(_) => (),
+    }
+
+    Ok((i, item))
 }
@@ -0,0 +1,24 @@
+use crate::expr::parse_many0;
+use crate::{parse_keyword, parse_name, tag, Ident, ParResult, TokenSpan, Weak};
+use nom::combinator::{all_consuming, opt};
+use nom::Parser;
+use w_tokenize::{Kind, Span};
+
+/// `{ a, b } :: mod;` declares the child modules `a` and `b`
+#[derive(Debug, Clone)]
+pub struct ItemModules {
+    pub modules: Vec<Ident>,
+    pub span_mod: Span,
+}
+
+pub fn parse_item_modules(i: TokenSpan) -> ParResult<ItemModules> {
+    let (i, block) = tag!(Kind::Block(_), Token { kind: Kind::Block(vals), .. } => vals)(i)?;
+    let block = TokenSpan::without_docs(i.file.clone(), block);
+    let (i, _) = Weak(Kind::DoubleCol).parse(i)?;
+    let (i, span_mod) = parse_keyword("mod")(i)?;
+    let (i, _) = opt(Weak(Kind::Semicolon))(i)?;
+
+    let (_, modules) = all_consuming(parse_many0(parse_name))(block)?;
+
+    Ok((i, ItemModules { modules, span_mod }))
+}
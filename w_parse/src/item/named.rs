@@ -1,13 +1,22 @@
+use crate::item::attr::{parse_attributes, Attribute};
+use crate::item::doc::Docs;
 use crate::item::func::{parse_item_func, ItemFunc};
+use crate::item::generics::{parse_generics, Generics};
+use crate::item::r#trait::{parse_item_trait, ItemTrait};
+use crate::item::value::{parse_item_value, ItemValue};
 use crate::{parse_name, parse_type, Ident, ItemTy, ParResult, TokenSpan, Weak};
 use nom::branch::alt;
-use nom::combinator::{cond, map};
+use nom::combinator::{cond, map, opt};
 use nom::Parser;
 use w_tokenize::{Kind, Token};
 
 #[derive(Debug, Clone)]
 pub struct ItemNamed {
+    pub docs: Docs,
     pub name: Ident,
+    pub generics: Option<Generics>,
+    /// `name :: @(attrs) kind`, for types, functions and values
+    pub attrs: Vec<Attribute>,
     pub kind: NamedKind,
 }
 
@@ -15,6 +24,9 @@ pub struct ItemNamed {
 pub enum NamedKind {
     Type(ItemNamedType),
     Func(ItemFunc),
+    Trait(ItemTrait),
+    /// A module level constant or static
+    Value(Box<ItemValue>),
 }
 
 #[derive(Debug, Clone)]
@@ -25,14 +37,27 @@ pub struct ItemNamedType {
 
 pub fn parse_named(i: TokenSpan) -> ParResult<ItemNamed> {
     let (i, name) = parse_name(i)?;
+    let (i, generics) = opt(parse_generics)(i)?;
     let (i, _) = Weak(Kind::DoubleCol).parse(i)?;
+    let (i, attrs) = parse_attributes(i)?;
 
     let (i, kind) = alt((
         map(parse_item_func, NamedKind::Func),
+        map(parse_item_trait, NamedKind::Trait),
+        map(map(parse_item_value, Box::new), NamedKind::Value),
         map(parse_type_definer, NamedKind::Type),
     ))(i)?;
 
-    Ok((i, ItemNamed { name, kind }))
+    Ok((
+        i,
+        ItemNamed {
+            docs: Docs::default(),
+            name,
+            generics,
+            attrs,
+            kind,
+        },
+    ))
 }
 
 pub fn parse_type_definer(i: TokenSpan) -> ParResult<ItemNamedType> {
@@ -40,6 +65,7 @@ pub fn parse_type_definer(i: TokenSpan) -> ParResult<ItemNamedType> {
 
     let terminated = match &ty {
         ItemTy::Referred(_) => true,
+        ItemTy::Applied(_) => true,
         ItemTy::Struct(_) => false,
         ItemTy::Enum(_) => false,
         ItemTy::Tuple(_) => true,
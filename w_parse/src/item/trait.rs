@@ -0,0 +1,75 @@
+use crate::expr::parse_many0;
+use crate::types::func::{parse_func_args, parse_func_named_args};
+use crate::{parse_keyword, parse_name, parse_type, tag, Ident, ItemTy, ParResult, TokenSpan};
+use nom::branch::alt;
+use nom::combinator::{all_consuming, map, opt};
+use w_tokenize::Span;
+
+/// `disposable(Self) :: trait { dispose func(self *Self) }`
+#[derive(Debug, Clone)]
+pub struct ItemTrait {
+    pub span_trait: Span,
+    pub methods: Vec<TraitMethod>,
+}
+
+/// A method signature every implementation has to provide
+#[derive(Debug, Clone)]
+pub struct TraitMethod {
+    pub name: Ident,
+    pub func: TraitFunc,
+}
+
+/// `func(self *Self, other R) R` or just `func(*Self, R) R`,
+/// only the types of the arguments matter to implementations
+#[derive(Debug, Clone)]
+pub struct TraitFunc {
+    pub span_func: Span,
+    pub args: Vec<ItemTy>,
+    /// `None` if the function returns the empty tuple
+    pub ret_ty: Option<Box<ItemTy>>,
+}
+
+pub fn parse_item_trait(i: TokenSpan) -> ParResult<ItemTrait> {
+    let (i, span_trait) = parse_keyword("trait")(i)?;
+
+    let (i, block) = tag!(Kind::Block(_), Token { kind: Kind::Block(vals), .. } => vals)(i)?;
+    let block = TokenSpan::without_docs(i.file.clone(), block);
+
+    let (_, methods) = all_consuming(parse_many0(parse_trait_method))(block)?;
+
+    Ok((
+        i,
+        ItemTrait {
+            span_trait,
+            methods,
+        },
+    ))
+}
+
+fn parse_trait_method(i: TokenSpan) -> ParResult<TraitMethod> {
+    let (i, name) = parse_name(i)?;
+    let (i, func) = parse_trait_func(i)?;
+
+    Ok((i, TraitMethod { name, func }))
+}
+
+fn parse_trait_func(i: TokenSpan) -> ParResult<TraitFunc> {
+    let (i, span_func) = parse_keyword("func")(i)?;
+
+    let (i, args) = alt((
+        map(parse_func_named_args, |args| {
+            args.into_iter().map(|arg| arg.ty).collect()
+        }),
+        parse_func_args,
+    ))(i)?;
+    let (i, ret_ty) = opt(map(parse_type, Box::new))(i)?;
+
+    Ok((
+        i,
+        TraitFunc {
+            span_func,
+            args,
+            ret_ty,
+        },
+    ))
+}
@@ -0,0 +1,38 @@
+use crate::expr::{parse_expression, Expr};
+use crate::{parse_keyword, parse_type, ItemTy, ParResult, TokenSpan, Weak};
+use nom::combinator::opt;
+use nom::{Err, Parser};
+use w_tokenize::{Kind, Span};
+
+/// `name :: ty := value;` defines a constant, `name :: mut ty := value;` a static,
+/// the type may be left out
+#[derive(Debug, Clone)]
+pub struct ItemValue {
+    pub mutability: Option<Span>,
+    pub ty: Option<ItemTy>,
+    pub span_define: Span,
+    pub value: Expr,
+}
+
+pub fn parse_item_value(i: TokenSpan) -> ParResult<ItemValue> {
+    let (i, mutability) = opt(parse_keyword("mut"))(i)?;
+    let (i, ty) = opt(parse_type)(i)?;
+    let (i, define) = Weak(Kind::Define).parse(i)?;
+
+    let failure = |err| match err {
+        Err::Error(err) => Err::Failure(err),
+        err => err,
+    };
+    let (i, value) = parse_expression(i).map_err(failure)?;
+    let (i, _) = Weak(Kind::Semicolon).parse(i).map_err(failure)?;
+
+    Ok((
+        i,
+        ItemValue {
+            mutability,
+            ty,
+            span_define: define.span,
+            value,
+        },
+    ))
+}
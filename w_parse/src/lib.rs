@@ -5,9 +5,13 @@ pub mod expr;
 pub mod item;
 pub mod module;
 pub mod parser;
+pub mod symbol;
 pub mod types;
 pub mod util;
 
+#[cfg(test)]
+mod tests;
+
 use crate::error::{Error, ErrorChain};
 use crate::parser::Weak;
 use crate::types::{parse_type, ItemTy};
@@ -19,10 +23,23 @@ use std::hash::{Hash, Hasher};
 use w_tokenize::{Kind, Span};
 
 pub use crate::parser::{ParResult, TokenSpan};
+pub use crate::symbol::Symbol;
 pub use module::{parse_module, ParsedModule};
 
+/// A name together with its interned text, equality and hashing only look at the [`Symbol`]
 #[derive(Debug, Clone)]
-pub struct Ident(pub Span);
+pub struct Ident(pub Span, pub Symbol);
+
+impl Ident {
+    pub fn new(span: Span) -> Self {
+        let sym = Symbol::intern(span.fragment());
+        Self(span, sym)
+    }
+
+    pub fn sym(&self) -> Symbol {
+        self.1
+    }
+}
 
 pub fn parse(i: TokenSpan) -> ParResult<()> {
     Ok((i, ()))
@@ -38,7 +55,7 @@ fn parse_keyword(specific: &str) -> impl FnMut(TokenSpan) -> ParResult<Span> + '
 
 fn parse_identifier(i: TokenSpan) -> ParResult<Ident> {
     let (i, tok) = Weak(Kind::Ident).parse(i)?;
-    Ok((i, Ident(tok.span)))
+    Ok((i, Ident::new(tok.span)))
 }
 
 fn quick_err<T>(span: TokenSpan, reason: impl Into<Cow<'static, str>>) -> ParResult<T> {
@@ -56,25 +73,46 @@ fn keyword_check(ident: &Ident) -> bool {
             | "enum"
             | "func"
             | "for"
+            | "in"
             | "while"
             | "loop"
+            | "break"
+            | "continue"
+            | "return"
             | "if"
+            | "match"
             | "else"
             | "mut"
             | "defer"
             | "mod"
+            | "trait"
+            | "impl"
+            | "as"
+            | "super"
     )
 }
 
 impl Hash for Ident {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        (*self.0).hash(state)
+        self.1.hash(state)
     }
 }
 
 impl PartialEq for Ident {
     fn eq(&self, other: &Self) -> bool {
-        (*self.0) == (*other.0)
+        self.1 == other.1
     }
 }
 impl Eq for Ident {}
+
+impl From<&Ident> for Symbol {
+    fn from(ident: &Ident) -> Self {
+        ident.1
+    }
+}
+
+impl From<Ident> for Symbol {
+    fn from(ident: Ident) -> Self {
+        ident.1
+    }
+}
@@ -1,17 +1,27 @@
+use crate::item::doc::{parse_docs, parse_module_docs, Docs};
 use crate::item::{parse_item, Item};
 use crate::{Ident, ParResult, TokenSpan};
+use nom::combinator::all_consuming;
 
 // Leaving this open for mode things in the future like imports
 
 pub struct ParsedModule {
     pub name: Ident,
+    pub docs: Docs,
     pub items: Vec<Item>,
 }
 
-pub fn parse_module(mut i: TokenSpan, name: Ident) -> ParResult<ParsedModule> {
+pub fn parse_module(i: TokenSpan, name: Ident) -> ParResult<ParsedModule> {
+    let (mut i, docs) = parse_module_docs(i)?;
     let mut items = vec![];
 
     while !i.is_empty() {
+        // doc comments at the end of the file have nothing to document
+        if let Ok((ni, _)) = all_consuming(parse_docs)(i.clone()) {
+            i = ni;
+            break;
+        }
+
         let (ni, item) = parse_item(i)?;
 
         items.push(item);
@@ -19,5 +29,5 @@ pub fn parse_module(mut i: TokenSpan, name: Ident) -> ParResult<ParsedModule> {
         i = ni;
     }
 
-    Ok((i, ParsedModule { name, items }))
+    Ok((i, ParsedModule { name, docs, items }))
 }
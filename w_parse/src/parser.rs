@@ -24,6 +24,18 @@ impl TokenSpan {
         }
     }
 
+    /// A span over the contents of a group where doc comments document nothing,
+    /// they are skipped like any other comment
+    pub fn without_docs(file: Span, tokens: Rc<[Token]>) -> TokenSpan {
+        let is_doc = |tk: &Token| matches!(tk.kind, Kind::DocOuter(_) | Kind::DocInner(_));
+        if !tokens.iter().any(is_doc) {
+            return TokenSpan::new(file, tokens);
+        }
+
+        let tokens = tokens.iter().filter(|tk| !is_doc(tk)).cloned().collect();
+        TokenSpan::new(file, tokens)
+    }
+
     pub fn as_span(&self) -> Span {
         let diff = self.local.end - self.local.start;
         match diff {
@@ -35,7 +47,7 @@ impl TokenSpan {
             1 => self.tokens[self.local.start].span.clone(),
             _ => {
                 let start = self.tokens[self.local.start].span.clone();
-                let end = self.tokens[self.local.end].span.clone();
+                let end = self.tokens[self.local.end - 1].span.clone();
 
                 let so = start.location_offset();
                 let eo = end.location_offset() + end.len();
@@ -178,7 +190,7 @@ impl Compare<Weak> for Token {
 
 impl Compare<Strong> for TokenSpan {
     fn compare(&self, t: Strong) -> CompareResult {
-        if self.len() >= 1 {
+        if !self.is_empty() {
             self[0].compare(t)
         } else {
             CompareResult::Error
@@ -186,7 +198,7 @@ impl Compare<Strong> for TokenSpan {
     }
 
     fn compare_no_case(&self, t: Strong) -> CompareResult {
-        if self.len() >= 1 {
+        if !self.is_empty() {
             self[0].compare_no_case(t)
         } else {
             CompareResult::Error
@@ -195,7 +207,7 @@ impl Compare<Strong> for TokenSpan {
 }
 impl Compare<Weak> for TokenSpan {
     fn compare(&self, t: Weak) -> CompareResult {
-        if self.len() >= 1 {
+        if !self.is_empty() {
             self[0].compare(t)
         } else {
             CompareResult::Error
@@ -203,7 +215,7 @@ impl Compare<Weak> for TokenSpan {
     }
 
     fn compare_no_case(&self, t: Weak) -> CompareResult {
-        if self.len() >= 1 {
+        if !self.is_empty() {
             self[0].compare_no_case(t)
         } else {
             CompareResult::Error
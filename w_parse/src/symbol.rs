@@ -0,0 +1,94 @@
+use lazy_static::lazy_static;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::ptr;
+use std::sync::Mutex;
+
+/// An interned identifier, comparing and hashing symbols does not touch the text.
+///
+/// Every text is interned exactly once, so the address of the text identifies the symbol.
+#[derive(Copy, Clone)]
+pub struct Symbol(&'static str);
+
+lazy_static! {
+    static ref INTERNER: Mutex<HashSet<&'static str>> = Mutex::default();
+}
+
+thread_local! {
+    /// Symbols this thread interned before, the parser backtracks over
+    /// the same identifiers a lot and shouldn't take the lock for them again
+    static SEEN: RefCell<HashMap<&'static str, Symbol>> = RefCell::default();
+}
+
+impl Symbol {
+    pub fn intern(str: &str) -> Self {
+        if let Some(sym) = SEEN.with(|seen| seen.borrow().get(str).copied()) {
+            return sym;
+        }
+
+        let sym = {
+            let mut interner = INTERNER.lock().unwrap();
+            match interner.get(str) {
+                Some(&str) => Symbol(str),
+                None => {
+                    // symbols live as long as the compiler, so there is no point in ever freeing them
+                    let str: &'static str = Box::leak(str.to_owned().into_boxed_str());
+                    interner.insert(str);
+                    Symbol(str)
+                }
+            }
+        };
+
+        SEEN.with(|seen| seen.borrow_mut().insert(sym.0, sym));
+        sym
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.0, state)
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders by text, which only has to be compared for different symbols
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self == other {
+            true => Ordering::Equal,
+            false => self.0.cmp(other.0),
+        }
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl Debug for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
@@ -0,0 +1,1071 @@
+use crate::expr::assign::AssignOp;
+use crate::expr::block::BlockKind;
+use crate::expr::define::ExprDefine;
+use crate::expr::loops::ExprWhile;
+use crate::expr::ops::BiOp;
+use crate::expr::path::{ExprPath, PathRoot};
+use crate::expr::pattern::Pattern;
+use crate::expr::unary::UnOp;
+use crate::expr::{parse_expression, Expr};
+use crate::item::attr::AttrArg;
+use crate::item::named::{ItemNamed, NamedKind};
+use crate::item::Item;
+use crate::types::array::ArraySize;
+use crate::types::ItemTy;
+use crate::{parse_module, ErrorChain, Ident, ParsedModule, Symbol, TokenSpan};
+use nom::combinator::all_consuming;
+use nom::Slice;
+use std::rc::Rc;
+use w_rcstr::{Origin, RcStr};
+use w_tokenize::{tokenize, Span};
+
+fn module(src: &str) -> ParsedModule {
+    let file = Span::new(RcStr::new(src.to_string(), Origin::Unknown));
+    let (_, tokens) = tokenize(file.clone()).unwrap();
+    let name = Ident::new(file.clone());
+    let (_, module) = parse_module(TokenSpan::new(file, Rc::from(tokens)), name).unwrap();
+    module
+}
+
+/// Parses the samples of WIP_tests, naming the one which fails
+fn samples(names: &[&str]) {
+    for name in names {
+        let path = format!("{}/../WIP_tests/{name}", env!("CARGO_MANIFEST_DIR"));
+        let src = std::fs::read_to_string(path).unwrap();
+        let file = Span::new(RcStr::new(src, Origin::Unknown));
+        let (_, tokens) = tokenize(file.clone()).unwrap();
+        let name_ident = Ident::new(file.clone());
+        if let Err(err) = parse_module(TokenSpan::new(file, Rc::from(tokens)), name_ident) {
+            panic!("{name} does not parse: {err:?}");
+        }
+    }
+}
+
+fn expr(src: &str) -> Result<Expr, nom::Err<ErrorChain>> {
+    let file = Span::new(RcStr::new(src.to_string(), Origin::Unknown));
+    let (_, tokens) = tokenize(file.clone()).unwrap();
+    all_consuming(parse_expression)(TokenSpan::new(file, Rc::from(tokens))).map(|(_, expr)| expr)
+}
+
+/// Prints the expression as an s-expression
+fn show(expr: &Expr) -> String {
+    let opt = |expr: &Option<Box<Expr>>| expr.as_deref().map_or("_".to_string(), show);
+    match expr {
+        Expr::Ident(ident) => ident.1.as_str().to_string(),
+        Expr::Number(num) => match &num.sign {
+            Some(sign) => format!("{}{}", &**sign, &**num.number),
+            None => num.number.to_string(),
+        },
+        Expr::Binary(bin) => format!("({:?} {} {})", bin.op, show(&bin.left), show(&bin.right)),
+        Expr::Range(range) => format!(
+            "({} {} {})",
+            if range.inclusive { "..=" } else { ".." },
+            opt(&range.start),
+            opt(&range.end)
+        ),
+        Expr::Index(index) => format!(
+            "{}[{}]",
+            show(&index.base),
+            index
+                .args
+                .values
+                .iter()
+                .map(show)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Expr::Tuple(tuple) => format!(
+            "({})",
+            tuple.values.iter().map(show).collect::<Vec<_>>().join(", ")
+        ),
+        Expr::Unary(unary) => {
+            let op = match unary.op {
+                UnOp::Ref(_) => "&",
+                UnOp::RefMut(_, _) => "&mut",
+                UnOp::Deref(_) => "*",
+                UnOp::Not(_) => "!",
+                UnOp::Neg(_) => "-",
+                UnOp::BitNot(_) => "~",
+            };
+            format!("({op} {})", show(&unary.expr))
+        }
+        Expr::Field(field) => format!("{}.{}", show(&field.base), field.field.0),
+        Expr::Cast(cast) => format!("(as {} {})", show(&cast.base), show_ty(&cast.ty)),
+        Expr::Call(call) => format!(
+            "({} {})",
+            show(&call.base),
+            call.args
+                .values
+                .iter()
+                .map(show)
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        Expr::Define(ExprDefine {
+            pattern: Pattern::Binding(bind),
+            value,
+            ..
+        }) => {
+            let mutability = if bind.mutability.is_some() {
+                "mut "
+            } else {
+                ""
+            };
+            format!("(:= {mutability}{} {})", bind.name.1.as_str(), show(value))
+        }
+        Expr::Assign(assign) => format!(
+            "({:?}= {} {})",
+            assign.op,
+            show(&assign.assignee),
+            show(&assign.value)
+        ),
+        Expr::Block(block) => format!(
+            "{{ {} }}",
+            block.exprs().map(show).collect::<Vec<_>>().join("; ")
+        ),
+        expr => panic!("unexpected expression {expr:?}"),
+    }
+}
+
+fn show_ty(ty: &ItemTy) -> String {
+    match ty {
+        ItemTy::Referred(path) => path
+            .path
+            .iter()
+            .map(|seg| seg.0.to_string())
+            .collect::<Vec<_>>()
+            .join(":"),
+        ItemTy::Pointer(ptr) if ptr.mutability.is_some() => format!("*mut {}", show_ty(&ptr.ty)),
+        ItemTy::Pointer(ptr) => format!("*{}", show_ty(&ptr.ty)),
+        ItemTy::Array(array) => {
+            let size = match &array.size {
+                None => String::new(),
+                Some(ArraySize::Sized(num)) => num.number.to_string(),
+                Some(ArraySize::Unsized(_)) => "?".to_string(),
+            };
+            format!("[{size}]{}", show_ty(&array.ty))
+        }
+        ItemTy::Func(func) => {
+            let args = func.args.iter().map(show_ty).collect::<Vec<_>>();
+            match &func.ret_ty {
+                Some(ret) => format!("func({}) {}", args.join(", "), show_ty(ret)),
+                None => format!("func({})", args.join(", ")),
+            }
+        }
+        ty => panic!("unexpected type {ty:?}"),
+    }
+}
+
+#[test]
+fn token_span_covers_its_tokens() {
+    let file = Span::new(RcStr::new("a + b c".to_string(), Origin::Unknown));
+    let (_, tokens) = tokenize(file.clone()).unwrap();
+    let tokens = TokenSpan::new(file, Rc::from(tokens));
+
+    assert_eq!(&**tokens.slice(0..3).as_span(), "a + b");
+    assert_eq!(&**tokens.slice(2..4).as_span(), "b c");
+    assert_eq!(&**tokens.slice(1..2).as_span(), "+");
+}
+
+#[test]
+fn docs_attach_to_items() {
+    let md = module(
+        "//! the module\n\
+         /// first line\n\
+         ///second line\n\
+         vec3 :: struct { x f32 }\n\
+         // not a doc\n\
+         other :: vec3;",
+    );
+
+    assert_eq!(md.docs.text(), "the module");
+    let docs = md
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Definer(named) => named.docs.text(),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(docs, ["first line\nsecond line", ""]);
+}
+
+#[test]
+fn docs_on_fields_variants_and_bodies() {
+    let md = module(
+        "point :: struct {\n\
+             /// horizontal\n\
+             x i32,\n\
+             y i32,\n\
+             /// nothing follows\n\
+         }\n\
+         shape :: enum {\n\
+             /// no corners\n\
+             circle,\n\
+             square(i32),\n\
+         }\n\
+         /// imports are not documented\n\
+         { a } :: b;\n\
+         main :: func() ! {\n\
+             /// just a comment\n\
+             x := 1;\n\
+             x\n\
+         }\n\
+         /// end of file",
+    );
+
+    let [Item::Definer(point), Item::Definer(shape), Item::Import(_), Item::Definer(_)] =
+        md.items.as_slice()
+    else {
+        panic!("expected four items");
+    };
+    let NamedKind::Type(ty) = &point.kind else {
+        unreachable!()
+    };
+    let ItemTy::Struct(st) = &ty.ty else {
+        unreachable!()
+    };
+    assert_eq!(st.fields[0].docs.text(), "horizontal");
+    assert!(st.fields[1].docs.is_empty());
+
+    let NamedKind::Type(ty) = &shape.kind else {
+        unreachable!()
+    };
+    let ItemTy::Enum(en) = &ty.ty else {
+        unreachable!()
+    };
+    assert_eq!(en.variants[0].docs.text(), "no corners");
+    assert!(en.variants[1].docs.is_empty());
+}
+
+#[test]
+fn range_expressions() {
+    let cases = [
+        ("0..(len - 1)", "(.. 0 ((Sub len 1)))"),
+        ("a + 1..b * 2", "(.. (Add a 1) (Mul b 2))"),
+        ("a..=b", "(..= a b)"),
+        ("..", "(.. _ _)"),
+        ("..b", "(.. _ b)"),
+        ("a..", "(.. a _)"),
+        ("0.._", "(.. 0 _)"),
+        ("buf[..rl]", "buf[(.. _ rl)]"),
+    ];
+    for (src, tree) in cases {
+        assert_eq!(show(&expr(src).unwrap()), tree, "{src}");
+    }
+
+    let Expr::Index(index) = expr("buf[1..]").unwrap() else {
+        unreachable!()
+    };
+    assert!(index.slice().is_some());
+    assert!(matches!(
+        expr("a..b").unwrap(),
+        Expr::Range(range) if range.end.is_some()
+    ));
+    assert_eq!(BiOp::Range.precedence(), BiOp::RangeInclusive.precedence());
+}
+
+#[test]
+fn array_sizes() {
+    let sources = ["*[?]u8", "[]u8", "[4][?]u8", "*mut [16]u8"];
+    let md = module(
+        &sources
+            .iter()
+            .enumerate()
+            .map(|(idx, ty)| format!("t{idx} :: {ty};\n"))
+            .collect::<String>(),
+    );
+
+    for (item, src) in md.items.iter().zip(sources) {
+        let Item::Definer(ItemNamed {
+            kind: NamedKind::Type(ty),
+            ..
+        }) = item
+        else {
+            panic!("expected a type definition");
+        };
+        assert_eq!(show_ty(&ty.ty), src);
+    }
+}
+
+#[test]
+fn range_errors() {
+    assert!(matches!(expr("a..b..c"), Err(nom::Err::Failure(_))));
+    assert!(matches!(expr("a..="), Err(nom::Err::Failure(_))));
+}
+
+#[test]
+fn attributes() {
+    let md = module(
+        "rc :: @(no_copy, repr(align(8), \"c\")) struct { @skip inner *u8, count usize }\n\
+         e :: enum { @(a) b(u8), c }\n\
+         trap :: @extern(\"__buildin__\") @cold func() !;",
+    );
+    let named = md
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Definer(named) => named,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    let names = |attrs: &[crate::item::attr::Attribute]| {
+        attrs
+            .iter()
+            .map(|attr| attr.name().0.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(names(&named[0].attrs), ["no_copy", "repr"]);
+    let repr = named[0].attrs[1].args.as_ref().unwrap();
+    assert_eq!(&**repr.span, "(align(8), \"c\")");
+    assert!(matches!(
+        &repr.args[..],
+        [AttrArg::Nested(align), AttrArg::Literal(_)] if align.args.is_some()
+    ));
+    assert_eq!(&**named[0].attrs[1].span, "repr(align(8), \"c\")");
+
+    let NamedKind::Type(ty) = &named[0].kind else {
+        unreachable!()
+    };
+    let ItemTy::Struct(st) = &ty.ty else {
+        unreachable!()
+    };
+    assert_eq!(names(&st.fields[0].attrs), ["skip"]);
+    assert!(st.fields[1].attrs.is_empty());
+
+    let NamedKind::Type(ty) = &named[1].kind else {
+        unreachable!()
+    };
+    let ItemTy::Enum(en) = &ty.ty else {
+        unreachable!()
+    };
+    assert_eq!(names(&en.variants[0].attrs), ["a"]);
+
+    assert_eq!(names(&named[2].attrs), ["extern", "cold"]);
+}
+
+#[test]
+fn identifiers_compare_by_symbol() {
+    let module = module("point :: struct { x i32 }\nline :: struct { a point, b point }");
+    let [Item::Definer(point), Item::Definer(line)] = module.items.as_slice() else {
+        panic!("expected two definitions");
+    };
+    let NamedKind::Type(ty) = &line.kind else {
+        panic!("expected a type");
+    };
+    let ItemTy::Struct(line) = &ty.ty else {
+        panic!("expected a struct");
+    };
+    let ItemTy::Referred(field_ty) = &line.fields[0].ty else {
+        panic!("expected a type reference");
+    };
+
+    assert_eq!(field_ty.path[0], point.name);
+    assert_ne!(
+        field_ty.path[0].0.location_offset(),
+        point.name.0.location_offset()
+    );
+    assert_eq!(point.name.sym(), Symbol::intern("point"));
+    assert_eq!(point.name.sym().as_str(), "point");
+    assert_ne!(line.fields[0].name, line.fields[1].name);
+}
+
+#[test]
+fn symbols_are_shared_between_threads() {
+    let other = std::thread::spawn(|| Symbol::intern("from_another_thread"))
+        .join()
+        .unwrap();
+
+    assert_eq!(Symbol::intern("from_another_thread"), other);
+    assert_eq!(other.as_str(), "from_another_thread");
+    assert!(Symbol::intern("alpha") < Symbol::intern("beta"));
+}
+
+#[test]
+fn generic_parameters() {
+    let module = module(
+        "option(T) :: enum { some(T), none }\n\
+         pair(A, B(ord, disposable)) :: struct { a A, b B }\n\
+         sort(T(ord)) :: func(data *[]T) ! { data }\n\
+         opt_vec3 :: option(vec3);\n\
+         nested :: struct { x option(pair(a, *b)) }",
+    );
+    let named = module
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Definer(def) => def,
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+
+    let params = |idx: usize| {
+        named[idx]
+            .generics
+            .as_ref()
+            .map(|generics| {
+                generics
+                    .params
+                    .iter()
+                    .map(|param| (param.name.0.to_string(), param.bounds.len()))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default()
+    };
+    assert_eq!(params(0), [("T".to_string(), 0)]);
+    assert_eq!(params(1), [("A".to_string(), 0), ("B".to_string(), 2)]);
+    assert_eq!(params(2), [("T".to_string(), 1)]);
+    assert!(named[3].generics.is_none());
+
+    let NamedKind::Type(ty) = &named[3].kind else {
+        unreachable!()
+    };
+    let ItemTy::Applied(applied) = &ty.ty else {
+        panic!("expected a type application");
+    };
+    assert_eq!(&**applied.path.path[0].0, "option");
+    assert!(matches!(&applied.args.types[..], [ItemTy::Referred(_)]));
+
+    let NamedKind::Type(ty) = &named[4].kind else {
+        unreachable!()
+    };
+    let ItemTy::Struct(st) = &ty.ty else {
+        unreachable!()
+    };
+    let ItemTy::Applied(option) = &st.fields[0].ty else {
+        panic!("expected a type application");
+    };
+    assert!(matches!(
+        &option.args.types[..],
+        [ItemTy::Applied(pair)] if pair.args.types.len() == 2
+    ));
+}
+
+#[test]
+fn methods() {
+    let module = module(
+        "iter(T):next :: func(self *iter(T)) option(*T) { self }\n\
+         vec(T(disposable)):dispose :: func(self *vec(T)) ! { self }\n\
+         point:len :: @inline func(self *point) f32 { self }\n\
+         geo:point:zero :: func() point { x }",
+    );
+    let methods = module
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Method(method) => method,
+            _ => panic!("expected a method"),
+        })
+        .collect::<Vec<_>>();
+
+    let show = |idx: usize| {
+        let method = methods[idx];
+        let ty = method
+            .ty
+            .path
+            .iter()
+            .map(|ident| ident.0.to_string())
+            .collect::<Vec<_>>()
+            .join(":");
+        let generics = method.generics.as_ref().map_or(0, |g| g.params.len());
+        format!("{ty}({generics}).{}", method.name.0)
+    };
+    assert_eq!(show(0), "iter(1).next");
+    assert_eq!(show(1), "vec(1).dispose");
+    assert_eq!(show(2), "point(0).len");
+    assert_eq!(show(3), "geo:point(0).zero");
+    assert_eq!(methods[2].attrs.len(), 1);
+    assert_eq!(methods[0].func.func.args.len(), 1);
+}
+
+#[test]
+fn traits_and_impls() {
+    let module = module(
+        "disposable :: trait { dispose func(self *Self), size func(self *Self) usize }\n\
+         add(R) :: trait { add func(self Self, other R) Self }\n\
+         impl(T) :: disposable (rc(T)) { dispose func(self *rc(T)) { self } }\n\
+         impl :: add(i32) (i32) { add func(self i32, other i32) i32 { self } }",
+    );
+    let [Item::Definer(disposable), Item::Definer(add), Item::Impl(rc), Item::Impl(int)] =
+        module.items.as_slice()
+    else {
+        panic!("expected two traits and two impls");
+    };
+
+    let NamedKind::Trait(tr) = &disposable.kind else {
+        panic!("expected a trait");
+    };
+    assert_eq!(tr.methods.len(), 2);
+    assert!(tr.methods[0].func.ret_ty.is_none());
+    assert!(tr.methods[1].func.ret_ty.is_some());
+    assert!(matches!(&add.kind, NamedKind::Trait(_)));
+    assert_eq!(add.generics.as_ref().unwrap().params.len(), 1);
+
+    assert!(rc.generics.is_some() && rc.tr_args.is_none());
+    assert!(matches!(rc.target, ItemTy::Applied(_)));
+    assert_eq!(&**rc.methods[0].name.0, "dispose");
+    assert_eq!(int.tr_args.as_ref().unwrap().types.len(), 1);
+    assert_eq!(&**int.tr.path[0].0, "add");
+}
+
+#[test]
+fn trait_methods_without_argument_names() {
+    let traits = module("add_t(A, B, C) :: trait { add func(A, B) C, neg func(*A) }");
+    let [Item::Definer(add)] = traits.items.as_slice() else {
+        panic!("expected a trait");
+    };
+    let NamedKind::Trait(tr) = &add.kind else {
+        panic!("expected a trait");
+    };
+
+    let sig = |idx: usize| {
+        let func = &tr.methods[idx].func;
+        let args = func.args.iter().map(show_ty).collect::<Vec<_>>();
+        let ret = func.ret_ty.as_deref().map_or(String::new(), show_ty);
+        format!("({}) {ret}", args.join(", "))
+    };
+    assert_eq!(sig(0), "(A, B) C");
+    assert_eq!(sig(1), "(*A) ");
+
+    // function types may leave out the return type as well
+    let apply = module("apply :: func(f func(*u8), g func(u8) u8) ! { f }");
+    let [Item::Definer(apply)] = apply.items.as_slice() else {
+        panic!("expected a function");
+    };
+    let NamedKind::Func(func) = &apply.kind else {
+        panic!("expected a function");
+    };
+    let args = func
+        .func
+        .args
+        .iter()
+        .map(|arg| show_ty(&arg.ty))
+        .collect::<Vec<_>>();
+    assert_eq!(args, ["func(*u8)", "func(u8) u8"]);
+}
+
+#[test]
+fn for_loops() {
+    let Expr::For(simple) = expr("for i in 0..(len - 1) { swap(i) }").unwrap() else {
+        panic!("expected a for loop");
+    };
+    assert!(matches!(&simple.binding, Pattern::Binding(bind) if &**bind.name.0 == "i"));
+    assert_eq!(show(&simple.iterable), "(.. 0 ((Sub len 1)))");
+
+    let Expr::For(inline) = expr("for (_, b) in pairs -> b").unwrap() else {
+        panic!("expected a for loop");
+    };
+    assert!(matches!(
+        &inline.binding,
+        Pattern::Tuple(tuple) if matches!(tuple.fields[..], [Pattern::Wildcard(_), Pattern::Binding(_)])
+    ));
+    assert!(Expr::For(inline.clone()).needs_termination());
+
+    // { it := pairs; while option:some((_, b)) := it.next() -> b }
+    let BlockKind::Many { stmts, returning } = inline.desugar().kind else {
+        unreachable!()
+    };
+    let Expr::Define(define) = &stmts[0].expr else {
+        panic!("expected the iterator to be stored");
+    };
+    let Some(Expr::While(lowered)) = returning.as_deref() else {
+        panic!("expected a while loop");
+    };
+    let Expr::Define(cond) = &*lowered.cond else {
+        panic!("expected a pattern condition");
+    };
+    let (Pattern::Binding(iter), Expr::Call(next)) = (&define.pattern, &*cond.value) else {
+        unreachable!()
+    };
+    assert!(matches!(
+        &*next.base,
+        Expr::Field(field) if matches!(&*field.base, Expr::Ident(base) if *base == iter.name)
+    ));
+    assert!(iter.mutability.is_some());
+    assert!(matches!(
+        &cond.pattern,
+        Pattern::Variant(some) if matches!(&some.fields.as_ref().unwrap().fields[0], Pattern::Tuple(_))
+            && matches!(some.path.root, Some(PathRoot::Absolute(_)))
+    ));
+
+    // { mut it := 0; end := n; while it < end { i := it; it += 1; { use(i) } } }
+    let lower = |src| {
+        let Ok(Expr::For(lp)) = expr(src) else {
+            panic!("expected a for loop");
+        };
+        let BlockKind::Many { stmts, returning } = lp.desugar().kind else {
+            unreachable!()
+        };
+        let shown = stmts
+            .iter()
+            .map(|stmt| show(&stmt.expr))
+            .collect::<Vec<_>>();
+        (shown, *returning.unwrap())
+    };
+    let (stmts, lowered) = lower("for i in 0..n { use(i) }");
+    assert_eq!(stmts, ["(:= mut for iter 0)", "(:= for end n)"]);
+    let Expr::While(lowered) = lowered else {
+        panic!("expected a while loop");
+    };
+    assert_eq!(show(&lowered.cond), "(Lt for iter for end)");
+    let body = lowered.body.exprs().map(show).collect::<Vec<_>>();
+    assert_eq!(
+        body,
+        ["(:= i for iter)", "(Add= for iter 1)", "{ (use i) }"]
+    );
+
+    let (stmts, lowered) = lower("for i in 0..=n { use(i) }");
+    assert_eq!(stmts[2], "(:= mut for more (Le for iter for end))");
+    assert!(matches!(lowered, Expr::While(lp) if show(&lp.cond) == "for more"));
+    let (_, lowered) = lower("for i in 0.._ { use(i) }");
+    assert!(matches!(lowered, Expr::Loop(_)));
+    assert!(matches!(expr("for i in ..n {}"), Err(nom::Err::Failure(_))));
+
+    // control flow needs no arrow in front of it, which chains `else if` as well
+    let Expr::For(bare) = expr("for i in 0.._ if cstr[i] == 0 return i").unwrap() else {
+        panic!("expected a for loop");
+    };
+    assert!(
+        matches!(&bare.body.kind, BlockKind::Inline(body) if matches!(**body, Expr::Branch(_)))
+    );
+    let Expr::Branch(chain) = expr("if a -> 1 else if b -> 2 else return c").unwrap() else {
+        panic!("expected a branch");
+    };
+    assert!(matches!(
+        chain.body_else.map(|body| body.kind),
+        Some(BlockKind::Inline(body)) if matches!(*body, Expr::Branch(_))
+    ));
+
+    assert!(matches!(expr("for in x {}"), Err(nom::Err::Failure(_))));
+}
+
+#[test]
+fn jumps() {
+    let Expr::While(ExprWhile { body: block, .. }) = expr(
+        "while go {\n\
+             if !valid(x) return false;\n\
+             use(if a -> 1 else return false);\n\
+             while c { if d continue; break }\n\
+             total\n\
+         }",
+    )
+    .unwrap() else {
+        panic!("expected a while loop");
+    };
+    let stmts = block.exprs().collect::<Vec<_>>();
+    assert_eq!(stmts.len(), 4);
+    assert!(matches!(stmts[0], Expr::Branch(branch) if matches!(
+        &branch.body.kind,
+        BlockKind::Inline(ret) if matches!(&**ret, Expr::Return(ret) if ret.value.is_some())
+    )));
+    assert!(!block.diverges());
+
+    let Expr::Break(brk) = expr("break @outer (a, b)").unwrap() else {
+        panic!("expected a break");
+    };
+    assert_eq!(&**brk.label.unwrap().name.0, "outer");
+    assert!(matches!(brk.value.as_deref(), Some(Expr::Tuple(_))));
+    assert!(matches!(expr("return").unwrap(), Expr::Return(ret) if ret.value.is_none()));
+
+    let cases = [
+        ("loop { x }", true),
+        ("loop { break }", false),
+        ("loop { while a { break } }", true),
+        ("loop @outer { while a { break @outer } }", false),
+        ("loop @outer { loop { break @inner } }", true),
+        ("if a return b else continue", true),
+        ("if a return b", false),
+        ("f(x, return y)", true),
+    ];
+    for (src, diverges) in cases {
+        assert_eq!(expr(src).unwrap().diverges(), diverges, "{src}");
+    }
+}
+
+#[test]
+fn defers_run_on_every_exit() {
+    let Expr::While(ExprWhile { mut body, .. }) = expr(
+        "while go {\n\
+             defer a();\n\
+             if x return y;\n\
+             defer b();\n\
+             loop { if z break; continue }\n\
+             if w break;\n\
+             c\n\
+         }",
+    )
+    .unwrap() else {
+        panic!("expected a while loop");
+    };
+    body.lower_defers();
+
+    // the called functions and the jumps in order
+    fn flow(expr: &Expr, out: &mut Vec<String>) {
+        match expr {
+            Expr::Call(call) => out.push(show(&call.base)),
+            Expr::Return(_) => out.push("return".into()),
+            Expr::Break(_) => out.push("break".into()),
+            Expr::Continue(_) => out.push("continue".into()),
+            Expr::Defer(_) => panic!("defer was not lowered"),
+            _ => {}
+        }
+        expr.for_each_child(&mut |child| flow(child, out));
+    }
+    let mut out = vec![];
+    body.exprs().for_each(|expr| flow(expr, &mut out));
+    assert_eq!(
+        out,
+        ["a", "return", "break", "continue", "b", "a", "break", "b", "a"]
+    );
+    assert!(matches!(body.exprs().last(), Some(Expr::Ident(_))));
+}
+
+#[test]
+fn defers_are_statements() {
+    let Expr::While(ExprWhile { mut body, .. }) = expr("while go { a(); defer b() }").unwrap()
+    else {
+        panic!("expected a while loop");
+    };
+    body.lower_defers();
+    let BlockKind::Many { stmts, returning } = &body.kind else {
+        panic!("expected a block");
+    };
+    let calls = stmts
+        .iter()
+        .map(|stmt| match &stmt.expr {
+            Expr::Call(call) => show(&call.base),
+            expr => panic!("unexpected statement {expr:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(calls, ["a", "b"]);
+    assert!(returning.is_none());
+
+    let cases = [
+        "while go { a; if c -> defer d }",
+        "while go { x := defer y; z }",
+        "f(defer y)",
+        "a + defer b",
+    ];
+    for src in cases {
+        let Err(nom::Err::Failure(err)) = expr(src) else {
+            panic!("`{src}` should not parse");
+        };
+        assert!(
+            format!("{err:?}").contains("statement of a `{ ... }` block"),
+            "{src}"
+        );
+    }
+
+    // a bare `{ ... }` is a scope of its own, its defers run when it is left
+    let Expr::Block(mut scope) = expr("{ defer a(); b() }").unwrap() else {
+        panic!("expected a block");
+    };
+    scope.lower_defers();
+    let BlockKind::Many { stmts, returning } = &scope.kind else {
+        panic!("expected a block");
+    };
+    assert_eq!(stmts.len(), 2);
+    assert!(matches!(returning.as_deref(), Some(Expr::Ident(_))));
+}
+
+#[test]
+fn mutability() {
+    let Expr::Define(define) = expr("mut buf := &mut other").unwrap() else {
+        panic!("expected a definition");
+    };
+    assert!(matches!(&define.pattern, Pattern::Binding(bind) if bind.mutability.is_some()));
+    assert!(matches!(
+        &*define.value,
+        Expr::Unary(unary) if matches!(unary.op, UnOp::RefMut(_, _))
+    ));
+    assert!(
+        matches!(expr("is := stdin()").unwrap(), Expr::Define(def) if matches!(&def.pattern, Pattern::Binding(bind) if bind.mutability.is_none()))
+    );
+
+    // `*a = b` assigns to the place behind `a`
+    let Expr::Assign(assign) = expr("*a = b").unwrap() else {
+        panic!("expected an assignment");
+    };
+    assert!(matches!(&*assign.assignee, Expr::Unary(_)));
+
+    let module = module("read :: func(mut is *mut stream, buf *[]u8) usize { x }");
+    let [Item::Definer(read)] = module.items.as_slice() else {
+        unreachable!()
+    };
+    let NamedKind::Func(func) = &read.kind else {
+        unreachable!()
+    };
+    let args = &func.func.args;
+    assert!(args[0].mutability.is_some() && args[1].mutability.is_none());
+    assert!(matches!(&args[0].ty, ItemTy::Pointer(ptr) if ptr.mutability.is_some()));
+    assert!(matches!(&args[1].ty, ItemTy::Pointer(ptr) if ptr.mutability.is_none()));
+}
+
+#[test]
+fn patterns() {
+    let Expr::While(ExprWhile { cond, .. }) =
+        expr("while option:some(c) := it.next() { c }").unwrap()
+    else {
+        panic!("expected a while loop");
+    };
+    let Expr::Define(define) = *cond else {
+        panic!("expected a pattern condition");
+    };
+    assert!(!define.pattern.is_irrefutable());
+
+    let Expr::Match(mt) = expr(
+        "match shape {\n\
+             shape:circle(r) -> r,\n\
+             shape:rect(vec2 { x = 0, y }, _) -> y,\n\
+             shape:point { 0 },\n\
+             (mut a, 'b', \"c\") -> a,\n\
+             other -> return other,\n\
+         }",
+    )
+    .unwrap() else {
+        panic!("expected a match");
+    };
+    let kinds = mt
+        .arms
+        .iter()
+        .map(|arm| match &arm.pattern {
+            Pattern::Variant(variant) => format!("variant {}", variant.path.path[1].0),
+            Pattern::Tuple(tuple) => format!("tuple {}", tuple.fields.len()),
+            Pattern::Binding(bind) => format!("binding {}", bind.name.0),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            "variant circle",
+            "variant rect",
+            "variant point",
+            "tuple 3",
+            "binding other"
+        ]
+    );
+
+    let Pattern::Variant(rect) = &mt.arms[1].pattern else {
+        unreachable!()
+    };
+    let fields = &rect.fields.as_ref().unwrap().fields;
+    assert!(matches!(&fields[0], Pattern::Struct(st) if st.fields.len() == 2));
+    let mut names = vec![];
+    mt.arms[1]
+        .pattern
+        .for_each_binding(&mut |bind| names.push(bind.name.0.to_string()));
+    assert_eq!(names, ["y"]);
+    assert!(mt.arms[4].pattern.is_irrefutable());
+    assert!(!Expr::Match(mt).needs_termination());
+
+    // braces are only fields of a struct pattern if a body follows them
+    let Expr::Match(mt) = expr(
+        "match o {
+             option:none { d },
+             vec2 { x } { x },
+             x { y }
+         }",
+    )
+    .unwrap() else {
+        panic!("expected a match");
+    };
+    assert!(matches!(&mt.arms[0].pattern, Pattern::Variant(none) if none.fields.is_none()));
+    assert!(matches!(&mt.arms[1].pattern, Pattern::Struct(st) if st.fields.len() == 1));
+    assert!(matches!(&mt.arms[2].pattern, Pattern::Binding(bind) if &**bind.name.0 == "x"));
+    assert!(mt.arms.iter().all(|arm| arm.body.exprs().count() == 1));
+}
+
+#[test]
+fn casts() {
+    let cases = [
+        ("a as u8", "(as a u8)"),
+        ("a + b as u32 * c", "(Add a (Mul (as b u32) c))"),
+        ("*p as usize", "(as (* p) usize)"),
+        ("&x as *mut u8 as *u16", "(as (as (& x) *mut u8) *u16)"),
+        ("a as i64 < b", "(Lt (as a i64) b)"),
+    ];
+    for (src, tree) in cases {
+        assert_eq!(show(&expr(src).unwrap()), tree, "{src}");
+    }
+
+    assert!(matches!(expr("a as"), Err(nom::Err::Failure(_))));
+    assert!(expr("as := 1").is_err());
+}
+
+#[test]
+fn operator_precedence() {
+    let cases = [
+        ("a - b - c", "(Sub (Sub a b) c)"),
+        ("a / b * c % d", "(Mod (Mul (Div a b) c) d)"),
+        ("a || b && c", "(OrL a (AndL b c))"),
+        ("a && b || c && d", "(OrL (AndL a b) (AndL c d))"),
+        ("a | b ^ c & d", "(Or a (Xor b (And c d)))"),
+        ("a & b << c + d", "(And a (Shl b (Add c d)))"),
+        ("a == b | c", "(Eq a (Or b c))"),
+        ("a < b && c >= d", "(AndL (Lt a b) (Ge c d))"),
+        ("a * *b", "(Mul a (* b))"),
+        ("*a * *b", "(Mul (* a) (* b))"),
+        ("!a && b", "(AndL (! a) b)"),
+        ("-a * ~b", "(Mul (- a) (~ b))"),
+        ("-a.b", "(- a.b)"),
+        ("a - -b", "(Sub a (- b))"),
+        ("a -1 * b", "(Sub a (Mul 1 b))"),
+        ("a * -1", "(Mul a -1)"),
+        ("~a & b", "(And (~ a) b)"),
+        ("a + b * c - d", "(Sub (Add a (Mul b c)) d)"),
+    ];
+    for (src, tree) in cases {
+        assert_eq!(show(&expr(src).unwrap()), tree, "{src}");
+    }
+
+    // lone operands used to be parsed twice on every level of nesting
+    let nested = format!("{}a{}", "f(".repeat(24), ")".repeat(24));
+    assert!(matches!(expr(&nested), Ok(Expr::Call(_))));
+
+    // long chains used to be rebuilt quadratically
+    let chain = (0..2000).map(|n| format!("a{n}")).collect::<Vec<_>>();
+    let mut expr = expr(&chain.join(" + ")).unwrap();
+    let mut depth = 0;
+    while let Expr::Binary(bin) = expr {
+        assert!(matches!(*bin.right, Expr::Ident(_)));
+        expr = *bin.left;
+        depth += 1;
+    }
+    assert_eq!(depth, 1999);
+}
+
+#[test]
+fn modules() {
+    let module = module(
+        "{iter, utf8_chk} :: mod;\n\
+         { iter } :: super;\n\
+         { a, b:c } :: super:super:d;\n\
+         { e } :: self:f\n\
+         g :: struct { x self:h, y :i }",
+    );
+    let [Item::Modules(mods), Item::Import(sup), Item::Import(nested), Item::Import(current), Item::Definer(g)] =
+        module.items.as_slice()
+    else {
+        panic!("unexpected items {:?}", module.items);
+    };
+    let names = mods
+        .modules
+        .iter()
+        .map(|m| m.0.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["iter", "utf8_chk"]);
+
+    let depth = |path: &ExprPath| path.root.as_ref().map(PathRoot::depth);
+    assert_eq!(depth(&sup.from), Some(Some(1)));
+    assert!(sup.from.path.is_empty());
+    assert_eq!(depth(&nested.from), Some(Some(2)));
+    assert_eq!(nested.from.path.len(), 1);
+    assert_eq!(depth(&current.from), Some(Some(0)));
+
+    let NamedKind::Type(ty) = &g.kind else {
+        unreachable!()
+    };
+    let ItemTy::Struct(st) = &ty.ty else {
+        unreachable!()
+    };
+    let roots = st
+        .fields
+        .iter()
+        .map(|field| match &field.ty {
+            ItemTy::Referred(path) => depth(path),
+            _ => unreachable!(),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(roots, [Some(Some(0)), Some(None)]);
+
+    // `self` on its own is still a name
+    assert!(matches!(expr("self").unwrap(), Expr::Ident(_)));
+    assert!(matches!(expr("self:a").unwrap(), Expr::Path(_)));
+    assert!(expr("super").is_err());
+}
+
+#[test]
+fn values() {
+    let module = module(
+        "some_test :: option(str) := test(\"test\");\n\
+         counter :: mut usize := 0;\n\
+         answer :: := 42;",
+    );
+    let values = module
+        .items
+        .iter()
+        .map(|item| match item {
+            Item::Definer(ItemNamed {
+                kind: NamedKind::Value(value),
+                ..
+            }) => (value.mutability.is_some(), value.ty.is_some()),
+            item => panic!("unexpected item {item:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(values, [(false, true), (true, true), (false, false)]);
+
+    let file = Span::new(RcStr::new("x :: usize := ;".to_string(), Origin::Unknown));
+    let (_, tokens) = tokenize(file.clone()).unwrap();
+    let name = Ident::new(file.clone());
+    assert!(matches!(
+        parse_module(TokenSpan::new(file, Rc::from(tokens)), name),
+        Err(nom::Err::Failure(_))
+    ));
+}
+
+#[test]
+fn compound_assignments() {
+    let ops = [
+        ("=", AssignOp::Assign),
+        ("+=", AssignOp::Add),
+        ("-=", AssignOp::Sub),
+        ("*=", AssignOp::Mul),
+        ("/=", AssignOp::Div),
+        ("%=", AssignOp::Mod),
+        ("&=", AssignOp::And),
+        ("|=", AssignOp::Or),
+        ("^=", AssignOp::Xor),
+        ("<<=", AssignOp::Shl),
+        (">>=", AssignOp::Shr),
+    ];
+    for (src, op) in ops {
+        let Expr::Assign(assign) = expr(&format!("self.offset {src} a + 1")).unwrap() else {
+            panic!("expected an assignment for {src}");
+        };
+        assert_eq!(assign.op, op);
+        assert!(matches!(&*assign.assignee, Expr::Field(_)));
+        assert_eq!(show(&assign.value), "(Add a 1)");
+    }
+
+    let Expr::Assign(assign) = expr("*p <<= 2").unwrap() else {
+        panic!("expected an assignment");
+    };
+    assert_eq!(show(&assign.assignee), "(* p)");
+    assert!(matches!(assign.op.binary(), Some(BiOp::Shl)));
+}
+
+#[test]
+fn samples_parse() {
+    samples(&[
+        "types.w",
+        "mod_test/bubble_sort.w",
+        "mod_test/utf8_chk.w",
+        "mod_test/iter.w",
+        "mod_test/vec.w",
+        "mod_test/trait.w",
+        "mod_test/main.w",
+        "test1.w",
+    ]);
+
+    let md = module("main :: func() {\n    // nothing yet\n}");
+    let [Item::Definer(ItemNamed {
+        kind: NamedKind::Func(main),
+        ..
+    })] = md.items.as_slice()
+    else {
+        panic!("expected a function");
+    };
+    assert_eq!(main.body.exprs().count(), 0);
+}
@@ -0,0 +1,17 @@
+use crate::expr::path::{parse_path, ExprPath};
+use crate::types::tuple::{parse_ty_tuple, TyTuple};
+use crate::{ParResult, TokenSpan};
+
+/// A generic type with its parameters filled in, `option(vec3)`
+#[derive(Debug, Clone)]
+pub struct TyApplied {
+    pub path: ExprPath,
+    pub args: TyTuple,
+}
+
+pub fn parse_ty_applied(i: TokenSpan) -> ParResult<TyApplied> {
+    let (i, path) = parse_path(i)?;
+    let (i, args) = parse_ty_tuple(i)?;
+
+    Ok((i, TyApplied { path, args }))
+}
@@ -10,24 +10,36 @@ use w_tokenize::{Number, Span};
 pub struct TyArray {
     pub span: Span,
     pub ty: Box<ItemTy>,
-    pub size: Option<Box<Number>>,
+    /// `None` for `[]T`
+    pub size: Option<ArraySize>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArraySize {
+    /// `[4]T`
+    Sized(Box<Number>),
+    /// `[?]T`, the length is only known at runtime
+    Unsized(Span),
 }
 
 pub fn parse_ty_array(oi: TokenSpan) -> ParResult<TyArray> {
     let (i, array) =
         tag!(Kind::Array(_), Token { kind: Kind::Array(vals), .. } => vals)(oi.clone())?;
-    let array = TokenSpan::new(i.file.clone(), array);
+    let array = TokenSpan::without_docs(i.file.clone(), array);
 
     let (_, size) = all_consuming(opt(alt((
+        map(tag!(Kind::Question), |span| {
+            Either::Left(ArraySize::Unsized(span))
+        }),
         map(
             tag!(Kind::Number(_), Token { kind: Kind::Number(n), .. } => n),
-            Either::Left,
+            |n| Either::Left(ArraySize::Sized(n)),
         ),
         map(recognize(parse_expression), Either::Right),
     ))))(array)?;
     let size = match size {
         None => None,
-        Some(Either::Left(n)) => Some(n),
+        Some(Either::Left(size)) => Some(size),
         Some(Either::Right(expr)) => {
             return Err(nom::Err::Failure(ErrorChain::from(Error::new(
                 expr,
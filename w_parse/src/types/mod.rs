@@ -1,3 +1,4 @@
+pub mod applied;
 pub mod array;
 pub mod r#enum;
 pub mod func;
@@ -12,6 +13,7 @@ use nom::branch::alt;
 use nom::combinator::map;
 
 use crate::expr::path::{parse_path, ExprPath};
+use crate::types::applied::{parse_ty_applied, TyApplied};
 use crate::types::array::{parse_ty_array, TyArray};
 use crate::types::func::{parse_ty_func, TyFunc};
 use crate::types::never::{parse_ty_never, TyNever};
@@ -23,6 +25,7 @@ use crate::types::tuple::{parse_ty_tuple, TyTuple};
 #[derive(Debug, Clone)]
 pub enum ItemTy {
     Referred(ExprPath),
+    Applied(TyApplied),
     Struct(TyStruct),
     Enum(TyEnum),
     Tuple(TyTuple),
@@ -34,6 +37,7 @@ pub enum ItemTy {
 
 pub fn parse_type(i: TokenSpan) -> ParResult<ItemTy> {
     alt((
+        map(parse_ty_applied, ItemTy::Applied),
         map(parse_path, ItemTy::Referred),
         map(parse_ty_struct, ItemTy::Struct),
         map(parse_ty_enum, ItemTy::Enum),
@@ -1,19 +1,24 @@
-use crate::{parse_type, tag, ItemTy, ParResult, TokenSpan};
+use crate::{parse_keyword, parse_type, tag, ItemTy, ParResult, TokenSpan};
+use nom::combinator::opt;
 use w_tokenize::Span;
 
 #[derive(Debug, Clone)]
+/// `*T` or `*mut T`, only the latter allows writing through the pointer
 pub struct TyPtr {
     pub span_ptr: Span,
+    pub mutability: Option<Span>,
     pub ty: Box<ItemTy>,
 }
 
 pub fn parse_ty_ptr(i: TokenSpan) -> ParResult<TyPtr> {
     let (i, span_ptr) = tag!(Kind::Mul)(i)?;
+    let (i, mutability) = opt(parse_keyword("mut"))(i)?;
     let (i, ty) = parse_type(i)?;
     Ok((
         i,
         TyPtr {
             span_ptr,
+            mutability,
             ty: Box::new(ty),
         },
     ))
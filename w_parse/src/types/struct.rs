@@ -1,13 +1,23 @@
 use crate::expr::parse_many0;
-use crate::util::{parse_name_ty_pair, NameTyPair};
-use crate::{parse_keyword, tag, ParResult, TokenSpan};
+use crate::item::attr::{parse_attributes, Attribute};
+use crate::item::doc::{parse_docs, Docs};
+use crate::{parse_keyword, parse_name, parse_type, tag, Ident, ItemTy, ParResult, TokenSpan};
 use nom::combinator::all_consuming;
+use nom::sequence::terminated;
 use w_tokenize::Span;
 
 #[derive(Debug, Clone)]
 pub struct TyStruct {
     pub span_struct: Span,
-    pub fields: Vec<NameTyPair>,
+    pub fields: Vec<TyStructField>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TyStructField {
+    pub docs: Docs,
+    pub attrs: Vec<Attribute>,
+    pub name: Ident,
+    pub ty: ItemTy,
 }
 
 pub fn parse_ty_struct(i: TokenSpan) -> ParResult<TyStruct> {
@@ -16,7 +26,7 @@ pub fn parse_ty_struct(i: TokenSpan) -> ParResult<TyStruct> {
     let (i, block) = tag!(Kind::Block(_), Token { kind: Kind::Block(vals), .. } => vals)(i)?;
     let block = TokenSpan::new(i.file.clone(), block);
 
-    let (_, fields) = all_consuming(parse_many0(parse_name_ty_pair))(block)?;
+    let (_, fields) = all_consuming(terminated(parse_many0(parse_field), parse_docs))(block)?;
 
     Ok((
         i,
@@ -26,3 +36,20 @@ pub fn parse_ty_struct(i: TokenSpan) -> ParResult<TyStruct> {
         },
     ))
 }
+
+fn parse_field(i: TokenSpan) -> ParResult<TyStructField> {
+    let (i, docs) = parse_docs(i)?;
+    let (i, attrs) = parse_attributes(i)?;
+    let (i, name) = parse_name(i)?;
+    let (i, ty) = parse_type(i)?;
+
+    Ok((
+        i,
+        TyStructField {
+            docs,
+            attrs,
+            name,
+            ty,
+        },
+    ))
+}
@@ -1,14 +1,26 @@
-use crate::{parse_name, parse_type, Ident, ItemTy, ParResult, TokenSpan};
+use crate::{parse_keyword, parse_name, parse_type, Ident, ItemTy, ParResult, TokenSpan};
+use nom::combinator::opt;
+use w_tokenize::Span;
 
 #[derive(Debug, Clone)]
 pub struct NameTyPair {
+    /// `mut name ty`
+    pub mutability: Option<Span>,
     pub name: Ident,
     pub ty: ItemTy,
 }
 
 pub fn parse_name_ty_pair(i: TokenSpan) -> ParResult<NameTyPair> {
+    let (i, mutability) = opt(parse_keyword("mut"))(i)?;
     let (i, name) = parse_name(i)?;
     let (i, ty) = parse_type(i)?;
 
-    Ok((i, NameTyPair { name, ty }))
+    Ok((
+        i,
+        NameTyPair {
+            mutability,
+            name,
+            ty,
+        },
+    ))
 }
@@ -1,6 +1,11 @@
+mod lines;
+mod source;
 #[cfg(test)]
 mod tests;
 
+pub use crate::lines::{expand_tabs, Column, LineCol, LineIndex, TAB_WIDTH};
+pub use crate::source::{Location, SourceFile, SourceId, SourceMap};
+
 use nom::{
     AsBytes, Compare, CompareResult, ExtendInto, FindToken, InputIter, InputLength, InputTake,
     Needed, Offset, Slice, UnspecializedInput,
@@ -11,18 +16,31 @@ use std::fmt::{Debug, Display, Formatter};
 use std::ops::{Deref, Range, RangeBounds};
 use std::sync::Arc;
 
-#[derive(Clone)]
+/// Where the text of a [`RcStr`] comes from, see [`SourceMap`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum Origin {
     Unknown,
+    /// A file on disk
+    File(SourceId),
+    /// Text which only exists in memory
+    Virtual(SourceId),
+    /// Code produced by the compiler
+    Generated(SourceId),
 }
 
 #[derive(Clone)]
 pub struct RcStr {
-    str: Arc<String>,
+    str: Arc<Text>,
     frag: Range<usize>,
     origin: Origin,
 }
 
+/// The string shared by all fragments together with its line index
+struct Text {
+    str: String,
+    lines: LineIndex,
+}
+
 pub struct RcChars(RcCharsIndices);
 pub struct RcCharsIndices(RcStr, usize);
 
@@ -73,21 +91,77 @@ impl Iterator for RcCharsIndices {
 
 impl RcStr {
     pub fn new(str: String, origin: Origin) -> Self {
-        let str = Arc::new(str);
+        let str = Arc::new(Text {
+            lines: LineIndex::new(&str),
+            str,
+        });
 
         Self {
             origin,
-            frag: 0..str.len(),
+            frag: 0..str.str.len(),
             str,
         }
     }
+
+    pub fn origin(&self) -> &Origin {
+        &self.origin
+    }
+
+    /// Byte range of this fragment in the whole string
+    pub fn range(&self) -> Range<usize> {
+        self.frag.clone()
+    }
+
+    /// The whole string this fragment was taken from
+    pub fn get_whole(&self) -> &str {
+        &self.str.str
+    }
+
+    pub fn line_index(&self) -> &LineIndex {
+        &self.str.lines
+    }
+
+    /// Position of the start of this fragment in the whole string
+    pub fn line_col(&self) -> LineCol {
+        self.line_index()
+            .line_col(self.get_whole(), self.frag.start)
+    }
+
+    /// Position of the end of this fragment in the whole string
+    pub fn line_col_end(&self) -> LineCol {
+        self.line_index().line_col(self.get_whole(), self.frag.end)
+    }
+
+    /// Byte offset of a position in the whole string, see [`LineIndex::offset`]
+    pub fn offset_at(&self, line: usize, col: Column) -> Option<usize> {
+        self.line_index().offset(self.get_whole(), line, col)
+    }
+
+    /// Display columns this fragment covers on its first line, at least one wide,
+    /// used to underline it below the line
+    pub fn marker(&self) -> Range<usize> {
+        let (index, whole) = (self.line_index(), self.get_whole());
+        let start = self.line_col();
+        let end = match index.line_of(self.frag.end) == start.line {
+            true => self.frag.end,
+            false => index.line_range(whole, start.line).unwrap().end,
+        };
+        let end = index.line_col(whole, end).width;
+        start.width..end.max(start.width + 1)
+    }
+
+    /// The text of a 0-based line of the whole string, without its line break
+    pub fn line(&self, line: usize) -> Option<&str> {
+        let range = self.line_index().line_range(self.get_whole(), line)?;
+        Some(&self.get_whole()[range])
+    }
 }
 
 impl Deref for RcStr {
     type Target = str;
 
     fn deref(&self) -> &Self::Target {
-        &self.str[self.frag.clone()]
+        &self.str.str[self.frag.clone()]
     }
 }
 
@@ -162,7 +236,7 @@ impl InputTake for RcStr {
         assert!(count <= self.input_len());
 
         Self {
-            origin: self.origin.clone(),
+            origin: self.origin,
             str: self.str.clone(),
             frag: self.frag.start..self.frag.start + count,
         }
@@ -174,12 +248,12 @@ impl InputTake for RcStr {
 
         (
             Self {
-                origin: self.origin.clone(),
+                origin: self.origin,
                 str: self.str.clone(),
                 frag: self.frag.start..split,
             },
             Self {
-                origin: self.origin.clone(),
+                origin: self.origin,
                 str: self.str.clone(),
                 frag: split..self.frag.end,
             },
@@ -239,7 +313,7 @@ where
         assert!(self.frag.start + end <= self.frag.end);
 
         Self {
-            origin: self.origin.clone(),
+            origin: self.origin,
             str: self.str.clone(),
             frag: self.frag.start + start..self.frag.start + end,
         }
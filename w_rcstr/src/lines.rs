@@ -0,0 +1,166 @@
+use unicode_width::UnicodeWidthChar;
+
+/// Columns a tab advances to the next multiple of
+pub const TAB_WIDTH: usize = 4;
+
+/// Byte offsets of the start of every line, built once for the string behind a [`RcStr`](crate::RcStr)
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+    len: usize,
+}
+
+/// A position in a text, everything is 0-based
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LineCol {
+    pub line: usize,
+    /// Bytes since the start of the line
+    pub utf8: usize,
+    /// Chars since the start of the line
+    pub chars: usize,
+    /// UTF-16 code units since the start of the line, as used by LSP
+    pub utf16: usize,
+    /// Columns in a terminal, tabs expand to the next multiple of [`TAB_WIDTH`]
+    pub width: usize,
+}
+
+/// The unit of a column passed to [`LineIndex::offset`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Column {
+    Utf8(usize),
+    Chars(usize),
+    Utf16(usize),
+    Width(usize),
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+
+        Self {
+            line_starts,
+            len: text.len(),
+        }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Byte range of `line` without its line break
+    pub fn line_range(&self, text: &str, line: usize) -> Option<std::ops::Range<usize>> {
+        let start = *self.line_starts.get(line)?;
+        let end = self
+            .line_starts
+            .get(line + 1)
+            .map_or(self.len, |&next| next - 1);
+        let end = match text[start..end].ends_with('\r') {
+            true => end - 1,
+            false => end,
+        };
+        Some(start..end)
+    }
+
+    /// The line containing the byte `offset`
+    pub fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// Position of the byte `offset` in `text`, which has to be the text the index was built for
+    pub fn line_col(&self, text: &str, offset: usize) -> LineCol {
+        assert!(offset <= self.len, "offset out of bounds");
+
+        let line = self.line_of(offset);
+        let start = self.line_starts[line];
+        let prefix = &text[start..offset];
+
+        // most lines are plain ascii without tabs, which makes every column the byte count
+        if prefix.bytes().all(|b| b.is_ascii() && b != b'\t') {
+            let col = prefix.len();
+            return LineCol {
+                line,
+                utf8: col,
+                chars: col,
+                utf16: col,
+                width: col,
+            };
+        }
+
+        let mut pos = LineCol {
+            line,
+            utf8: prefix.len(),
+            chars: 0,
+            utf16: 0,
+            width: 0,
+        };
+        for c in prefix.chars() {
+            pos.chars += 1;
+            pos.utf16 += c.len_utf16();
+            pos.width = advance(pos.width, c);
+        }
+        pos
+    }
+
+    /// Byte offset of `col` in `line`, `None` if the line does not exist
+    ///
+    /// Columns past the end of the line are clamped to its end,
+    /// columns in the middle of a char resolve to the start of that char.
+    pub fn offset(&self, text: &str, line: usize, col: Column) -> Option<usize> {
+        let range = self.line_range(text, line)?;
+        let line_text = &text[range.clone()];
+
+        let mut pos = LineCol {
+            line,
+            utf8: 0,
+            chars: 0,
+            utf16: 0,
+            width: 0,
+        };
+        for c in line_text.chars() {
+            let next = LineCol {
+                line,
+                utf8: pos.utf8 + c.len_utf8(),
+                chars: pos.chars + 1,
+                utf16: pos.utf16 + c.len_utf16(),
+                width: advance(pos.width, c),
+            };
+            let past = match col {
+                Column::Utf8(col) => next.utf8 > col,
+                Column::Chars(col) => next.chars > col,
+                Column::Utf16(col) => next.utf16 > col,
+                Column::Width(col) => next.width > col,
+            };
+            if past {
+                break;
+            }
+            pos = next;
+        }
+
+        Some(range.start + pos.utf8)
+    }
+}
+
+/// Display column after printing `c` at `col`
+fn advance(col: usize, c: char) -> usize {
+    match c {
+        '\t' => (col / TAB_WIDTH + 1) * TAB_WIDTH,
+        c => col + c.width().unwrap_or(0),
+    }
+}
+
+/// Replaces tabs with spaces so the text lines up with [`LineCol::width`]
+pub fn expand_tabs(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut col = 0;
+    for c in line.chars() {
+        let next = advance(col, c);
+        match c {
+            '\t' => out.extend(std::iter::repeat_n(' ', next - col)),
+            c => out.push(c),
+        }
+        col = next;
+    }
+    out
+}
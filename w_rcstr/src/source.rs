@@ -0,0 +1,139 @@
+use crate::{Origin, RcStr};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// Index of a file in a [`SourceMap`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SourceId(u32);
+
+/// Every source text the compiler works with, files as well as in-memory and generated code
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+pub struct SourceFile {
+    id: SourceId,
+    name: String,
+    path: Option<PathBuf>,
+    text: RcStr,
+}
+
+/// A resolved position, displayed as `path:line:col`
+#[derive(Copy, Clone)]
+pub struct Location<'a> {
+    pub file: &'a SourceFile,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, counted in chars
+    pub col: usize,
+}
+
+impl SourceMap {
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> io::Result<RcStr> {
+        let text = fs::read_to_string(path.as_ref())?;
+        Ok(self.add_file(path.as_ref(), text))
+    }
+
+    /// Registers the contents of a file on disk
+    pub fn add_file(&mut self, path: impl Into<PathBuf>, text: String) -> RcStr {
+        let path = path.into();
+        let name = path.display().to_string();
+        self.add(Origin::File, name, Some(path), text)
+    }
+
+    /// Registers text which only exists in memory, like an editor buffer
+    pub fn add_virtual(&mut self, name: impl Into<String>, text: String) -> RcStr {
+        self.add(Origin::Virtual, name.into(), None, text)
+    }
+
+    /// Registers code produced by the compiler itself
+    pub fn add_generated(&mut self, name: impl Into<String>, text: String) -> RcStr {
+        self.add(Origin::Generated, format!("<{}>", name.into()), None, text)
+    }
+
+    fn add(
+        &mut self,
+        origin: fn(SourceId) -> Origin,
+        name: String,
+        path: Option<PathBuf>,
+        text: String,
+    ) -> RcStr {
+        let id = SourceId(self.files.len() as u32);
+        let text = RcStr::new(text, origin(id));
+
+        self.files.push(SourceFile {
+            id,
+            name,
+            path,
+            text: text.clone(),
+        });
+        text
+    }
+
+    pub fn get(&self, id: SourceId) -> &SourceFile {
+        &self.files[id.0 as usize]
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &SourceFile> {
+        self.files.iter()
+    }
+
+    /// The file `str` was taken from, `None` for strings not created by this map
+    pub fn source_of(&self, str: &RcStr) -> Option<&SourceFile> {
+        str.origin()
+            .source()
+            .and_then(|id| self.files.get(id.0 as usize))
+    }
+
+    /// Resolves the start of `str`, which is usually the fragment of a span
+    pub fn locate(&self, str: &RcStr) -> Option<Location<'_>> {
+        let file = self.source_of(str)?;
+        let (line, col) = file.line_col(str.range().start);
+        Some(Location { file, line, col })
+    }
+}
+
+impl SourceFile {
+    pub fn id(&self) -> SourceId {
+        self.id
+    }
+
+    /// The path for files on disk, otherwise the name it was registered with
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn text(&self) -> &RcStr {
+        &self.text
+    }
+
+    /// 1-based line and char column of a byte offset
+    pub fn line_col(&self, offset: usize) -> (usize, usize) {
+        let pos = self
+            .text
+            .line_index()
+            .line_col(self.text.get_whole(), offset);
+        (pos.line + 1, pos.chars + 1)
+    }
+}
+
+impl Display for Location<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file.name, self.line, self.col)
+    }
+}
+
+impl Origin {
+    pub fn source(&self) -> Option<SourceId> {
+        match self {
+            Origin::Unknown => None,
+            Origin::File(id) | Origin::Virtual(id) | Origin::Generated(id) => Some(*id),
+        }
+    }
+}
@@ -49,3 +49,56 @@ fn test_rcstr_slice_index() {
     let a = s.slice_index(7);
     assert_eq!(a, Ok(7));
 }
+
+#[test]
+fn test_source_map_locate() {
+    let mut map = SourceMap::default();
+    let main = map.add_file("mod_test/main.w", "a :: b;\nc :: d;\n".to_string());
+    let other = map.add_virtual("<buffer>", "x\n  äy".to_string());
+    let gen = map.add_generated("desugar", "z".to_string());
+
+    assert_eq!(
+        *main.origin(),
+        Origin::File(map.files().next().unwrap().id())
+    );
+    assert!(matches!(other.origin(), Origin::Virtual(_)));
+
+    let d = main.slice(13..14);
+    assert_eq!(d, "d");
+    assert_eq!(map.locate(&d).unwrap().to_string(), "mod_test/main.w:2:6");
+
+    let y = other.slice(other.len() - 1..);
+    assert_eq!(map.locate(&y).unwrap().to_string(), "<buffer>:2:4");
+    assert_eq!(map.locate(&gen).unwrap().to_string(), "<desugar>:1:1");
+
+    assert!(map.locate(&RcStr::from("unregistered")).is_none());
+}
+
+#[test]
+fn test_line_index_columns() {
+    let s = RcStr::from("a\n\tä😀x\r\nlast");
+    let x = s.slice(9..10);
+    assert_eq!(x, "x");
+
+    assert_eq!(
+        x.line_col(),
+        LineCol {
+            line: 1,
+            utf8: 7,
+            chars: 3,
+            utf16: 4,
+            width: 7,
+        }
+    );
+    assert_eq!(x.marker(), 7..8);
+    assert_eq!(s.line(1), Some("\tä😀x"));
+    assert_eq!(s.line(3), None);
+
+    assert_eq!(s.offset_at(1, Column::Utf8(7)), Some(9));
+    assert_eq!(s.offset_at(1, Column::Utf16(4)), Some(9));
+    assert_eq!(s.offset_at(1, Column::Width(7)), Some(9));
+    // the middle of the emoji resolves to its start
+    assert_eq!(s.offset_at(1, Column::Width(6)), Some(5));
+    assert_eq!(s.offset_at(2, Column::Chars(99)), Some(16));
+    assert_eq!(expand_tabs("\tä\tb"), "    ä   b");
+}
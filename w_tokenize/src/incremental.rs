@@ -0,0 +1,217 @@
+use crate::trivia::{attach_group, Trivia};
+use crate::{
+    skip_whitespace, token_list, tokenize, tokenize_lossless, Kind, Span, TokResult, Token,
+};
+use nom::Slice;
+use std::ops::Range;
+use std::rc::Rc;
+
+/// A change to the source text
+#[derive(Debug, Clone)]
+pub struct Edit {
+    /// Byte range of the old text which got replaced
+    pub range: Range<usize>,
+    /// Length in bytes of the text inserted in its place
+    pub new_len: usize,
+}
+
+impl Edit {
+    fn delta(&self) -> isize {
+        self.new_len as isize - self.range.len() as isize
+    }
+}
+
+/// Updates a token tree from [`tokenize`] or [`tokenize_lossless`] after `edit` was applied,
+/// `file` being the edited text.
+///
+/// Only the contents of the smallest group enclosing the edit are tokenized again.
+/// Every other token is moved to the new text in place, spans behind the edit shift by its length.
+/// Groups keep their `Rc<[Token]>` unless another tree still holds it, then it is copied once.
+/// Trivia is carried over and recomputed inside the tokenized group.
+/// If the edit changes the structure of the group, e.g. by removing its closing delimiter,
+/// the parent group is tried instead, up to tokenizing the entire file.
+pub fn retokenize(mut tokens: Vec<Token>, file: Span, edit: &Edit) -> TokResult<Vec<Token>> {
+    let lossless = tokens.first().is_some_and(|tk| tk.trivia.is_some());
+
+    let mut path = vec![];
+    enclosing_group(&tokens, &edit.range, &mut path);
+
+    while !path.is_empty() {
+        let group = group_at(&tokens, &path);
+        if let Some(inner) = relex_group(group, &file, edit) {
+            let mut anchor = Anchor {
+                file: &file,
+                pos: file.clone(),
+                edit,
+            };
+            anchor.tokens(&mut tokens, Some(&path), &mut Some(inner));
+            return Ok((file.slice(file.len()..), tokens));
+        }
+        path.pop();
+    }
+
+    match lossless {
+        true => tokenize_lossless(file).map(|(i, lossless)| (i, lossless.tokens)),
+        false => tokenize(file),
+    }
+}
+
+/// Indices from the top level down to the innermost group whose contents contain `edit`
+fn enclosing_group(tokens: &[Token], edit: &Range<usize>, path: &mut Vec<usize>) {
+    for (idx, token) in tokens.iter().enumerate() {
+        let Some(inner) = group(&token.kind) else {
+            continue;
+        };
+
+        let open = token.span.location_offset();
+        let close = open + token.span.len() - 1;
+        if open < edit.start && edit.end <= close {
+            path.push(idx);
+            enclosing_group(inner, edit, path);
+            return;
+        }
+    }
+}
+
+fn group_at<'a>(tokens: &'a [Token], path: &[usize]) -> &'a Token {
+    let token = &tokens[path[0]];
+    match &path[1..] {
+        [] => token,
+        rest => group_at(group(&token.kind).unwrap(), rest),
+    }
+}
+
+/// Tokenizes the contents of `group` in the new text,
+/// `None` if they no longer end at its closing delimiter
+fn relex_group(group: &Token, file: &Span, edit: &Edit) -> Option<Vec<Token>> {
+    let open = group.span.location_offset();
+    let close = (open + group.span.len() - 1).checked_add_signed(edit.delta())?;
+    let end = group.span.chars().last().unwrap();
+
+    let i = file.slice(open + 1 - file.location_offset()..);
+
+    let mut errors = vec![];
+    let (i, tokens) = token_list(skip_whitespace(i), &mut vec![end], &mut errors);
+
+    (errors.is_empty() && i.location_offset() == close && i.starts_with(end)).then_some(tokens)
+}
+
+/// Moves the spans of the old tree to the new text
+struct Anchor<'a> {
+    file: &'a Span,
+    /// Spans are handed out in source order,
+    /// slicing on from the previous one only counts the lines in between
+    pos: Span,
+    edit: &'a Edit,
+}
+
+impl Anchor<'_> {
+    /// `path` leads to the group whose contents are replaced by `inner`
+    fn tokens(
+        &mut self,
+        tokens: &mut [Token],
+        path: Option<&[usize]>,
+        inner: &mut Option<Vec<Token>>,
+    ) {
+        for (idx, token) in tokens.iter_mut().enumerate() {
+            let path = path.and_then(|path| match path {
+                [first, rest @ ..] if *first == idx => Some(rest),
+                _ => None,
+            });
+            self.token(token, path, inner);
+        }
+    }
+
+    fn token(&mut self, token: &mut Token, path: Option<&[usize]>, inner: &mut Option<Vec<Token>>) {
+        let mut trivia = token.trivia.take();
+        if let Some(trivia) = &mut trivia {
+            self.trivia(&mut trivia.leading);
+        }
+        token.span = self.span(&token.span);
+
+        match &mut token.kind {
+            Kind::Number(num) => {
+                num.sign = num.sign.as_ref().map(|s| self.span(s));
+                num.base = num.base.as_ref().map(|s| self.span(s));
+                num.number = self.span(&num.number);
+                num.suffix = num.suffix.as_ref().map(|s| self.span(s));
+            }
+            Kind::Float(num) => {
+                num.sign = num.sign.as_ref().map(|s| self.span(s));
+                num.number = self.span(&num.number);
+                num.exponent = num.exponent.as_ref().map(|s| self.span(s));
+                num.suffix = num.suffix.as_ref().map(|s| self.span(s));
+            }
+            Kind::Tuple(tokens) | Kind::Block(tokens) | Kind::Array(tokens) => {
+                let mut group_trivia = trivia.as_mut().and_then(|t| t.group.as_mut());
+
+                if let Some([]) = path {
+                    let new = inner.take().expect("only one group is tokenized again");
+                    *tokens = match &mut group_trivia {
+                        Some(group) => {
+                            let open = token.span.location_offset();
+                            let close = open + token.span.len() - 1;
+                            let (new, open_trailing, close_leading) =
+                                attach_group(self.file, open, close, &new);
+                            group.open_trailing = open_trailing;
+                            group.close_leading = close_leading;
+                            Rc::from(new)
+                        }
+                        None => Rc::from(new),
+                    };
+                } else {
+                    if let Some(group) = &mut group_trivia {
+                        self.trivia(&mut group.open_trailing);
+                    }
+                    self.tokens(Rc::make_mut(tokens), path, inner);
+                    if let Some(group) = &mut group_trivia {
+                        self.trivia(&mut group.close_leading);
+                    }
+                }
+
+                if let Some(group) = group_trivia {
+                    group.close = self.span(&group.close);
+                }
+            }
+            _ => (),
+        }
+
+        if let Some(trivia) = &mut trivia {
+            self.trivia(&mut trivia.trailing);
+        }
+        token.trivia = trivia;
+    }
+
+    fn trivia(&mut self, trivia: &mut [Trivia]) {
+        for t in trivia {
+            t.span = self.span(&t.span);
+        }
+    }
+
+    fn span(&mut self, span: &Span) -> Span {
+        let start = self.offset(span.location_offset());
+        let end = self.offset(span.location_offset() + span.len());
+
+        if start < self.pos.location_offset() {
+            self.pos = self.file.clone();
+        }
+        self.pos = self.pos.slice(start - self.pos.location_offset()..);
+        self.pos.slice(..end - start)
+    }
+
+    /// Only the group containing the edit is tokenized again,
+    /// so no other span starts or ends inside the replaced text
+    fn offset(&self, offset: usize) -> usize {
+        match offset >= self.edit.range.end {
+            true => offset.checked_add_signed(self.edit.delta()).unwrap(),
+            false => offset,
+        }
+    }
+}
+
+fn group(kind: &Kind) -> Option<&Rc<[Token]>> {
+    match kind {
+        Kind::Tuple(inner) | Kind::Block(inner) | Kind::Array(inner) => Some(inner),
+        _ => None,
+    }
+}
@@ -3,28 +3,33 @@
 extern crate core;
 
 use nom::bytes::complete::{is_not, tag, take_while, take_while_m_n};
-use nom::character::complete::char;
-use nom::combinator::{map, not, opt};
+use nom::combinator::{map, not, opt, recognize};
 use std::rc::Rc;
 
 use nom::branch::alt;
 
 use nom::multi::{fold_many0, many0};
-use nom::sequence::{delimited, pair, terminated};
-use nom::{Err, IResult, InputLength, Offset, Parser, Slice};
+use nom::sequence::{delimited, pair, preceded, terminated};
+use nom::{Err, IResult, InputLength, InputTake, Offset, Parser, Slice};
 
 mod error;
 mod identifier;
+mod incremental;
 mod number;
 mod string;
 #[cfg(test)]
 mod tests;
+mod trivia;
 
-use crate::error::{TokenError, TokenErrorKind};
+pub use crate::error::{TokenError, TokenErrorKind};
 use crate::identifier::parse_ident;
-use crate::number::parse_integer;
-pub use crate::number::Number;
-use crate::string::parse_string;
+pub use crate::incremental::{retokenize, Edit};
+use crate::number::{parse_float, parse_integer};
+pub use crate::number::{Float, IntTy, IntValue, Number};
+use crate::string::{parse_byte_char, parse_byte_string, parse_char, parse_string};
+pub use crate::trivia::{
+    tokenize_lossless, GroupTrivia, LosslessTokens, TokenTrivia, Trivia, TriviaKind,
+};
 use error::ToTokenError;
 use w_rcstr::RcStr;
 
@@ -35,6 +40,8 @@ pub type TokResult<R = Span> = IResult<Span, R, TokenError>;
 pub struct Token {
     pub span: Span,
     pub kind: Kind,
+    /// Only populated by [`tokenize_lossless`]
+    pub trivia: Option<Box<TokenTrivia>>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -48,9 +55,9 @@ pub enum Kind {
     /// `->`
     InlineBlk,
 
-    /// `:`
+    /// `:`, separates path segments like `option:some`
     Colon,
-    /// `::`
+    /// `::`, separates a name from its definition
     DoubleCol,
 
     /// `:=`
@@ -64,6 +71,14 @@ pub enum Kind {
     Semicolon,
     /// `.`
     Dot,
+    /// `..`
+    Range,
+    /// `..=`
+    RangeInclusive,
+    /// `?`
+    Question,
+    /// `@`
+    At,
 
     // Math operands
     /// `+`
@@ -88,6 +103,8 @@ pub enum Kind {
     Shl,
     /// `>>`
     Shr,
+    /// `~`
+    Tilde,
 
     // Comparison operands
     /// `==`
@@ -140,25 +157,46 @@ pub enum Kind {
     /// `[TOKENS]`
     Array(Rc<[Token]>),
 
+    /// `/// TEXT`, documents the following item
+    DocOuter(String),
+    /// `//! TEXT`, documents the enclosing module
+    DocInner(String),
+
     String(String),
+    /// `b"..."`
+    ByteString(Vec<u8>),
+    /// `'c'`
+    Char(char),
+    /// `b'c'`
+    Byte(u8),
     Number(Box<Number>),
-}
+    Float(Box<Float>),
 
-pub fn tokenize(mut i: Span) -> TokResult<Vec<Token>> {
-    let mut tokens = vec![];
-    loop {
-        let (ni, token) = token(i).reason("failed to parse entire file")?;
-        if let Some(token) = token {
-            tokens.push(token);
-        }
-        i = ni;
+    /// Text the tokenizer could not make sense of, see [`tokenize_recovering`]
+    Error,
+}
 
-        if i.len() == 0 {
-            break;
-        }
+/// Tokenizes the entire file, failing on the first error.
+///
+/// Use [`tokenize_recovering`] to get every error along with a best-effort token tree.
+pub fn tokenize(i: Span) -> TokResult<Vec<Token>> {
+    let (tokens, errors) = tokenize_recovering(i.clone());
+    if let Some(err) = errors.into_iter().next() {
+        return Err::<(Span, Vec<Token>), _>(Err::Failure(err))
+            .reason("failed to parse entire file");
     }
 
-    Ok((i, tokens))
+    Ok((Span::slice(&i, i.len()..), tokens))
+}
+
+/// Tokenizes the entire file without stopping at errors.
+///
+/// Unrecognized text becomes a [`Kind::Error`] token, a stray closing delimiter is skipped
+/// and a group missing its closing delimiter is closed when an enclosing group ends.
+pub fn tokenize_recovering(i: Span) -> (Vec<Token>, Vec<TokenError>) {
+    let mut errors = vec![];
+    let (_, tokens) = token_list(skip_whitespace(i), &mut vec![], &mut errors);
+    (tokens, errors)
 }
 
 fn token(i: Span) -> TokResult<Option<Token>> {
@@ -172,20 +210,41 @@ fn token(i: Span) -> TokResult<Option<Token>> {
 
     // this had to be done due to limitations with `alt`
     let res = alt((
+        parse_doc_comment,
         map(parse_string, |(span, str)| Token {
             span,
             kind: Kind::String(str),
+            trivia: None,
+        }),
+        map(parse_byte_string, |(span, bytes)| Token {
+            span,
+            kind: Kind::ByteString(bytes),
+            trivia: None,
+        }),
+        map(parse_char, |(span, c)| Token {
+            span,
+            kind: Kind::Char(c),
+            trivia: None,
+        }),
+        map(parse_byte_char, |(span, b)| Token {
+            span,
+            kind: Kind::Byte(b),
+            trivia: None,
+        }),
+        map(parse_float, |(span, num)| Token {
+            span,
+            kind: Kind::Float(Box::new(num)),
+            trivia: None,
         }),
         map(parse_integer, |(span, num)| Token {
             span,
             kind: Kind::Number(Box::new(num)),
+            trivia: None,
         }),
-        parse_block,
-        parse_tuple,
-        parse_array,
         map(parse_ident, |span| Token {
             span,
             kind: Kind::Ident,
+            trivia: None,
         }),
         // assignment operators
         op("_", "", || Kind::Fill),
@@ -215,6 +274,7 @@ fn token(i: Span) -> TokResult<Option<Token>> {
             op("&", "", || Kind::And),
             op("|", "", || Kind::Or),
             op("^", "", || Kind::Xor),
+            op("~", "", || Kind::Tilde),
         )),
         // math operations
         alt((
@@ -237,9 +297,13 @@ fn token(i: Span) -> TokResult<Option<Token>> {
         alt((
             op("::", ":=", || Kind::DoubleCol),
             op(":=", ":=", || Kind::Define),
-            op(":", "=", || Kind::DoubleCol),
+            op(":", "=", || Kind::Colon),
             op(",", "", || Kind::Comma),
+            op("..=", "", || Kind::RangeInclusive),
+            op("..", "", || Kind::Range),
             op(".", "", || Kind::Dot),
+            op("?", "", || Kind::Question),
+            op("@", "", || Kind::At),
             op(";", "", || Kind::Semicolon),
             op("=", "", || Kind::Assign),
         )),
@@ -256,117 +320,197 @@ fn token(i: Span) -> TokResult<Option<Token>> {
 
 fn consume_singleline_comments(mut oi: Span) -> TokResult<()> {
     loop {
-        let (i, _) = tag("//")(oi)?;
+        let (i, _) = comment_start(oi)?;
         let (i, _) = terminated(many0(is_not("\r\n")), whitespace)(i)?;
         oi = i;
-        if tag::<_, _, TokenError>("//")(oi.clone()).is_err() {
+        if comment_start(oi.clone()).is_err() {
             break;
         }
     }
     Ok((oi, ()))
 }
 
-fn consume_multiline_comments(i: Span) -> TokResult<()> {
-    delimited(
-        tag("/*"),
-        fold_many0(
-            alt((
-                consume_multiline_comments,
-                map(pair(not(tag("*/")), take_while_m_n(1, 1, |_| true)), |_| ()),
-            )),
-            || (),
-            |_, _| (),
-        ),
-        pair(tag("*/"), whitespace),
-    )(i)
+fn comment_start(i: Span) -> TokResult {
+    preceded(not(doc_marker), tag("//"))(i)
 }
 
-fn parse_tuple(oi: Span) -> TokResult<Token> {
-    let (i, (span, o)) = parsed_delimited(oi, '(', ')')?;
-
-    Ok((
-        i,
-        Token {
-            span,
-            kind: Kind::Tuple(Rc::from(o.into_boxed_slice())),
-        },
-    ))
+/// `////` is a regular comment, just like in rust
+fn doc_marker(i: Span) -> TokResult {
+    alt((terminated(tag("///"), not(tag("/"))), tag("//!")))(i)
 }
 
-fn parse_block(oi: Span) -> TokResult<Token> {
-    let (i, (span, o)) = parsed_delimited(oi, '{', '}')?;
+fn parse_doc_comment(oi: Span) -> TokResult<Token> {
+    let (i, marker) = doc_marker(oi.clone())?;
+    let (i, text) = take_while(|c| c != '\r' && c != '\n')(i)?;
+
+    let offset = oi.offset(&i);
+    let span = Span::slice(&oi, ..offset);
+
+    let kind = if *marker == "//!" {
+        Kind::DocInner(text.to_string())
+    } else {
+        Kind::DocOuter(text.to_string())
+    };
 
     Ok((
-        i,
+        whitespace(i)?.0,
         Token {
             span,
-            kind: Kind::Block(Rc::from(o.into_boxed_slice())),
+            kind,
+            trivia: None,
         },
     ))
 }
 
-fn parse_array(oi: Span) -> TokResult<Token> {
-    let (i, (span, o)) = parsed_delimited(oi, '[', ']')?;
+fn consume_multiline_comments(i: Span) -> TokResult<()> {
+    map(pair(block_comment, whitespace), |_| ())(i)
+}
 
-    Ok((
-        i,
-        Token {
-            span,
-            kind: Kind::Array(Rc::from(o.into_boxed_slice())),
-        },
-    ))
+fn block_comment(i: Span) -> TokResult {
+    recognize(delimited(
+        tag("/*"),
+        fold_many0(
+            alt((
+                map(block_comment, |_| ()),
+                map(pair(not(tag("*/")), take_while_m_n(1, 1, |_| true)), |_| ()),
+            )),
+            || (),
+            |_, _| (),
+        ),
+        tag("*/"),
+    ))(i)
+}
+
+fn line_comment(i: Span) -> TokResult {
+    recognize(pair(tag("//"), take_while(|c| c != '\r' && c != '\n')))(i)
 }
 
-fn parsed_delimited(oi: Span, start: char, end: char) -> TokResult<(Span, Vec<Token>)> {
-    let (mut i, _) = pair(char(start), whitespace)(oi.clone())?;
+/// `(start, end)` of every group token
+const GROUPS: [(char, char); 3] = [('(', ')'), ('{', '}'), ('[', ']')];
+
+/// Collects tokens until the end of input or a closing delimiter of one of the `open` groups.
+///
+/// Text that can not be tokenized is reported to `errors` and kept as a [`Kind::Error`] token.
+fn token_list(
+    mut i: Span,
+    open: &mut Vec<char>,
+    errors: &mut Vec<TokenError>,
+) -> (Span, Vec<Token>) {
     let mut acc = vec![];
 
-    let last_err;
-    loop {
+    while let Some(c) = i.chars().next() {
+        if let Some(&(start, end)) = GROUPS.iter().find(|(start, _)| *start == c) {
+            let (ni, token) = parse_group(i, start, end, open, errors);
+            acc.push(token);
+            i = ni;
+            continue;
+        }
+
+        if GROUPS.iter().any(|(_, end)| *end == c) {
+            if open.contains(&c) {
+                break;
+            }
+
+            let (ni, span) = i.take_split(1);
+            errors.push(TokenError::new(span.clone(), format!("Unexpected `{c}`")));
+            acc.push(Token {
+                span,
+                kind: Kind::Error,
+                trivia: None,
+            });
+            i = skip_whitespace(ni);
+            continue;
+        }
+
         match token(i.clone()) {
             Ok((ni, token)) => {
-                if let Some(token) = token {
-                    acc.push(token)
-                };
+                acc.extend(token);
                 i = ni;
             }
-            Err(Err::Error(mut err) | Err::Failure(mut err)) => {
-                err.reason = Some(
-                    format!(
-                        "failure to parse at {}:{}",
-                        i.location_line(),
-                        i.location_offset()
-                    )
-                    .into(),
-                );
-                let err = TokenError {
-                    span: oi.clone(),
-                    kind: TokenErrorKind::Other(Box::new(err)),
-                    reason: Some(
-                        "Failed to parse delimited section due to unparseable token inside".into(),
-                    ),
-                };
-                last_err = Some(Err(Err::Failure(err)));
-                break;
-            }
-            err @ Err(_) => {
-                return err.map(|_| unreachable!());
+            Err(err) => {
+                let (ni, span) = skip_invalid(i);
+                errors.push(match err {
+                    Err::Failure(err) => err,
+                    _ => TokenError::new(span.clone(), "Unrecognized token"),
+                });
+                acc.push(Token {
+                    span,
+                    kind: Kind::Error,
+                    trivia: None,
+                });
+                i = ni;
             }
         }
     }
 
-    let (i, end_p) = opt(char(end))(i)?;
-    if end_p.is_none() {
-        if let Some(err) = last_err {
-            err
-        } else {
-            Err(Err::Error(TokenError::new(i, format!("expected `{end}`"))))
-        }
+    (i, acc)
+}
+
+/// Parses a group, a missing closing delimiter is reported and the group
+/// ends after its last token instead
+fn parse_group(
+    oi: Span,
+    start: char,
+    end: char,
+    open: &mut Vec<char>,
+    errors: &mut Vec<TokenError>,
+) -> (Span, Token) {
+    let (i, opener) = oi.take_split(1);
+
+    open.push(end);
+    let (i, inner) = token_list(skip_whitespace(i), open, errors);
+    open.pop();
+
+    let (i, span) = if i.starts_with(end) {
+        let (i, _) = i.take_split(1);
+        let span = Span::slice(&oi, ..oi.offset(&i));
+        (skip_whitespace(i), span)
     } else {
-        let offset = oi.offset(&i);
-        let span = Span::slice(&oi, ..offset);
-        Ok((whitespace(i)?.0, (span, acc)))
-    }
+        errors.push(TokenError::new(
+            opener,
+            format!("Unclosed `{start}`, expected `{end}`"),
+        ));
+        let len = inner.last().map_or(1, |tk| {
+            tk.span.location_offset() + tk.span.len() - oi.location_offset()
+        });
+        (i, Span::slice(&oi, ..len))
+    };
+
+    let inner = Rc::from(inner.into_boxed_slice());
+    let kind = match start {
+        '(' => Kind::Tuple(inner),
+        '{' => Kind::Block(inner),
+        _ => Kind::Array(inner),
+    };
+
+    (
+        i,
+        Token {
+            span,
+            kind,
+            trivia: None,
+        },
+    )
+}
+
+/// Skips the text a token failed on, up to the next whitespace or delimiter.
+/// Strings may contain anything, so they are skipped to the end of the line.
+fn skip_invalid(i: Span) -> (Span, Span) {
+    let first = i.chars().next().unwrap().len_utf8();
+    let len = if i.starts_with('"') {
+        i.find(['\r', '\n'])
+    } else {
+        i[first..]
+            .find(|c: char| c.is_whitespace() || "()[]{};,".contains(c))
+            .map(|len| first + len)
+    };
+
+    let (ni, span) = i.take_split(len.unwrap_or(i.len()));
+    (skip_whitespace(ni), span)
+}
+
+fn skip_whitespace(i: Span) -> Span {
+    whitespace(i).expect("whitespace always succeeds").0
 }
 
 fn op(
@@ -378,6 +522,7 @@ fn op(
         Token {
             span: o,
             kind: kind(),
+            trivia: None,
         }
     })
 }
@@ -387,7 +532,7 @@ fn defines(i: Span) -> TokResult {
 }
 
 fn boundary(i: Span, mut fail: impl FnMut(char) -> bool) -> TokResult<()> {
-    if i.len() == 0 || {
+    if i.is_empty() || {
         let first = i.chars().next().unwrap();
         char::is_whitespace(first) || !fail(first)
     } {
@@ -471,6 +616,18 @@ impl Kind {
             Kind::Colon => 41,
             Kind::Fill => 42,
             Kind::InlineBlk => 43,
+            Kind::Float(_) => 44,
+            Kind::ByteString(_) => 45,
+            Kind::Char(_) => 46,
+            Kind::Byte(_) => 47,
+            Kind::DocOuter(_) => 48,
+            Kind::DocInner(_) => 49,
+            Kind::Error => 50,
+            Kind::Range => 51,
+            Kind::RangeInclusive => 52,
+            Kind::Question => 53,
+            Kind::At => 54,
+            Kind::Tilde => 55,
         }
     }
 }
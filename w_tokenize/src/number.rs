@@ -1,9 +1,9 @@
-use crate::{bounded, Span, TokResult};
+use crate::{bounded, Span, TokResult, TokenError};
 use nom::branch::alt;
-use nom::bytes::complete::{is_a, tag, take_while, take_while1};
+use nom::bytes::complete::{tag, take_while, take_while1, take_while_m_n};
 use nom::combinator::{opt, recognize};
-use nom::sequence::pair;
-use nom::{Offset, Slice};
+use nom::sequence::{pair, preceded};
+use nom::{Err, Offset, Slice};
 
 #[derive(Debug, Clone)]
 pub struct Number {
@@ -13,24 +13,37 @@ pub struct Number {
     pub base: Option<Span>,
 }
 
+#[derive(Debug, Clone)]
+pub struct Float {
+    pub sign: Option<Span>,
+    /// Integer and fractional digits, e.g. `1_000.25`
+    pub number: Span,
+    /// The exponent including its marker, e.g. `e-3`
+    pub exponent: Option<Span>,
+    pub suffix: Option<Span>,
+}
+
 pub fn parse_integer(i: Span) -> TokResult<(Span, Number)> {
     bounded(parse_integer_inner, |c| c.is_alphanumeric())(i)
 }
 
 fn parse_integer_inner(oi: Span) -> TokResult<(Span, Number)> {
-    let (i, sign) = opt(is_a("+-"))(oi.clone())?;
+    let (i, sign) = opt(parse_sign)(oi.clone())?;
     let (i, base) = opt(parse_base)(i)?;
-    let num_check = match &base {
-        Some(span) if **span == "0x" => |c: char| c.is_ascii_hexdigit(),
-        Some(span) if **span == "0b" => |c: char| matches!(c, '0'..='1'),
-        Some(span) if **span == "0o" => |c: char| matches!(c, '0'..='8'),
-        Some(_) => unreachable!(),
-        None => |c: char| c.is_ascii_digit(),
-    };
+    let radix = radix_of(base.as_ref());
+    let num_check = move |c: char| c.is_digit(radix);
     let (i, num) = recognize(pair(
         take_while1(num_check),
         take_while(move |c: char| num_check(c) || c == '_'),
     ))(i)?;
+
+    if let Some(c) = i.chars().next().filter(char::is_ascii_digit) {
+        return Err(Err::Failure(TokenError::new(
+            Span::slice(&i, ..1),
+            format!("Invalid digit `{c}` for a base {radix} literal"),
+        )));
+    }
+
     let (i, suffix) = opt(parse_suffix)(i)?;
 
     let offset = oi.offset(&i);
@@ -50,17 +63,303 @@ fn parse_integer_inner(oi: Span) -> TokResult<(Span, Number)> {
     ))
 }
 
+pub fn parse_float(i: Span) -> TokResult<(Span, Float)> {
+    bounded(parse_float_inner, |c| c.is_alphanumeric())(i)
+}
+
+fn parse_float_inner(oi: Span) -> TokResult<(Span, Float)> {
+    let (i, sign) = opt(parse_sign)(oi.clone())?;
+    let (ni, _) = decimal_digits(i.clone())?;
+    // a fraction must start with a digit, otherwise `1..2` and `1.len()` would break
+    let (ni, fraction) = opt(preceded(tag("."), decimal_digits))(ni)?;
+    let number = Span::slice(&i, ..i.offset(&ni));
+    let (i, exponent) = opt(parse_exponent)(ni)?;
+    let (i, suffix) = opt(parse_float_suffix)(i)?;
+
+    if fraction.is_none() && exponent.is_none() && suffix.is_none() {
+        // plain integers are handled by `parse_integer`
+        return Err(Err::Error(TokenError::new(
+            Span::slice(&oi, ..0),
+            "Not a float literal",
+        )));
+    }
+
+    let (_, trailing) = take_while(char::is_alphanumeric)(i.clone())?;
+    if !trailing.is_empty() {
+        return Err(Err::Failure(TokenError::new(
+            trailing,
+            "Invalid float suffix, expected `f32` or `f64`",
+        )));
+    }
+
+    let offset = oi.offset(&i);
+    let span = Span::slice(&oi, ..offset);
+
+    Ok((
+        i,
+        (
+            span,
+            Float {
+                sign,
+                number,
+                exponent,
+                suffix,
+            },
+        ),
+    ))
+}
+
+fn decimal_digits(i: Span) -> TokResult {
+    recognize(pair(
+        take_while1(|c: char| c.is_ascii_digit()),
+        take_while(|c: char| c.is_ascii_digit() || c == '_'),
+    ))(i)
+}
+
+fn parse_exponent(oi: Span) -> TokResult {
+    let (i, _) = take_while_m_n(1, 1, |c| c == 'e' || c == 'E')(oi.clone())?;
+    let (i, _) = opt(take_while_m_n(1, 1, |c| c == '+' || c == '-'))(i)?;
+    let (i, digits) = take_while(|c: char| c.is_ascii_digit() || c == '_')(i)?;
+
+    let span = Span::slice(&oi, ..oi.offset(&i));
+    if !digits.chars().any(|c| c.is_ascii_digit()) {
+        return Err(Err::Failure(TokenError::new(
+            span,
+            "Float exponent must contain at least one digit",
+        )));
+    }
+
+    Ok((i, span))
+}
+
+fn parse_float_suffix(i: Span) -> TokResult {
+    alt((tag("f32"), tag("f64")))(i)
+}
+
+fn parse_sign(i: Span) -> TokResult {
+    take_while_m_n(1, 1, |c| c == '+' || c == '-')(i)
+}
+
 fn parse_base(i: Span) -> TokResult {
     alt((tag("0x"), tag("0b"), tag("0o")))(i)
 }
 
+fn radix_of(base: Option<&Span>) -> u32 {
+    match base.map(|span| &***span) {
+        Some("0x") => 16,
+        Some("0o") => 8,
+        Some("0b") => 2,
+        Some(_) => unreachable!("allowed bases exceeded"),
+        None => 10,
+    }
+}
+
 fn parse_suffix(i: Span) -> TokResult {
     recognize(pair(
-        is_a("ui"),
-        alt((tag("8"), tag("16"), tag("32"), tag("64"))),
+        take_while_m_n(1, 1, |c| c == 'u' || c == 'i'),
+        alt((
+            tag("8"),
+            tag("16"),
+            tag("32"),
+            tag("64"),
+            tag("128"),
+            tag("size"),
+        )),
     ))(i)
 }
 
+/// The integer types a [`Number`] can be evaluated to
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum IntTy {
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    Usize,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    Isize,
+}
+
+/// A checked integer constant, pointer sized integers are currently always 64 bit
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum IntValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    U128(u128),
+    Usize(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    I128(i128),
+    Isize(i64),
+}
+
+impl IntTy {
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        Some(match suffix {
+            "u8" => IntTy::U8,
+            "u16" => IntTy::U16,
+            "u32" => IntTy::U32,
+            "u64" => IntTy::U64,
+            "u128" => IntTy::U128,
+            "usize" => IntTy::Usize,
+            "i8" => IntTy::I8,
+            "i16" => IntTy::I16,
+            "i32" => IntTy::I32,
+            "i64" => IntTy::I64,
+            "i128" => IntTy::I128,
+            "isize" => IntTy::Isize,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IntTy::U8 => "u8",
+            IntTy::U16 => "u16",
+            IntTy::U32 => "u32",
+            IntTy::U64 => "u64",
+            IntTy::U128 => "u128",
+            IntTy::Usize => "usize",
+            IntTy::I8 => "i8",
+            IntTy::I16 => "i16",
+            IntTy::I32 => "i32",
+            IntTy::I64 => "i64",
+            IntTy::I128 => "i128",
+            IntTy::Isize => "isize",
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        match self {
+            IntTy::U8 | IntTy::I8 => 8,
+            IntTy::U16 | IntTy::I16 => 16,
+            IntTy::U32 | IntTy::I32 => 32,
+            IntTy::U64 | IntTy::I64 | IntTy::Usize | IntTy::Isize => 64,
+            IntTy::U128 | IntTy::I128 => 128,
+        }
+    }
+
+    pub fn signed(self) -> bool {
+        matches!(
+            self,
+            IntTy::I8 | IntTy::I16 | IntTy::I32 | IntTy::I64 | IntTy::I128 | IntTy::Isize
+        )
+    }
+
+    /// Largest absolute value representable, negative signed numbers reach one further
+    fn max_magnitude(self, negative: bool) -> u128 {
+        match (self.signed(), negative) {
+            (false, _) => u128::MAX >> (128 - self.bits()),
+            (true, false) => (1 << (self.bits() - 1)) - 1,
+            (true, true) => 1 << (self.bits() - 1),
+        }
+    }
+}
+
+impl IntValue {
+    pub fn ty(&self) -> IntTy {
+        match self {
+            IntValue::U8(_) => IntTy::U8,
+            IntValue::U16(_) => IntTy::U16,
+            IntValue::U32(_) => IntTy::U32,
+            IntValue::U64(_) => IntTy::U64,
+            IntValue::U128(_) => IntTy::U128,
+            IntValue::Usize(_) => IntTy::Usize,
+            IntValue::I8(_) => IntTy::I8,
+            IntValue::I16(_) => IntTy::I16,
+            IntValue::I32(_) => IntTy::I32,
+            IntValue::I64(_) => IntTy::I64,
+            IntValue::I128(_) => IntTy::I128,
+            IntValue::Isize(_) => IntTy::Isize,
+        }
+    }
+}
+
+impl Number {
+    /// The type requested by the suffix, if any
+    pub fn ty(&self) -> Option<IntTy> {
+        self.suffix
+            .as_ref()
+            .map(|suffix| IntTy::from_suffix(suffix).expect("suffix was validated by the lexer"))
+    }
+
+    /// Evaluates the literal, unsuffixed numbers are evaluated as `default`.
+    ///
+    /// Errors point at the sign, digit or suffix responsible.
+    pub fn value(&self, default: IntTy) -> Result<IntValue, TokenError> {
+        let ty = self.ty().unwrap_or(default);
+        let radix = radix_of(self.base.as_ref());
+
+        let negative = match &self.sign {
+            Some(sign) if **sign == "-" => {
+                if !ty.signed() {
+                    return Err(TokenError::new(
+                        sign.clone(),
+                        format!("`{}` can not be negative", ty.name()),
+                    ));
+                }
+                true
+            }
+            _ => false,
+        };
+
+        let max = ty.max_magnitude(negative);
+        let mut magnitude = 0u128;
+
+        for (idx, c) in self.number.char_indices().filter(|(_, c)| *c != '_') {
+            let digit_span = || Span::slice(&self.number, idx..idx + c.len_utf8());
+
+            let digit = c.to_digit(radix).ok_or_else(|| {
+                TokenError::new(
+                    digit_span(),
+                    format!("Invalid digit `{c}` for a base {radix} literal"),
+                )
+            })?;
+
+            magnitude = magnitude
+                .checked_mul(radix as u128)
+                .and_then(|n| n.checked_add(digit as u128))
+                .filter(|n| *n <= max)
+                .ok_or_else(|| {
+                    TokenError::new(
+                        digit_span(),
+                        format!("Number does not fit into `{}`", ty.name()),
+                    )
+                })?;
+        }
+
+        let signed = if negative {
+            (magnitude as i128).wrapping_neg()
+        } else {
+            magnitude as i128
+        };
+
+        Ok(match ty {
+            IntTy::U8 => IntValue::U8(magnitude as u8),
+            IntTy::U16 => IntValue::U16(magnitude as u16),
+            IntTy::U32 => IntValue::U32(magnitude as u32),
+            IntTy::U64 => IntValue::U64(magnitude as u64),
+            IntTy::U128 => IntValue::U128(magnitude),
+            IntTy::Usize => IntValue::Usize(magnitude as u64),
+            IntTy::I8 => IntValue::I8(signed as i8),
+            IntTy::I16 => IntValue::I16(signed as i16),
+            IntTy::I32 => IntValue::I32(signed as i32),
+            IntTy::I64 => IntValue::I64(signed as i64),
+            IntTy::I128 => IntValue::I128(signed),
+            IntTy::Isize => IntValue::Isize(signed as i64),
+        })
+    }
+}
+
 impl PartialEq for Number {
     fn eq(&self, other: &Self) -> bool {
         *self.number == *other.number
@@ -70,3 +369,17 @@ impl PartialEq for Number {
     }
 }
 impl Eq for Number {}
+
+impl PartialEq for Float {
+    fn eq(&self, other: &Self) -> bool {
+        fn frag(s: &Option<Span>) -> Option<&str> {
+            s.as_ref().map(|s| &***s)
+        }
+
+        **self.number == **other.number
+            && frag(&self.sign) == frag(&other.sign)
+            && frag(&self.exponent) == frag(&other.exponent)
+            && frag(&self.suffix) == frag(&other.suffix)
+    }
+}
+impl Eq for Float {}
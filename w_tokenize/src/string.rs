@@ -3,7 +3,7 @@ use either::Either;
 use nom::branch::alt;
 use nom::bytes::complete::{tag, take_while1, take_while_m_n};
 use nom::character::complete::char as char_;
-use nom::combinator::value;
+use nom::combinator::{map, value};
 use nom::multi::fold_many0;
 use nom::sequence::delimited;
 use nom::{Err, Offset, Slice};
@@ -18,7 +18,7 @@ fn parse_string_inner(i: Span) -> TokResult<(Span, String)> {
         char_('"'),
         fold_many0(parse_partial, String::new, |mut str, frag| {
             match frag {
-                Either::Left(s) => str.push_str(&*s),
+                Either::Left(s) => str.push_str(&s),
                 Either::Right(c) => str.push(c),
             }
             str
@@ -29,8 +29,8 @@ fn parse_string_inner(i: Span) -> TokResult<(Span, String)> {
     Ok((ni, (Span::slice(&i, ..span_end), o)))
 }
 
-fn parse_partial<'a>(i: Span) -> TokResult<Either<RcStr, char>> {
-    if i.len() == 0 {
+fn parse_partial(i: Span) -> TokResult<Either<RcStr, char>> {
+    if i.is_empty() {
         return Err(Err::Failure(TokenError::new(i, "Empty string segment")));
     }
     match i.chars().next().unwrap() {
@@ -44,6 +44,124 @@ fn parse_partial<'a>(i: Span) -> TokResult<Either<RcStr, char>> {
     }
 }
 
+pub fn parse_char(i: Span) -> TokResult<(Span, char)> {
+    bounded(parse_char_inner, |c| c == '\'' || c.is_alphanumeric())(i)
+}
+
+fn parse_char_inner(oi: Span) -> TokResult<(Span, char)> {
+    let (i, _) = char_('\'')(oi.clone())?;
+    let (i, c) = parse_quoted_char(i)?;
+    let (i, _) = char_('\'')(i)?;
+
+    let offset = oi.offset(&i);
+    Ok((i, (Span::slice(&oi, ..offset), c)))
+}
+
+fn parse_quoted_char(i: Span) -> TokResult<char> {
+    match i.chars().next() {
+        None | Some('\n' | '\r') => Err(Err::Error(TokenError::new(
+            Span::slice(&i, ..0),
+            "Unterminated character literal",
+        ))),
+        Some('\'') => Err(Err::Failure(TokenError::new(
+            Span::slice(&i, ..1),
+            "Empty character literal",
+        ))),
+        Some('\\') => parse_escape(i),
+        Some(c) => Ok((Span::slice(&i, c.len_utf8()..), c)),
+    }
+}
+
+pub fn parse_byte_char(i: Span) -> TokResult<(Span, u8)> {
+    bounded(parse_byte_char_inner, |c| c == '\'' || c.is_alphanumeric())(i)
+}
+
+fn parse_byte_char_inner(oi: Span) -> TokResult<(Span, u8)> {
+    let (i, _) = tag("b'")(oi.clone())?;
+    let (i, b) = match i.chars().next() {
+        None | Some('\n' | '\r') => {
+            return Err(Err::Error(TokenError::new(
+                Span::slice(&i, ..0),
+                "Unterminated byte literal",
+            )))
+        }
+        Some('\'') => {
+            return Err(Err::Failure(TokenError::new(
+                Span::slice(&i, ..1),
+                "Empty byte literal",
+            )))
+        }
+        Some('\\') => parse_byte_escape(i)?,
+        Some(c) => (Span::slice(&i, c.len_utf8()..), ascii_byte(&i, c)?),
+    };
+    let (i, _) = char_('\'')(i)?;
+
+    let offset = oi.offset(&i);
+    Ok((i, (Span::slice(&oi, ..offset), b)))
+}
+
+pub fn parse_byte_string(i: Span) -> TokResult<(Span, Vec<u8>)> {
+    bounded(parse_byte_string_inner, |c| c == '"' || c.is_alphanumeric())(i)
+}
+
+fn parse_byte_string_inner(i: Span) -> TokResult<(Span, Vec<u8>)> {
+    let (ni, o) = delimited(
+        tag("b\""),
+        fold_many0(parse_byte_partial, Vec::new, |mut bytes, frag| {
+            match frag {
+                Either::Left(s) => bytes.extend_from_slice(s.as_bytes()),
+                Either::Right(b) => bytes.push(b),
+            }
+            bytes
+        }),
+        char_('"'),
+    )(i.clone())?;
+    let span_end = i.offset(&ni);
+    Ok((ni, (Span::slice(&i, ..span_end), o)))
+}
+
+fn parse_byte_partial(i: Span) -> TokResult<Either<RcStr, u8>> {
+    if i.is_empty() {
+        return Err(Err::Failure(TokenError::new(i, "Empty string segment")));
+    }
+    match i.chars().next().unwrap() {
+        '"' => Err(Err::Error(TokenError::new(
+            Span::slice(&i, ..1),
+            "End of string",
+        ))),
+        '\\' => parse_byte_escape(i).map(|(s, b)| (s, Either::Right(b))),
+        _ => {
+            let (ni, s) = take_while1(|c: char| c != '"' && c != '\\')(i)?;
+            if let Some((idx, c)) = s.char_indices().find(|(_, c)| !c.is_ascii()) {
+                ascii_byte(&Span::slice(&s, idx..), c)?;
+            }
+            Ok((ni, Either::Left(s.fragment().clone())))
+        }
+    }
+}
+
+fn ascii_byte(i: &Span, c: char) -> Result<u8, Err<TokenError>> {
+    if c.is_ascii() {
+        Ok(c as u8)
+    } else {
+        Err(Err::Failure(TokenError::new(
+            Span::slice(i, ..c.len_utf8()),
+            "Byte literals may only contain ASCII characters, use a \\x escape instead",
+        )))
+    }
+}
+
+fn parse_byte_escape(i: Span) -> TokResult<u8> {
+    if tag::<_, _, TokenError>("\\u")(i.clone()).is_ok() {
+        return Err(Err::Failure(TokenError::new(
+            Span::slice(&i, ..2),
+            "Unicode escapes are not allowed in byte literals",
+        )));
+    }
+
+    alt((parse_hex_byte, map(parse_escape, |c| c as u8)))(i)
+}
+
 fn parse_escape(i: Span) -> TokResult<char> {
     alt((
         parse_byte,
@@ -69,7 +187,7 @@ fn parse_unicode(oi: Span) -> TokResult<char> {
     let offset = oi.offset(&i);
     let si = Span::slice(&oi, ..offset);
 
-    let val = u32::from_str_radix(&**hex, 16).unwrap();
+    let val = u32::from_str_radix(&hex, 16).unwrap();
     let val =
         char::try_from(val).map_err(|err| Err::Failure(TokenError::new(si, err.to_string())))?;
 
@@ -77,16 +195,12 @@ fn parse_unicode(oi: Span) -> TokResult<char> {
 }
 
 fn parse_byte(oi: Span) -> TokResult<char> {
-    let (i, _) = tag("\\")(oi.clone())?;
-    let (i, _) = tag("x")(i)?;
-    let (i, hex) = take_while_m_n(2, 2, |c: char| c.is_ascii_hexdigit())(i)
-        .reason("\\x must be followed by 2 hex characters")?;
-
-    let offset = oi.offset(&i);
-    let si = Span::slice(&oi, ..offset);
+    let (i, val) = parse_hex_byte(oi.clone())?;
 
-    let val = u8::from_str_radix(&hex, 16).unwrap();
     if val >= 0x80 {
+        let offset = oi.offset(&i);
+        let si = Span::slice(&oi, ..offset);
+
         return Err(Err::Failure(TokenError::new(
             si,
             "Character must be in range of [\\x00-\\x7f]",
@@ -96,6 +210,15 @@ fn parse_byte(oi: Span) -> TokResult<char> {
     Ok((i, char::from(val)))
 }
 
+fn parse_hex_byte(oi: Span) -> TokResult<u8> {
+    let (i, _) = tag("\\")(oi)?;
+    let (i, _) = tag("x")(i)?;
+    let (i, hex) = take_while_m_n(2, 2, |c: char| c.is_ascii_hexdigit())(i)
+        .reason("\\x must be followed by 2 hex characters")?;
+
+    Ok((i, u8::from_str_radix(&hex, 16).unwrap()))
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -1,5 +1,18 @@
-use crate::{tokenize, Span};
-use w_rcstr::{Origin, RcStr};
+use crate::{
+    retokenize, tokenize, tokenize_lossless, tokenize_recovering, Edit, IntTy, IntValue, Kind,
+    Number, Span, Token, TriviaKind,
+};
+use w_rcstr::{Origin, RcStr, SourceId, SourceMap};
+
+fn tokenize_str(src: &str) -> Result<Vec<Token>, crate::TokenError> {
+    let span = Span::new(RcStr::new(src.to_string(), Origin::Unknown));
+    tokenize(span)
+        .map(|(_, tokens)| tokens)
+        .map_err(|err| match err {
+            nom::Err::Error(err) | nom::Err::Failure(err) => err,
+            nom::Err::Incomplete(_) => unreachable!(),
+        })
+}
 
 #[test]
 fn parses() {
@@ -10,3 +23,538 @@ fn parses() {
     let (_, tokens) = tokenize(span).unwrap();
     println!("{:#?}", tokens);
 }
+
+#[test]
+fn float_literals() {
+    let tokens = tokenize_str("1.5 2e-3 1_000.25f32 7f64 -0.5E+10").unwrap();
+    let floats = tokens
+        .iter()
+        .map(|tk| match &tk.kind {
+            Kind::Float(f) => (
+                &**tk.span,
+                &**f.number,
+                f.exponent.as_ref().map(|s| &***s),
+                f.suffix.as_ref().map(|s| &***s),
+            ),
+            kind => panic!("expected float, got {kind:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        floats,
+        [
+            ("1.5", "1.5", None, None),
+            ("2e-3", "2", Some("e-3"), None),
+            ("1_000.25f32", "1_000.25", None, Some("f32")),
+            ("7f64", "7", None, Some("f64")),
+            ("-0.5E+10", "0.5", Some("E+10"), None),
+        ]
+    );
+}
+
+#[test]
+fn float_does_not_eat_dots() {
+    let tokens = tokenize_str("1.len a.b 3").unwrap();
+    let kinds = tokens.iter().map(|tk| &tk.kind).collect::<Vec<_>>();
+    assert!(matches!(
+        kinds[..],
+        [
+            Kind::Number(_),
+            Kind::Dot,
+            Kind::Ident,
+            Kind::Ident,
+            Kind::Dot,
+            Kind::Ident,
+            Kind::Number(_),
+        ]
+    ));
+}
+
+#[test]
+fn float_malformed_exponent() {
+    let err = tokenize_str("x := 2e+;").unwrap_err();
+    let inner = innermost(&err);
+    assert_eq!(&**inner.span, "e+");
+}
+
+#[test]
+fn float_invalid_suffix() {
+    let err = tokenize_str("1.5u8").unwrap_err();
+    let inner = innermost(&err);
+    assert_eq!(&**inner.span, "u8");
+}
+
+fn number(src: &str) -> Number {
+    match tokenize_str(src).unwrap().remove(0).kind {
+        Kind::Number(num) => *num,
+        kind => panic!("expected number, got {kind:?}"),
+    }
+}
+
+#[test]
+fn number_values() {
+    assert_eq!(
+        number("255u8").value(IntTy::I32).unwrap(),
+        IntValue::U8(255)
+    );
+    assert_eq!(
+        number("-128i8").value(IntTy::I32).unwrap(),
+        IntValue::I8(-128)
+    );
+    assert_eq!(
+        number("0xff_ff").value(IntTy::U16).unwrap(),
+        IntValue::U16(0xffff)
+    );
+    assert_eq!(
+        number("0o17usize").value(IntTy::I32).unwrap(),
+        IntValue::Usize(15)
+    );
+    assert_eq!(
+        number("0b101isize").value(IntTy::I32).unwrap(),
+        IntValue::Isize(5)
+    );
+    assert_eq!(
+        number("-170141183460469231731687303715884105728i128")
+            .value(IntTy::I32)
+            .unwrap(),
+        IntValue::I128(i128::MIN)
+    );
+    assert_eq!(
+        number("340282366920938463463374607431768211455u128")
+            .value(IntTy::I32)
+            .unwrap(),
+        IntValue::U128(u128::MAX)
+    );
+}
+
+#[test]
+fn number_overflow_points_at_digit() {
+    let err = number("1_256u8").value(IntTy::I32).unwrap_err();
+    assert_eq!(&**err.span, "6");
+    assert_eq!(err.span.location_offset(), 4);
+
+    let err = number("128").value(IntTy::I8).unwrap_err();
+    assert_eq!(err.span.location_offset(), 2);
+}
+
+#[test]
+fn number_negative_unsigned() {
+    let err = number("-1u32").value(IntTy::I32).unwrap_err();
+    assert_eq!(&**err.span, "-");
+}
+
+#[test]
+fn number_octal_rejects_eight() {
+    let err = tokenize_str("0o178").unwrap_err();
+    let inner = innermost(&err);
+    assert_eq!(&**inner.span, "8");
+    assert_eq!(inner.span.location_offset(), 4);
+}
+
+#[test]
+fn char_and_byte_literals() {
+    let tokens = tokenize_str(r"'a' '\n' '\u{1f308}' '\'' b'x' b'\xff' b'\t'").unwrap();
+    let kinds = tokens.into_iter().map(|tk| tk.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            Kind::Char('a'),
+            Kind::Char('\n'),
+            Kind::Char('🌈'),
+            Kind::Char('\''),
+            Kind::Byte(b'x'),
+            Kind::Byte(0xff),
+            Kind::Byte(b'\t'),
+        ]
+    );
+}
+
+#[test]
+fn byte_string_literal() {
+    let tokens = tokenize_str(r#"b"GET\x80\r\n" "str""#).unwrap();
+    assert_eq!(&**tokens[0].span, r#"b"GET\x80\r\n""#);
+    assert_eq!(tokens[0].kind, Kind::ByteString(b"GET\x80\r\n".to_vec()));
+    assert_eq!(tokens[1].kind, Kind::String("str".into()));
+}
+
+#[test]
+fn byte_literal_rejects_non_ascii() {
+    let err = tokenize_str("b\"ab\u{e4}c\"").unwrap_err();
+    assert_eq!(&**innermost(&err).span, "\u{e4}");
+
+    let err = tokenize_str("b'ä'").unwrap_err();
+    assert_eq!(&**innermost(&err).span, "ä");
+
+    let err = tokenize_str("''").unwrap_err();
+    assert_eq!(innermost(&err).span.location_offset(), 1);
+}
+
+#[test]
+fn range_tokens() {
+    let tokens = tokenize_str("0..5 a..=b ..x [?]u8 1.5..").unwrap();
+    let kinds = tokens.iter().map(|tk| &tk.kind).collect::<Vec<_>>();
+    assert!(matches!(
+        kinds[..],
+        [
+            Kind::Number(_),
+            Kind::Range,
+            Kind::Number(_),
+            Kind::Ident,
+            Kind::RangeInclusive,
+            Kind::Ident,
+            Kind::Range,
+            Kind::Ident,
+            Kind::Array(inner),
+            Kind::Ident,
+            Kind::Float(_),
+            Kind::Range,
+        ] if inner[0].kind == Kind::Question
+    ));
+}
+
+#[test]
+fn colon_tokens() {
+    let tokens = tokenize_str("vec(T):new :: option:none; x := a : b").unwrap();
+    let kinds = tokens.iter().map(|tk| &tk.kind).collect::<Vec<_>>();
+    assert!(matches!(
+        kinds[..],
+        [
+            Kind::Ident,
+            Kind::Tuple(_),
+            Kind::Colon,
+            Kind::Ident,
+            Kind::DoubleCol,
+            Kind::Ident,
+            Kind::Colon,
+            Kind::Ident,
+            Kind::Semicolon,
+            Kind::Ident,
+            Kind::Define,
+            Kind::Ident,
+            Kind::Colon,
+            Kind::Ident,
+        ]
+    ));
+}
+
+#[test]
+fn doc_comments() {
+    let tokens = tokenize_str("//! module\n// plain\n//// plain\n/// item\nx").unwrap();
+    let kinds = tokens.into_iter().map(|tk| tk.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            Kind::DocInner(" module".into()),
+            Kind::DocOuter(" item".into()),
+            Kind::Ident,
+        ]
+    );
+}
+
+#[test]
+fn lossless_roundtrip() {
+    let sources = [
+        include_str!("../../WIP_tests/old_1/generics1.w"),
+        include_str!("../../WIP_tests/mod_test/vec.w"),
+        include_str!("../../WIP_tests/mod_test/iter.w"),
+        include_str!("../../WIP_tests/mod_test/bubble_sort.w"),
+        include_str!("../../WIP_tests/mod_test/main.w"),
+        include_str!("../../WIP_tests/mod_test/utf8_chk.w"),
+        include_str!("../../WIP_tests/mod_test/trait.w"),
+        include_str!("../../WIP_tests/test1.w"),
+        include_str!("../../WIP_tests/types.w"),
+        "",
+        "  \n\t",
+        "// only a comment",
+        "a /* x /* nested */ */ b // tail\n\n  /* lead */ c\r\n",
+        "f( ) { x, [ 1 ] } // done",
+    ];
+
+    for src in sources {
+        let span = Span::new(RcStr::new(src.to_string(), Origin::Unknown));
+        let (_, lossless) = tokenize_lossless(span).unwrap();
+        assert_eq!(lossless.to_source(), src);
+    }
+}
+
+#[test]
+fn lossless_trivia_attachment() {
+    let span = Span::new(RcStr::from("a // one\n  b\n{ c }\n"));
+    let (_, lossless) = tokenize_lossless(span).unwrap();
+    let texts = |trivia: &[crate::Trivia]| {
+        trivia
+            .iter()
+            .map(|t| (t.kind, t.span.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    let a = lossless.tokens[0].trivia.as_deref().unwrap();
+    assert_eq!(
+        texts(&a.trailing),
+        [
+            (TriviaKind::Whitespace, " ".to_string()),
+            (TriviaKind::LineComment, "// one".to_string()),
+            (TriviaKind::Whitespace, "\n".to_string()),
+        ]
+    );
+
+    let b = lossless.tokens[1].trivia.as_deref().unwrap();
+    assert_eq!(
+        texts(&b.leading),
+        [(TriviaKind::Whitespace, "  ".to_string())]
+    );
+
+    let block = lossless.tokens[2].trivia.as_deref().unwrap();
+    let group = block.group.as_ref().unwrap();
+    assert_eq!(&**group.close, "}");
+    assert_eq!(group.close.location_offset(), 17);
+    assert_eq!(
+        texts(&group.open_trailing),
+        [(TriviaKind::Whitespace, " ".to_string())]
+    );
+    assert_eq!(
+        texts(&block.trailing),
+        [(TriviaKind::Whitespace, "\n".to_string())]
+    );
+}
+
+fn innermost(mut err: &crate::TokenError) -> &crate::TokenError {
+    while let crate::error::TokenErrorKind::Other(inner) = &err.kind {
+        err = inner;
+    }
+    err
+}
+
+fn recover_str(src: &str) -> (Vec<Token>, Vec<String>) {
+    let span = Span::new(RcStr::new(src.to_string(), Origin::Unknown));
+    let (tokens, errors) = tokenize_recovering(span);
+    let errors = errors
+        .iter()
+        .map(|err| format!("{}: {}", &**err.span, err.reason.as_deref().unwrap()))
+        .collect();
+    (tokens, errors)
+}
+
+#[test]
+fn recovery_reports_every_error() {
+    let (tokens, errors) = recover_str("a $ b 1e+ c # d");
+    assert_eq!(
+        errors,
+        [
+            "$: Unrecognized token",
+            "e+: Float exponent must contain at least one digit",
+            "#: Unrecognized token",
+        ]
+    );
+
+    let kinds = tokens.iter().map(|tk| tk.kind.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            Kind::Ident,
+            Kind::Error,
+            Kind::Ident,
+            Kind::Error,
+            Kind::Ident,
+            Kind::Error,
+            Kind::Ident
+        ]
+    );
+    assert_eq!(&**tokens[3].span, "1e+");
+}
+
+#[test]
+fn recovery_closes_unbalanced_groups() {
+    let (tokens, errors) = recover_str("{ a ( b } c ) d [ e");
+    assert_eq!(
+        errors,
+        [
+            "(: Unclosed `(`, expected `)`",
+            "): Unexpected `)`",
+            "[: Unclosed `[`, expected `]`",
+        ]
+    );
+
+    assert_eq!(tokens.len(), 5);
+    let Kind::Block(block) = &tokens[0].kind else {
+        panic!("expected block, got {:?}", tokens[0].kind);
+    };
+    assert_eq!(&**tokens[0].span, "{ a ( b }");
+    assert_eq!(&**block[1].span, "( b");
+    assert!(matches!(block[1].kind, Kind::Tuple(_)));
+    assert_eq!(tokens[2].kind, Kind::Error);
+    assert_eq!(&**tokens[4].span, "[ e");
+}
+
+#[test]
+fn strict_tokenize_fails_on_recovered_errors() {
+    let err = tokenize_str("{ a").unwrap_err();
+    assert_eq!(
+        innermost(&err).reason.as_deref(),
+        Some("Unclosed `{`, expected `}`")
+    );
+}
+
+/// Kind, location and text of every token, numbers include the location of their digits
+/// and tokens from [`tokenize_lossless`] the location of their trivia
+fn dump(tokens: &[Token], out: &mut Vec<String>) {
+    let loc = |span: &Span| format!("{}:{}", span.location_offset(), span.location_line());
+    let locs = |trivia: &[crate::Trivia]| trivia.iter().map(|t| loc(&t.span)).collect::<Vec<_>>();
+
+    for tk in tokens {
+        let mut line = format!("{} {} {:?}", tk.kind.cmp_id(), loc(&tk.span), &**tk.span);
+        if let Kind::Number(num) = &tk.kind {
+            line += &format!(" digits {}", loc(&num.number));
+        }
+        if let Some(trivia) = &tk.trivia {
+            line += &format!(
+                " trivia {:?} {:?}",
+                locs(&trivia.leading),
+                locs(&trivia.trailing)
+            );
+            if let Some(group) = &trivia.group {
+                line += &format!(
+                    " group {:?} {:?} {}",
+                    locs(&group.open_trailing),
+                    locs(&group.close_leading),
+                    loc(&group.close)
+                );
+            }
+        }
+        out.push(line);
+
+        if let Kind::Tuple(inner) | Kind::Block(inner) | Kind::Array(inner) = &tk.kind {
+            dump(inner, out);
+        }
+    }
+}
+
+fn dumped(tokens: &[Token]) -> Vec<String> {
+    let mut out = vec![];
+    dump(tokens, &mut out);
+    out
+}
+
+/// Retokenizes `old` after the edit turned its text into `file`
+fn apply(old: Vec<Token>, file: RcStr, range: std::ops::Range<usize>, text: &str) -> Vec<Token> {
+    let edit = Edit {
+        range,
+        new_len: text.len(),
+    };
+    retokenize(old, Span::new(file), &edit).unwrap().1
+}
+
+fn edited(src: &str, range: std::ops::Range<usize>, text: &str) -> RcStr {
+    let mut new = src.to_string();
+    new.replace_range(range, text);
+    new.into()
+}
+
+/// Checks the incremental result against tokenizing the edited text from scratch
+fn edit(src: &str, range: std::ops::Range<usize>, text: &str) {
+    let (_, old) = tokenize(Span::new(src.into())).unwrap();
+    let file = edited(src, range.clone(), text);
+    let new = apply(old, file.clone(), range, text);
+
+    assert_eq!(dumped(&new), dumped(&tokenize(Span::new(file)).unwrap().1));
+}
+
+const INCREMENTAL_SRC: &str = "e :: [1];\nf :: func {\n    y { 7 };\n    x(1, 2);\n}\ng :: { 3 }\n";
+
+#[test]
+fn retokenize_reuses_untouched_groups() {
+    let group = |tokens: &[Token], path: &[usize]| {
+        let mut tokens = tokens;
+        for &idx in path {
+            tokens = match &tokens[idx].kind {
+                Kind::Tuple(inner) | Kind::Block(inner) | Kind::Array(inner) => inner,
+                kind => panic!("expected group, got {kind:?}"),
+            };
+        }
+        tokens.as_ptr()
+    };
+
+    let at = INCREMENTAL_SRC.find('2').unwrap();
+    let (_, old) = tokenize(Span::new(INCREMENTAL_SRC.into())).unwrap();
+    let paths: [&[usize]; 5] = [&[2], &[7], &[7, 1], &[7, 4], &[10]];
+    let before = paths.map(|path| group(&old, path));
+
+    let file = edited(INCREMENTAL_SRC, at..at + 1, "22,\n        4");
+    let new = apply(old, file.clone(), at..at + 1, "22,\n        4");
+    assert_eq!(dumped(&new), dumped(&tokenize(Span::new(file)).unwrap().1));
+
+    let after = paths.map(|path| group(&new, path));
+    // only the contents of `x(1, 2)` are new
+    assert_eq!(before[..3], after[..3]);
+    assert_ne!(before[3], after[3]);
+    assert_eq!(before[4], after[4]);
+}
+
+#[test]
+fn retokenize_keeps_trivia() {
+    let src = "a :: { b(1 /* one */, 2) // call\n    c }\n// tail\nd :: (3)\n";
+    let at = src.find('2').unwrap();
+    let (_, old) = tokenize_lossless(Span::new(src.into())).unwrap();
+    let untouched = |tokens: &[Token]| match &tokens[5].kind {
+        Kind::Tuple(inner) => inner.as_ptr(),
+        kind => panic!("expected tuple, got {kind:?}"),
+    };
+    let before = untouched(&old.tokens);
+
+    let file = edited(src, at..at + 1, "x,\n  y ");
+    let new = apply(old.tokens, file.clone(), at..at + 1, "x,\n  y ");
+    assert_eq!(before, untouched(&new));
+
+    let (_, fresh) = tokenize_lossless(Span::new(file.clone())).unwrap();
+    assert_eq!(dumped(&new), dumped(&fresh.tokens));
+    let lossless = crate::LosslessTokens {
+        tokens: new,
+        eof: fresh.eof,
+    };
+    assert_eq!(lossless.to_source(), &*file);
+}
+
+#[test]
+fn retokenize_spans_resolve_in_source_map() {
+    fn check(tokens: &[Token], sources: &SourceMap, id: SourceId) {
+        for tk in tokens {
+            let file = sources
+                .source_of(tk.span.fragment())
+                .expect("span of an unregistered source");
+            assert_eq!(file.id(), id);
+
+            let loc = sources.locate(tk.span.fragment()).unwrap();
+            assert_eq!(loc.line, tk.span.location_line() as usize);
+
+            if let Kind::Tuple(inner) | Kind::Block(inner) | Kind::Array(inner) = &tk.kind {
+                check(inner, sources, id);
+            }
+        }
+    }
+
+    let mut sources = SourceMap::default();
+    let old_text = sources.add_virtual("buffer", INCREMENTAL_SRC.to_string());
+    let (_, old) = tokenize(Span::new(old_text)).unwrap();
+
+    let at = INCREMENTAL_SRC.find('7').unwrap();
+    let mut text = INCREMENTAL_SRC.to_string();
+    text.replace_range(at..at + 1, "8\n");
+    let new_text = sources.add_virtual("buffer", text);
+    let id = new_text.origin().source().unwrap();
+
+    let new = apply(old, new_text, at..at + 1, "8\n");
+    check(&new, &sources, id);
+}
+
+#[test]
+fn retokenize_falls_back_to_parent_group() {
+    let at = INCREMENTAL_SRC.find('2').unwrap();
+    edit(INCREMENTAL_SRC, at..at, ") + (");
+}
+
+#[test]
+fn retokenize_top_level_edit() {
+    edit(INCREMENTAL_SRC, 0..1, "long_name");
+    let end = INCREMENTAL_SRC.len();
+    edit(INCREMENTAL_SRC, end..end, "h :: { 4 }");
+}
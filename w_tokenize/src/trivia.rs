@@ -0,0 +1,281 @@
+use crate::{block_comment, line_comment, tokenize, Kind, Span, TokResult, Token};
+use nom::branch::alt;
+use nom::bytes::complete::take_while1;
+use nom::combinator::map;
+use nom::{InputTake, Offset, Slice};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,
+    /// `// TEXT`, without the line break
+    LineComment,
+    /// `/* TEXT */`, may be nested
+    BlockComment,
+}
+
+#[derive(Debug, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub span: Span,
+}
+
+/// Trivia attached to a token by [`tokenize_lossless`].
+///
+/// Trailing trivia runs up to and including the next line break,
+/// everything after that is leading trivia of the following token.
+#[derive(Debug, Clone, Default)]
+pub struct TokenTrivia {
+    pub leading: Vec<Trivia>,
+    pub trailing: Vec<Trivia>,
+    /// Only present on `Tuple`, `Block` and `Array` tokens
+    pub group: Option<GroupTrivia>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GroupTrivia {
+    /// Trivia directly after the opening delimiter
+    pub open_trailing: Vec<Trivia>,
+    /// Trivia directly before the closing delimiter
+    pub close_leading: Vec<Trivia>,
+    /// The closing delimiter itself
+    pub close: Span,
+}
+
+/// A token stream where every byte of the source is owned by a token or by [`Self::eof`]
+#[derive(Debug, Clone)]
+pub struct LosslessTokens {
+    pub tokens: Vec<Token>,
+    /// Trivia after the last line containing a token
+    pub eof: Vec<Trivia>,
+}
+
+/// Like [`tokenize`] but keeps all whitespace and comments as [`TokenTrivia`]
+pub fn tokenize_lossless(file: Span) -> TokResult<LosslessTokens> {
+    let (i, tokens) = tokenize(file.clone())?;
+
+    let mut atoms = vec![];
+    collect_atoms(&tokens, &mut atoms);
+
+    let mut pos = file.location_offset();
+    let mut gaps = vec![];
+    for (start, end) in atoms {
+        gaps.push(lex_trivia(&file, pos, start));
+        pos = end;
+    }
+    gaps.push(lex_trivia(&file, pos, file.location_offset() + file.len()));
+
+    // the gap in front of the first atom is entirely leading trivia,
+    // every other gap is split between the atoms surrounding it
+    let mut gaps = gaps.into_iter();
+    let mut leading = vec![gaps.next().unwrap()];
+    let mut trailing = vec![];
+    for gap in gaps {
+        let (t, l) = split_gap(gap);
+        trailing.push(t);
+        leading.push(l);
+    }
+    let eof = leading.pop().unwrap();
+
+    let mut trivia = leading.into_iter().zip(trailing);
+    let tokens = attach(&tokens, &mut trivia);
+
+    Ok((i, LosslessTokens { tokens, eof }))
+}
+
+impl LosslessTokens {
+    /// Reassembles the original source text
+    pub fn to_source(&self) -> String {
+        let mut out = String::new();
+        for token in &self.tokens {
+            token.write_source(&mut out);
+        }
+        push_trivia(&mut out, &self.eof);
+        out
+    }
+}
+
+impl Token {
+    /// Writes the token with its trivia, requires a token from [`tokenize_lossless`]
+    pub fn write_source(&self, out: &mut String) {
+        let trivia = self
+            .trivia
+            .as_deref()
+            .expect("token was not produced by tokenize_lossless");
+        push_trivia(out, &trivia.leading);
+
+        match (&self.kind, &trivia.group) {
+            (Kind::Tuple(inner) | Kind::Block(inner) | Kind::Array(inner), Some(group)) => {
+                out.push_str(&self.span[..1]);
+                push_trivia(out, &group.open_trailing);
+                for token in inner.iter() {
+                    token.write_source(out);
+                }
+                push_trivia(out, &group.close_leading);
+                out.push_str(&group.close);
+            }
+            _ => out.push_str(&self.span),
+        }
+
+        push_trivia(out, &trivia.trailing);
+    }
+}
+
+fn push_trivia(out: &mut String, trivia: &[Trivia]) {
+    for t in trivia {
+        out.push_str(&t.span);
+    }
+}
+
+/// Flattens the tree into the source ranges not covered by trivia,
+/// groups contribute their opening and closing delimiter
+fn collect_atoms(tokens: &[Token], out: &mut Vec<(usize, usize)>) {
+    for token in tokens {
+        let start = token.span.location_offset();
+        let end = start + token.span.len();
+
+        match &token.kind {
+            Kind::Tuple(inner) | Kind::Block(inner) | Kind::Array(inner) => {
+                out.push((start, start + 1));
+                collect_atoms(inner, out);
+                out.push((end - 1, end));
+            }
+            _ => out.push((start, end)),
+        }
+    }
+}
+
+type AtomTrivia = (Vec<Trivia>, Vec<Trivia>);
+
+/// Hands out the trivia in the same order [`collect_atoms`] visited the atoms
+fn attach(tokens: &[Token], trivia: &mut impl Iterator<Item = AtomTrivia>) -> Vec<Token> {
+    tokens
+        .iter()
+        .map(|token| {
+            let (leading, trailing) = trivia.next().expect("atom count mismatch");
+            let mut token = token.clone();
+
+            let inner = match &token.kind {
+                Kind::Tuple(inner) | Kind::Block(inner) | Kind::Array(inner) => {
+                    Some(Rc::from(attach(inner, trivia)))
+                }
+                _ => None,
+            };
+
+            let (trailing, group) = match inner {
+                Some(inner) => {
+                    token.kind = match token.kind {
+                        Kind::Tuple(_) => Kind::Tuple(inner),
+                        Kind::Block(_) => Kind::Block(inner),
+                        Kind::Array(_) => Kind::Array(inner),
+                        _ => unreachable!(),
+                    };
+
+                    let (close_leading, close_trailing) =
+                        trivia.next().expect("atom count mismatch");
+                    let close = token.span.slice(token.span.len() - 1..);
+
+                    (
+                        close_trailing,
+                        Some(GroupTrivia {
+                            open_trailing: trailing,
+                            close_leading,
+                            close,
+                        }),
+                    )
+                }
+                None => (trailing, None),
+            };
+
+            token.trivia = Some(Box::new(TokenTrivia {
+                leading,
+                trailing,
+                group,
+            }));
+            token
+        })
+        .collect()
+}
+
+/// Trivia for the freshly tokenized contents of a group spanning `open..=close`,
+/// returns the contents together with the trivia after the opening and before the closing delimiter
+pub(crate) fn attach_group(
+    file: &Span,
+    open: usize,
+    close: usize,
+    inner: &[Token],
+) -> (Vec<Token>, Vec<Trivia>, Vec<Trivia>) {
+    let mut atoms = vec![(open, open + 1)];
+    collect_atoms(inner, &mut atoms);
+    atoms.push((close, close + 1));
+
+    let mut gaps = atoms
+        .windows(2)
+        .map(|pair| split_gap(lex_trivia(file, pair[0].1, pair[1].0)));
+    let (open_trailing, mut leading) = gaps.next().unwrap();
+
+    let mut trivia = vec![];
+    for (trailing, next) in gaps {
+        trivia.push((std::mem::replace(&mut leading, next), trailing));
+    }
+    let inner = attach(inner, &mut trivia.into_iter());
+
+    (inner, open_trailing, leading)
+}
+
+fn lex_trivia(file: &Span, start: usize, end: usize) -> Vec<Trivia> {
+    let base = file.location_offset();
+    let mut i = file.slice(start - base..end - base);
+    let mut out = vec![];
+
+    while !i.is_empty() {
+        let (ni, kind) = alt((
+            map(take_while1(char::is_whitespace), |_| TriviaKind::Whitespace),
+            map(line_comment, |_| TriviaKind::LineComment),
+            map(block_comment, |_| TriviaKind::BlockComment),
+        ))(i.clone())
+        .expect("the tokenizer only skips whitespace and comments");
+
+        let span = i.slice(..i.offset(&ni));
+        out.push(Trivia { kind, span });
+        i = ni;
+    }
+
+    out
+}
+
+/// Splits the trivia between two tokens into the trailing trivia of the first
+/// and the leading trivia of the second
+fn split_gap(gap: Vec<Trivia>) -> (Vec<Trivia>, Vec<Trivia>) {
+    let mut trailing = vec![];
+    let mut rest = gap.into_iter();
+
+    for t in rest.by_ref() {
+        let newline = t.span.find('\n');
+        match (t.kind, newline) {
+            (_, None) => trailing.push(t),
+            (TriviaKind::Whitespace, Some(idx)) => {
+                let (next_line, line_end) = t.span.take_split(idx + 1);
+                trailing.push(Trivia {
+                    kind: TriviaKind::Whitespace,
+                    span: line_end,
+                });
+                let mut leading = vec![];
+                if !next_line.is_empty() {
+                    leading.push(Trivia {
+                        kind: TriviaKind::Whitespace,
+                        span: next_line,
+                    });
+                }
+                leading.extend(rest);
+                return (trailing, leading);
+            }
+            // multi line block comments belong to the next token
+            (_, Some(_)) => {
+                return (trailing, std::iter::once(t).chain(rest).collect());
+            }
+        }
+    }
+
+    (trailing, vec![])
+}
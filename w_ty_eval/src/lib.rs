@@ -8,24 +8,21 @@ use bevy_ecs::world::World;
 
 use std::collections::HashMap;
 
-use w_analyze::data::err::{DuplicateImport, ErrorCollector};
+use w_analyze::data::err::{DuplicateImport, ErrorCollector, ImportError, ImportErrorKind};
 use w_analyze::data::md_raw::RawModuleInfo;
-use w_analyze::data::path::PathBuf;
+use w_analyze::data::path::{Path, PathBuf};
 
-use w_parse::expr::path::ExprPath;
+use w_parse::expr::path::{ExprPath, PathRoot};
 use w_parse::item::import::{Imports, ItemImports};
 use w_parse::item::named::{ItemNamed, ItemNamedType, NamedKind};
 use w_parse::item::Item;
 use w_parse::types::r#struct::TyStruct;
 use w_parse::types::ItemTy;
-use w_parse::Ident;
+use w_parse::{Ident, Symbol};
 
 pub struct VmState {
     types: HashMap<PathBuf, Entity>,
 
-    root: Ident,
-    missing: Vec<PathBuf>,
-
     errs: ErrorCollector,
 
     world: World,
@@ -43,6 +40,7 @@ impl VmState {
                 for imp in imports {
                     flatten_imports(
                         &mut scope.imports,
+                        &scope.current,
                         module.origin.clone(),
                         Some(from),
                         imp,
@@ -57,6 +55,7 @@ impl VmState {
                 Item::Definer(ItemNamed {
                     name,
                     kind: NamedKind::Type(named_ty),
+                    ..
                 }) => {
                     let mut entity = self.world.spawn();
 
@@ -83,7 +82,9 @@ impl VmState {
     ) {
         match ty {
             ItemTy::Referred(other) => {
-                let other = self.resolve_path(other, scope);
+                let Some(other) = self.resolve_path(other, scope) else {
+                    return;
+                };
 
                 let path = self.world.get::<WithPath>(ety).unwrap().0.to_string();
 
@@ -103,6 +104,7 @@ impl VmState {
                     .collect::<Vec<_>>();
             }
             ItemTy::Enum(_) => todo!(),
+            ItemTy::Applied(_) => {}
             ItemTy::Tuple(_) => {}
             ItemTy::Func(_) => {}
             ItemTy::Array(_) => {}
@@ -111,22 +113,24 @@ impl VmState {
         }
     }
 
-    fn resolve_path(&mut self, path: ExprPath, scope: &Scope) -> PathBuf {
+    /// `None` if the path goes above the root module, which is reported
+    fn resolve_path(&mut self, path: ExprPath, scope: &Scope) -> Option<PathBuf> {
         let buf = PathBuf::from(path.path);
-        let buf = if path.root.is_some() {
-            buf
-        } else if let Some(import) = scope.imports.get(buf.first().unwrap()) {
+        let buf = if let Some(root) = &path.root {
+            rooted(root, &scope.current, &self.errs)?.join_path(&buf)
+        } else if let Some((_, import)) = scope.imports.get(buf.first().unwrap()) {
             import.join_path(&buf)
         } else {
             scope.current.join_path(&buf)
         };
-        buf
+        Some(buf)
     }
 }
 
 struct Scope {
     current: PathBuf,
-    imports: HashMap<Ident, PathBuf>,
+    /// The imported name is kept around for diagnostics
+    imports: HashMap<Symbol, (Ident, PathBuf)>,
 }
 
 #[derive(Component)]
@@ -156,47 +160,64 @@ pub enum IncompleteType {
     },
 }
 
-mod marker {
-    use bevy_ecs::component::Component;
-
-    #[derive(Component)]
-    pub struct Incomplete;
+/// The module a rooted path starts at, `current` is the module the path is written in.
+///
+/// `None` if `super` goes above the root module, which is reported.
+fn rooted(root: &PathRoot, current: &Path, errs: &ErrorCollector) -> Option<PathBuf> {
+    match root.depth() {
+        None => Some(PathBuf::default()),
+        Some(depth) if depth <= current.len() => {
+            Some(current.slice(..current.len() - depth).to_owned())
+        }
+        Some(_) => {
+            errs.add_error(ImportError {
+                loc: root.span().clone(),
+                kind: ImportErrorKind::AboveRoot,
+            });
+            None
+        }
+    }
 }
 
 fn flatten_imports(
-    out: &mut HashMap<Ident, PathBuf>,
+    out: &mut HashMap<Symbol, (Ident, PathBuf)>,
+    current: &Path,
     rel_root: PathBuf,
     base: Option<&ExprPath>,
     imp: &Imports,
     errs: &ErrorCollector,
 ) {
-    let base = base
-        .map(|base| {
-            if base.root.is_some() {
-                PathBuf::from(base.path.clone())
-            } else {
-                rel_root.join_path(&PathBuf::from(base.path.clone()))
-            }
-        })
-        .unwrap_or_else(|| rel_root.to_owned());
+    let base = match base {
+        Some(base) => {
+            let start = match &base.root {
+                Some(root) => match rooted(root, current, errs) {
+                    Some(start) => start,
+                    None => return,
+                },
+                None => rel_root,
+            };
+            start.join_path(&PathBuf::from(base.path.as_slice()))
+        }
+        None => rel_root,
+    };
 
     match imp {
         Imports::Single(pt) => {
-            let tp = PathBuf::from(pt.path.clone());
-            let imp = base.join_path(&tp);
-            if let Some(og) = out.get(imp.last().unwrap()) {
+            let name = pt.path.last().unwrap();
+            let imp = base.join_path(&PathBuf::from(pt.path.as_slice()));
+            if let Some((og, _)) = out.get(&name.sym()) {
                 errs.add_error(DuplicateImport {
-                    original: og.last().unwrap().0.clone(),
-                    new: imp.last().unwrap().0.clone(),
+                    original: og.0.clone(),
+                    new: name.0.clone(),
                 });
                 return;
             }
-            out.insert(imp.last().unwrap().clone(), imp);
+            out.insert(name.sym(), (name.clone(), imp));
         }
         Imports::Multiple(sub_base, other) => {
-            let base = base.join_path(&PathBuf::from(sub_base.path.clone()));
+            let base = base.join_path(&PathBuf::from(sub_base.path.as_slice()));
             for imp in other {
-                flatten_imports(out, base.clone(), None, imp, errs);
+                flatten_imports(out, current, base.clone(), None, imp, errs);
             }
         }
     }
@@ -1,43 +1,107 @@
 #![cfg_attr(debug_assertions, allow(dead_code))]
 
-use ariadne::{ColorGenerator, Label, Report, ReportKind, Source};
-use w_rcstr::{Origin, RcStr};
-use w_tokenize::{tokenize, Kind, Span};
+use ariadne::{ColorGenerator, Label, Report, ReportKind};
+use std::fs;
+use std::ops::Range;
+use std::path::PathBuf;
+use w_rcstr::SourceMap;
+use w_tokenize::{tokenize_recovering, Kind, Span};
+
+const PROJECT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../WIP_tests/mod_test");
 
 fn main() {
-    let str = RcStr::new(
-        include_str!("../../WIP_tests/mod_test/main.w").to_string(),
-        Origin::Unknown,
+    let mut sources = SourceMap::default();
+
+    let mut paths = std::env::args()
+        .skip(1)
+        .map(PathBuf::from)
+        .collect::<Vec<_>>();
+    if paths.is_empty() {
+        paths = project_files();
+    }
+
+    let files = paths
+        .iter()
+        .map(|path| {
+            sources
+                .load_file(path)
+                .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()))
+        })
+        .collect::<Vec<_>>();
+
+    let mut cache = ariadne::sources(
+        sources
+            .files()
+            .map(|file| (file.name().to_string(), file.text().to_string())),
     );
-    let file = Span::new(str.clone());
-    let (_, tokens) = tokenize(file).unwrap();
+
+    let mut trees = vec![];
+    let mut failed = false;
+    for file in &files {
+        let (tokens, errors) = tokenize_recovering(Span::new(file.clone()));
+
+        for err in errors {
+            let (name, range) = label(&sources, &err.span);
+            Report::build(ReportKind::Error, name.clone(), range.start)
+                .with_message(err.reason.as_deref().unwrap_or("Invalid token"))
+                .with_label(Label::new((name, range)))
+                .finish()
+                .print(&mut cache)
+                .unwrap();
+            failed = true;
+        }
+
+        trees.push(tokens);
+    }
+
+    if failed {
+        return;
+    }
     // let (_, parsed) = parse(TokenSpan::new(file, Rc::from(tokens))).unwrap();
 
     let mut colors = ColorGenerator::new();
 
-    let mut rpb = Report::build(ReportKind::Error, "main.w", 0).with_code("oh snap");
+    let entry = sources.source_of(&files[0]).unwrap().name().to_string();
+    let mut rpb = Report::build(ReportKind::Error, entry, 0).with_code("oh snap");
 
     let blockc = colors.next();
 
-    for tk in tokens.into_iter().skip(1) {
+    for tk in trees.swap_remove(0).into_iter().skip(1) {
         if !matches!(tk.kind, Kind::Block(_)) {
             continue;
         }
 
-        let span = tk.span;
-
         rpb = rpb.with_label(
-            Label::new((
-                "main.w",
-                span.location_offset()..span.location_offset() + span.len(),
-            ))
-            .with_message("that's a block of tokens")
-            .with_color(blockc),
+            Label::new(label(&sources, &tk.span))
+                .with_message("that's a block of tokens")
+                .with_color(blockc),
         );
     }
     rpb.with_message("well something happened here")
         .with_note("your program sucks")
         .finish()
-        .print(("main.w", Source::from(&*str)))
+        .print(&mut cache)
         .unwrap();
 }
+
+/// All `.w` files of the sample project, `main.w` first
+fn project_files() -> Vec<PathBuf> {
+    let mut paths = fs::read_dir(PROJECT)
+        .expect("sample project is missing")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "w"))
+        .collect::<Vec<_>>();
+    paths.sort_by_key(|path| (!path.ends_with("main.w"), path.clone()));
+    paths
+}
+
+fn label(sources: &SourceMap, span: &Span) -> (String, Range<usize>) {
+    let file = sources
+        .source_of(span.fragment())
+        .expect("span of an unregistered source");
+    let range = span.fragment().range();
+    (
+        file.name().to_string(),
+        range.start..range.end.max(range.start + 1),
+    )
+}